@@ -0,0 +1,77 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use polymarket_arbitrage_bot::arbitrage::{ArbitrageDetector, LegCombination};
+use polymarket_arbitrage_bot::models::{MarketData, TokenPrice};
+use polymarket_arbitrage_bot::monitor::MarketSnapshot;
+use rust_decimal_macros::dec;
+
+/// A token price with a plausible bid/ask spread, close enough to $1 total
+/// (paired with its opposite leg) to land in the hot path `check_arbitrage`
+/// actually walks through, rather than being rejected up front on a wildly
+/// off price.
+fn token_price(token_id: &str, bid: rust_decimal::Decimal, ask: rust_decimal::Decimal) -> TokenPrice {
+    TokenPrice {
+        token_id: token_id.to_string(),
+        bid: Some(bid),
+        ask: Some(ask),
+        smoothed_bid: Some(bid),
+        smoothed_ask: Some(ask),
+        is_midpoint_derived: false,
+        last: Some(ask),
+    }
+}
+
+fn representative_snapshot() -> MarketSnapshot {
+    MarketSnapshot {
+        sol_market: MarketData {
+            condition_id: "sol-cond".to_string(),
+            market_name: "SOL Up/Down 15m".to_string(),
+            up_token: Some(token_price("sol-up", dec!(0.47), dec!(0.48))),
+            down_token: Some(token_price("sol-down", dec!(0.51), dec!(0.52))),
+        },
+        btc_market: MarketData {
+            condition_id: "btc-cond".to_string(),
+            market_name: "BTC Up/Down 15m".to_string(),
+            up_token: Some(token_price("btc-up", dec!(0.46), dec!(0.47))),
+            down_token: Some(token_price("btc-down", dec!(0.50), dec!(0.51))),
+        },
+        timestamp: 1_700_000_000,
+    }
+}
+
+/// A period end far enough out that the interpolated threshold sits at its
+/// "early" end, matching the common case of detection running throughout an
+/// open period rather than only in the closing seconds.
+const PERIOD_END_UNIX: u64 = 4_102_444_800;
+
+fn bench_single_pair(c: &mut Criterion) {
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    let detector = ArbitrageDetector::new(0.01);
+    let snapshot = representative_snapshot();
+
+    c.bench_function("detect_opportunities_single_pair", |b| {
+        b.iter(|| rt.block_on(detector.detect_opportunities(black_box(&snapshot), black_box(PERIOD_END_UNIX))));
+    });
+}
+
+/// Once the bot supports more than the fixed SOL/BTC pair, detection will
+/// evaluate a wider set of leg combinations per snapshot. `with_leg_combinations`
+/// already accepts an arbitrary list, so this benchmarks that same hot path
+/// scaled up as a proxy for the eventual multi-asset case.
+fn bench_multi_asset(c: &mut Criterion) {
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    let combinations = vec![
+        LegCombination::new(true, false),
+        LegCombination::new(false, true),
+        LegCombination::new(true, true),
+        LegCombination::new(false, false),
+    ];
+    let detector = ArbitrageDetector::new(0.01).with_leg_combinations(combinations);
+    let snapshot = representative_snapshot();
+
+    c.bench_function("detect_opportunities_multi_leg_combination", |b| {
+        b.iter(|| rt.block_on(detector.detect_opportunities(black_box(&snapshot), black_box(PERIOD_END_UNIX))));
+    });
+}
+
+criterion_group!(benches, bench_single_pair, bench_multi_asset);
+criterion_main!(benches);
@@ -0,0 +1,23 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use polymarket_arbitrage_bot::trader::{settlement_profit, LegResult};
+
+fn bench_settlement_profit(c: &mut Criterion) {
+    c.bench_function("settlement_profit_both_legs_resolved", |b| {
+        b.iter(|| {
+            settlement_profit(
+                black_box(100.0),
+                black_box(200.0),
+                black_box(0.05),
+                black_box(0.48),
+                black_box(0.51),
+                black_box(LegResult::Won),
+                black_box(LegResult::Lost),
+                black_box(true),
+                black_box(true),
+            )
+        });
+    });
+}
+
+criterion_group!(benches, bench_settlement_profit);
+criterion_main!(benches);
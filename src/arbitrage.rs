@@ -2,25 +2,307 @@ use crate::models::*;
 use crate::monitor::MarketSnapshot;
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+/// Strategy name for SOL Up + BTC Down, used to identify it in runtime
+/// enable/disable requests (e.g. from a future status/control endpoint).
+pub const STRATEGY_SOL_UP_BTC_DOWN: &str = "sol_up_btc_down";
+/// Strategy name for SOL Down + BTC Up.
+pub const STRATEGY_SOL_DOWN_BTC_UP: &str = "sol_down_btc_up";
+
+/// Asset name identifying the SOL market in runtime enable/disable requests.
+pub const ASSET_SOL: &str = "sol";
+/// Asset name identifying the BTC market in runtime enable/disable requests.
+pub const ASSET_BTC: &str = "btc";
+
+/// A (SOL outcome, BTC outcome) pair to buy together as a leg combination.
+/// The detector evaluates whichever combinations it's configured with
+/// instead of only the two fixed hedges, so a user with a directional view
+/// (e.g. expecting SOL and BTC to move together) can configure any pairing,
+/// including the same side of both.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LegCombination {
+    pub sol_up: bool,
+    pub btc_up: bool,
+}
+
+impl LegCombination {
+    pub fn new(sol_up: bool, btc_up: bool) -> Self {
+        Self { sol_up, btc_up }
+    }
+
+    /// Strategy name this combination is identified by in runtime
+    /// enable/disable requests and on `ArbitrageOpportunity::strategy`, e.g.
+    /// "sol_up_btc_down".
+    fn strategy_name(&self) -> String {
+        format!(
+            "sol_{}_btc_{}",
+            if self.sol_up { "up" } else { "down" },
+            if self.btc_up { "up" } else { "down" }
+        )
+    }
+}
+
+/// The two hedges the bot has always traded, used when a detector is built
+/// without explicit leg combinations via `with_leg_combinations`.
+fn default_leg_combinations() -> Vec<LegCombination> {
+    let sol_up_btc_down = LegCombination::new(true, false);
+    let sol_down_btc_up = LegCombination::new(false, true);
+    debug_assert_eq!(sol_up_btc_down.strategy_name(), STRATEGY_SOL_UP_BTC_DOWN);
+    debug_assert_eq!(sol_down_btc_up.strategy_name(), STRATEGY_SOL_DOWN_BTC_UP);
+    vec![sol_up_btc_down, sol_down_btc_up]
+}
+
+/// Which strategies are currently allowed to produce opportunities, keyed by
+/// strategy name. Held behind a lock so it can be toggled at runtime (e.g.
+/// by an operator disabling a strategy mid-incident) without restarting the
+/// detector.
+fn enabled_map_for(combinations: &[LegCombination]) -> HashMap<String, bool> {
+    combinations.iter().map(|combo| (combo.strategy_name(), true)).collect()
+}
+
+/// Which assets are currently allowed to produce opportunities, keyed by
+/// asset name (`ASSET_SOL`/`ASSET_BTC`). An operator disabling one asset -
+/// e.g. its market has bad liquidity or a delayed resolution - stops it
+/// from being traded in either leg combination without touching the other
+/// asset. The monitor keeps polling a disabled asset's prices regardless;
+/// this only affects whether the detector will produce opportunities using
+/// it.
+fn default_enabled_assets() -> HashMap<String, bool> {
+    [(ASSET_SOL.to_string(), true), (ASSET_BTC.to_string(), true)].into_iter().collect()
+}
+
+/// Default market period length in seconds (15 minutes), used when a
+/// detector is built without an explicit period via `new`.
+const DEFAULT_PERIOD_SECS: u64 = 900;
 
 #[derive(Clone)]
 pub struct ArbitrageDetector {
-    min_profit_threshold: Decimal,
+    /// Threshold used right after a period opens (maximum time-to-close).
+    early_threshold: Decimal,
+    /// Threshold used right before a period closes (minimum time-to-close).
+    late_threshold: Decimal,
+    /// Length of a market period in seconds, used to normalize
+    /// time-to-close when scaling the profit threshold.
+    period_secs: u64,
+    /// When true, an opportunity must also clear the profit threshold using
+    /// each token's smoothed (EMA) ask price, not just its raw ask price,
+    /// filtering out phantom opportunities caused by a single anomalous
+    /// tick. Raw prices are still used to size and report the opportunity.
+    require_smoothed_confirmation: bool,
+    /// Leg combinations evaluated by `detect_opportunities`, in order.
+    leg_combinations: Vec<LegCombination>,
+    enabled_strategies: Arc<RwLock<HashMap<String, bool>>>,
+    enabled_assets: Arc<RwLock<HashMap<String, bool>>>,
+    /// Minimum acceptable combined ask cost for the two legs, below which an
+    /// opportunity is rejected as suspiciously cheap (illiquid/stale book)
+    /// rather than a real edge.
+    min_total_cost: Decimal,
+    /// Maximum acceptable combined ask cost for the two legs. Independent of
+    /// `min_profit_threshold`; lets an operator reject edges too thin to
+    /// survive fees/slippage even if they'd technically clear the profit
+    /// threshold.
+    max_total_cost: Decimal,
+    /// Opt-in sanity check: when set, an opportunity is rejected unless each
+    /// leg's ask falls within this fraction of that token's last traded
+    /// price. `None` (the default) disables the check.
+    last_trade_price_band_pct: Option<Decimal>,
+    /// Lower threshold, independent of `early_threshold`/`late_threshold`,
+    /// above which a too-thin-to-trade opportunity is still logged as a
+    /// near-miss. `None` (the default) disables near-miss logging.
+    log_profit_threshold: Option<Decimal>,
+}
+
+/// Fraction the combined ask cost could rise before `expected_profit` is
+/// wiped out, i.e. `expected_profit / total_cost`. Guards against a
+/// zero-cost book (which shouldn't reach here given the sane-price band
+/// enforced upstream) by returning zero rather than dividing by zero.
+fn breakeven_price_move_pct(expected_profit: Decimal, total_cost: Decimal) -> Decimal {
+    expected_profit.checked_div(total_cost).unwrap_or(dec!(0))
+}
+
+/// Linearly interpolate between `late` (at `time_to_close_secs == 0`) and
+/// `early` (at `time_to_close_secs >= period_secs`).
+fn interpolate_threshold(early: Decimal, late: Decimal, time_to_close_secs: u64, period_secs: u64) -> Decimal {
+    let clamped = time_to_close_secs.min(period_secs);
+    let fraction = Decimal::from(clamped) / Decimal::from(period_secs.max(1));
+    late + (early - late) * fraction
 }
 
 impl ArbitrageDetector {
     pub fn new(min_profit_threshold: f64) -> Self {
+        Self::with_time_scaled_threshold(min_profit_threshold, min_profit_threshold, DEFAULT_PERIOD_SECS)
+    }
+
+    /// Build a detector whose minimum profit threshold scales linearly with
+    /// time-to-close within a `period_secs`-long market period:
+    /// `early_threshold` applies right after a period opens (more time for
+    /// an adverse move), `late_threshold` applies right before it closes
+    /// (thinner edges are safer to take as resolution is imminent). Pass
+    /// equal values to fall back to a constant threshold.
+    pub fn with_time_scaled_threshold(early_threshold: f64, late_threshold: f64, period_secs: u64) -> Self {
+        let leg_combinations = default_leg_combinations();
+        let enabled_strategies = Arc::new(RwLock::new(enabled_map_for(&leg_combinations)));
+        Self {
+            early_threshold: Decimal::from_f64_retain(early_threshold).unwrap_or(dec!(0.01)),
+            late_threshold: Decimal::from_f64_retain(late_threshold).unwrap_or(dec!(0.01)),
+            period_secs,
+            require_smoothed_confirmation: false,
+            leg_combinations,
+            enabled_strategies,
+            enabled_assets: Arc::new(RwLock::new(default_enabled_assets())),
+            min_total_cost: dec!(0.0),
+            max_total_cost: dec!(1.0),
+            last_trade_price_band_pct: None,
+            log_profit_threshold: None,
+        }
+    }
+
+    /// Set the initial per-asset enabled state (`ASSET_SOL`/`ASSET_BTC`),
+    /// e.g. from config at startup. Both default to enabled; call this only
+    /// to start with one disabled. Toggling afterward goes through
+    /// `set_asset_enabled`, the same runtime control surface used for
+    /// strategies.
+    pub fn with_assets_enabled(self, sol_enabled: bool, btc_enabled: bool) -> Self {
+        let assets = [(ASSET_SOL.to_string(), sol_enabled), (ASSET_BTC.to_string(), btc_enabled)].into_iter().collect();
         Self {
-            min_profit_threshold: Decimal::from_f64_retain(min_profit_threshold)
-                .unwrap_or(dec!(0.01)),
+            enabled_assets: Arc::new(RwLock::new(assets)),
+            ..self
         }
     }
 
+    /// Configure which (SOL outcome, BTC outcome) combinations
+    /// `detect_opportunities` evaluates, replacing the default SOL-Up/BTC-Down
+    /// and SOL-Down/BTC-Up hedges. Resets the runtime enable/disable state:
+    /// every configured combination starts enabled.
+    pub fn with_leg_combinations(mut self, combinations: Vec<LegCombination>) -> Self {
+        self.enabled_strategies = Arc::new(RwLock::new(enabled_map_for(&combinations)));
+        self.leg_combinations = combinations;
+        self
+    }
+
+    /// Set the initial enabled state of the two canonical hedges
+    /// (`STRATEGY_SOL_UP_BTC_DOWN`/`STRATEGY_SOL_DOWN_BTC_UP`) by name, e.g.
+    /// from config at startup, so a user with a directional view can trade
+    /// only one side without the full pluggable-strategy machinery. Both
+    /// default to enabled; call this only to start with one disabled.
+    /// Toggling afterward goes through `set_strategy_enabled`, the same
+    /// runtime control surface. A no-op for any strategy name other than the
+    /// two canonical ones, so it's harmless to call alongside
+    /// `with_leg_combinations` with custom combinations.
+    pub fn with_strategies_enabled(self, sol_up_btc_down_enabled: bool, sol_down_btc_up_enabled: bool) -> Self {
+        let mut strategies = enabled_map_for(&self.leg_combinations);
+        if let Some(flag) = strategies.get_mut(STRATEGY_SOL_UP_BTC_DOWN) {
+            *flag = sol_up_btc_down_enabled;
+        }
+        if let Some(flag) = strategies.get_mut(STRATEGY_SOL_DOWN_BTC_UP) {
+            *flag = sol_down_btc_up_enabled;
+        }
+        Self {
+            enabled_strategies: Arc::new(RwLock::new(strategies)),
+            ..self
+        }
+    }
+
+    /// Bracket acceptable combined ask cost for the two legs between
+    /// `min_total_cost` and `max_total_cost`, independent of
+    /// `min_profit_threshold`. Defaults to `(0.0, 1.0)`, i.e. no additional
+    /// restriction beyond the existing "total cost under a dollar" check.
+    pub fn with_total_cost_bounds(mut self, min_total_cost: f64, max_total_cost: f64) -> Self {
+        self.min_total_cost = Decimal::from_f64_retain(min_total_cost).unwrap_or(dec!(0.0));
+        self.max_total_cost = Decimal::from_f64_retain(max_total_cost).unwrap_or(dec!(1.0));
+        self
+    }
+
+    /// Require the smoothed (EMA) total cost to also clear the profit
+    /// threshold before an opportunity is produced, on top of the raw-price
+    /// check. Off by default to preserve prior behavior.
+    pub fn with_smoothed_confirmation(mut self, require: bool) -> Self {
+        self.require_smoothed_confirmation = require;
+        self
+    }
+
+    /// Reject a leg whose ask strays more than `band_pct` from that token's
+    /// last traded price, e.g. `Some(0.05)` allows up to 5% either way.
+    /// `None` (the default) disables the check. Catches a stale or
+    /// manipulated quote that's wildly out of line with what actually just
+    /// traded; see `TokenPrice::is_within_last_trade_band`.
+    pub fn with_last_trade_price_band(mut self, band_pct: Option<f64>) -> Self {
+        self.last_trade_price_band_pct = band_pct.and_then(Decimal::from_f64_retain);
+        self
+    }
+
+    /// Set the lower threshold above which a too-thin-to-trade opportunity
+    /// is still logged as a near-miss, e.g. to inform tuning of
+    /// `min_profit_threshold`/`late_profit_threshold`. `None` (the default)
+    /// disables near-miss logging.
+    pub fn with_log_profit_threshold(mut self, log_profit_threshold: Option<f64>) -> Self {
+        self.log_profit_threshold = log_profit_threshold.and_then(Decimal::from_f64_retain);
+        self
+    }
+
+    /// Enable or disable a strategy by name at runtime, taking effect on the
+    /// next `detect_opportunities` call. Intended to be called from an
+    /// operator-facing control surface (e.g. a status endpoint's POST
+    /// handler); returns `Err` naming the unknown strategy so the caller can
+    /// map it to a 400 response.
+    pub async fn set_strategy_enabled(&self, strategy_name: &str, enabled: bool) -> Result<(), String> {
+        let mut strategies = self.enabled_strategies.write().await;
+        match strategies.get_mut(strategy_name) {
+            Some(flag) => *flag = enabled,
+            None => return Err(format!("unknown strategy: {}", strategy_name)),
+        }
+        Ok(())
+    }
+
+    /// Enable or disable an asset (`ASSET_SOL`/`ASSET_BTC`) by name at
+    /// runtime, taking effect on the next `detect_opportunities` call. A
+    /// disabled asset's opportunities are skipped regardless of which
+    /// strategies are enabled; the monitor keeps fetching its prices for
+    /// observability. Returns `Err` naming the unknown asset.
+    pub async fn set_asset_enabled(&self, asset: &str, enabled: bool) -> Result<(), String> {
+        let mut assets = self.enabled_assets.write().await;
+        match assets.get_mut(asset) {
+            Some(flag) => *flag = enabled,
+            None => return Err(format!("unknown asset: {}", asset)),
+        }
+        Ok(())
+    }
+
     /// Detect arbitrage opportunities between SOL and BTC markets
     /// Strategy: Buy Up token in SOL market + Buy Down token in BTC market
     /// when total cost < $1
-    pub fn detect_opportunities(&self, snapshot: &MarketSnapshot) -> Vec<ArbitrageOpportunity> {
+    ///
+    /// `period_end_unix` is the unix timestamp at which the current
+    /// `period_secs`-long period closes; it's used to scale the minimum profit
+    /// threshold between `early_threshold` (just after open) and
+    /// `late_threshold` (just before close).
+    pub async fn detect_opportunities(
+        &self,
+        snapshot: &MarketSnapshot,
+        period_end_unix: u64,
+    ) -> Vec<ArbitrageOpportunity> {
         let mut opportunities = Vec::new();
+        let strategies = self.enabled_strategies.read().await.clone();
+        let assets = self.enabled_assets.read().await.clone();
+        let sol_enabled = assets.get(ASSET_SOL).copied().unwrap_or(true);
+        let btc_enabled = assets.get(ASSET_BTC).copied().unwrap_or(true);
+        if !sol_enabled || !btc_enabled {
+            log::debug!(
+                "Skipping opportunity detection: sol_enabled={} btc_enabled={}",
+                sol_enabled, btc_enabled
+            );
+            return opportunities;
+        }
+
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        let time_to_close = period_end_unix.saturating_sub(now);
+        let threshold = interpolate_threshold(self.early_threshold, self.late_threshold, time_to_close, self.period_secs);
 
         // Get prices from both markets
         let sol_up = snapshot.sol_market.up_token.as_ref();
@@ -28,37 +310,48 @@ impl ArbitrageDetector {
         let btc_up = snapshot.btc_market.up_token.as_ref();
         let btc_down = snapshot.btc_market.down_token.as_ref();
 
-        // Strategy 1: SOL Up + BTC Down
-        if let (Some(sol_up_price), Some(btc_down_price)) = (sol_up, btc_down) {
-            if let Some(opportunity) = self.check_arbitrage(
-                sol_up_price,
-                btc_down_price,
-                &snapshot.sol_market.condition_id,
-                &snapshot.btc_market.condition_id,
-                "SOL_UP",
-                "BTC_DOWN",
-            ) {
-                opportunities.push(opportunity);
+        for combo in &self.leg_combinations {
+            let strategy_name = combo.strategy_name();
+            if !strategies.get(&strategy_name).copied().unwrap_or(false) {
+                continue;
             }
-        }
 
-        // Strategy 2: SOL Down + BTC Up
-        if let (Some(sol_down_price), Some(btc_up_price)) = (sol_down, btc_up) {
-            if let Some(opportunity) = self.check_arbitrage(
-                sol_down_price,
-                btc_up_price,
-                &snapshot.sol_market.condition_id,
-                &snapshot.btc_market.condition_id,
-                "SOL_DOWN",
-                "BTC_UP",
-            ) {
-                opportunities.push(opportunity);
+            let (sol_price, sol_label) = if combo.sol_up { (sol_up, "SOL_UP") } else { (sol_down, "SOL_DOWN") };
+            let (btc_price, btc_label) = if combo.btc_up { (btc_up, "BTC_UP") } else { (btc_down, "BTC_DOWN") };
+
+            if let (Some(sol_price), Some(btc_price)) = (sol_price, btc_price) {
+                if let Some(mut opportunity) = self.check_arbitrage(
+                    sol_price,
+                    btc_price,
+                    &snapshot.sol_market.condition_id,
+                    &snapshot.btc_market.condition_id,
+                    sol_label,
+                    btc_label,
+                    &strategy_name,
+                    threshold,
+                ) {
+                    // Opposing outcome combination, for an optional tail
+                    // hedge - see `TradingConfig::tail_hedge_fraction`.
+                    let opposing_sol = if combo.sol_up { sol_down } else { sol_up };
+                    let opposing_btc = if combo.btc_up { btc_down } else { btc_up };
+                    opportunity.hedge_candidate = match (opposing_sol, opposing_btc) {
+                        (Some(sol), Some(btc)) => Some(HedgeCandidate {
+                            sol_token_id: sol.token_id.clone(),
+                            sol_price: sol.ask_price(),
+                            btc_token_id: btc.token_id.clone(),
+                            btc_price: btc.ask_price(),
+                        }),
+                        _ => None,
+                    };
+                    opportunities.push(opportunity);
+                }
             }
         }
 
         opportunities
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn check_arbitrage(
         &self,
         token1: &TokenPrice,
@@ -67,7 +360,37 @@ impl ArbitrageDetector {
         _condition2: &str,
         _label1: &str,
         _label2: &str,
+        strategy: &str,
+        min_profit_threshold: Decimal,
     ) -> Option<ArbitrageOpportunity> {
+        // Guard against crossed/locked books (bid >= ask), which are usually
+        // a data glitch at rollover rather than a real opportunity.
+        if token1.is_crossed() {
+            log::warn!("Skipping opportunity: {} book is crossed/locked (bid >= ask)", _label1);
+            return None;
+        }
+        if token2.is_crossed() {
+            log::warn!("Skipping opportunity: {} book is crossed/locked (bid >= ask)", _label2);
+            return None;
+        }
+
+        if let Some(band_pct) = self.last_trade_price_band_pct {
+            if !token1.is_within_last_trade_band(band_pct) {
+                log::warn!(
+                    "Skipping opportunity: {} ask ${} is outside the last-trade-price band (last={:?})",
+                    _label1, token1.ask_price(), token1.last
+                );
+                return None;
+            }
+            if !token2.is_within_last_trade_band(band_pct) {
+                log::warn!(
+                    "Skipping opportunity: {} ask ${} is outside the last-trade-price band (last={:?})",
+                    _label2, token2.ask_price(), token2.last
+                );
+                return None;
+            }
+        }
+
         let price1 = token1.ask_price();
         let price2 = token2.ask_price();
         let total_cost = price1 + price2;
@@ -80,12 +403,52 @@ impl ArbitrageDetector {
             return None;
         }
 
+        // Bracket the combined cost between the configured bounds, on top of
+        // the standard "total cost under a dollar" requirement.
+        if total_cost < self.min_total_cost || total_cost > self.max_total_cost {
+            return None;
+        }
+
         // Check if total cost is less than $1
         if total_cost < dollar {
             let expected_profit = dollar - total_cost;
-            
+
+            if expected_profit < min_profit_threshold {
+                if let Some(log_threshold) = self.log_profit_threshold {
+                    if expected_profit >= log_threshold {
+                        log::debug!(
+                            "👀 Near-miss: strategy={} total_cost=${:.4} expected_profit=${:.4} below trade threshold ${:.4} ({} + {})",
+                            strategy, total_cost, expected_profit, min_profit_threshold, _label1, _label2,
+                        );
+                    }
+                }
+            }
+
             // Only return if profit meets threshold
-            if expected_profit >= self.min_profit_threshold {
+            if expected_profit >= min_profit_threshold {
+                if self.require_smoothed_confirmation {
+                    let smoothed_cost = token1.smoothed_ask_price() + token2.smoothed_ask_price();
+                    let smoothed_profit = dollar - smoothed_cost;
+                    if smoothed_cost >= dollar || smoothed_profit < min_profit_threshold {
+                        log::debug!(
+                            "Skipping opportunity: raw edge ${:.4} did not survive smoothed confirmation (smoothed edge ${:.4})",
+                            expected_profit, smoothed_profit
+                        );
+                        return None;
+                    }
+                }
+
+                let breakeven_price_move_pct = breakeven_price_move_pct(expected_profit, total_cost);
+                log::info!(
+                    "🎯 Opportunity: strategy={} total_cost=${:.4} expected_profit=${:.4} breakeven_cushion={:.2}% ({} + {} could climb this much combined before breaking even)",
+                    strategy,
+                    total_cost,
+                    expected_profit,
+                    breakeven_price_move_pct * dec!(100),
+                    _label1,
+                    _label2,
+                );
+
                 return Some(ArbitrageOpportunity {
                     sol_up_price: price1,
                     btc_down_price: price2,
@@ -95,6 +458,10 @@ impl ArbitrageDetector {
                     btc_down_token_id: token2.token_id.clone(),
                     sol_condition_id: _condition1.to_string(),
                     btc_condition_id: _condition2.to_string(),
+                    is_midpoint_derived: token1.is_midpoint_derived || token2.is_midpoint_derived,
+                    strategy: strategy.to_string(),
+                    breakeven_price_move_pct,
+                    hedge_candidate: None,
                 });
             }
         }
@@ -103,3 +470,589 @@ impl ArbitrageDetector {
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::monitor::MarketSnapshot;
+
+    /// A period end far enough away that `interpolate_threshold` resolves
+    /// to (approximately) `early_threshold`.
+    fn mid_period_end() -> u64 {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        now + DEFAULT_PERIOD_SECS
+    }
+
+    fn token(token_id: &str, bid: Option<Decimal>, ask: Option<Decimal>) -> TokenPrice {
+        TokenPrice {
+            token_id: token_id.to_string(),
+            bid,
+            ask,
+            smoothed_bid: None,
+            smoothed_ask: None,
+            is_midpoint_derived: false,
+            last: None,
+        }
+    }
+
+    fn token_with_smoothed_ask(token_id: &str, ask: Decimal, smoothed_ask: Decimal) -> TokenPrice {
+        TokenPrice {
+            token_id: token_id.to_string(),
+            bid: None,
+            ask: Some(ask),
+            smoothed_bid: None,
+            smoothed_ask: Some(smoothed_ask),
+            is_midpoint_derived: false,
+            last: None,
+        }
+    }
+
+    fn token_with_last(token_id: &str, ask: Decimal, last: Decimal) -> TokenPrice {
+        TokenPrice {
+            token_id: token_id.to_string(),
+            bid: None,
+            ask: Some(ask),
+            smoothed_bid: None,
+            smoothed_ask: None,
+            is_midpoint_derived: false,
+            last: Some(last),
+        }
+    }
+
+    fn snapshot(sol_up: Option<TokenPrice>, btc_down: Option<TokenPrice>) -> MarketSnapshot {
+        MarketSnapshot {
+            sol_market: MarketData {
+                condition_id: "sol-cond".to_string(),
+                market_name: "SOL".to_string(),
+                up_token: sol_up,
+                down_token: None,
+            },
+            btc_market: MarketData {
+                condition_id: "btc-cond".to_string(),
+                market_name: "BTC".to_string(),
+                up_token: None,
+                down_token: btc_down,
+            },
+            timestamp: 0,
+        }
+    }
+
+    #[tokio::test]
+    async fn crossed_book_is_skipped() {
+        let detector = ArbitrageDetector::new(0.01);
+        let sol_up = token("sol-up", Some(dec!(0.6)), Some(dec!(0.5))); // bid > ask: crossed
+        let btc_down = token("btc-down", Some(dec!(0.1)), Some(dec!(0.3)));
+
+        let snapshot = snapshot(Some(sol_up), Some(btc_down));
+        let opportunities = detector.detect_opportunities(&snapshot, mid_period_end()).await;
+
+        assert!(opportunities.is_empty());
+    }
+
+    #[tokio::test]
+    async fn locked_book_is_skipped() {
+        let detector = ArbitrageDetector::new(0.01);
+        let sol_up = token("sol-up", Some(dec!(0.5)), Some(dec!(0.5))); // bid == ask: locked
+        let btc_down = token("btc-down", Some(dec!(0.1)), Some(dec!(0.3)));
+
+        let snapshot = snapshot(Some(sol_up), Some(btc_down));
+        let opportunities = detector.detect_opportunities(&snapshot, mid_period_end()).await;
+
+        assert!(opportunities.is_empty());
+    }
+
+    #[tokio::test]
+    async fn non_crossed_book_still_produces_an_opportunity() {
+        let detector = ArbitrageDetector::new(0.01);
+        let sol_up = token("sol-up", Some(dec!(0.6)), Some(dec!(0.65)));
+        let btc_down = token("btc-down", Some(dec!(0.2)), Some(dec!(0.3)));
+
+        let snapshot = snapshot(Some(sol_up), Some(btc_down));
+        let opportunities = detector.detect_opportunities(&snapshot, mid_period_end()).await;
+
+        assert_eq!(opportunities.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn hedge_candidate_is_populated_from_the_opposing_combination_when_available() {
+        let detector = ArbitrageDetector::new(0.01);
+        let sol_up = token("sol-up", Some(dec!(0.6)), Some(dec!(0.65)));
+        let btc_down = token("btc-down", Some(dec!(0.2)), Some(dec!(0.3)));
+        let sol_down = token("sol-down", Some(dec!(0.3)), Some(dec!(0.35)));
+        let btc_up = token("btc-up", Some(dec!(0.6)), Some(dec!(0.62)));
+
+        let mut snapshot = snapshot(Some(sol_up), Some(btc_down));
+        snapshot.sol_market.down_token = Some(sol_down);
+        snapshot.btc_market.up_token = Some(btc_up);
+
+        let opportunities = detector.detect_opportunities(&snapshot, mid_period_end()).await;
+
+        let opportunity = opportunities.iter().find(|o| o.strategy == STRATEGY_SOL_UP_BTC_DOWN).unwrap();
+        let hedge = opportunity.hedge_candidate.as_ref().unwrap();
+        assert_eq!(hedge.sol_token_id, "sol-down");
+        assert_eq!(hedge.sol_price, dec!(0.35));
+        assert_eq!(hedge.btc_token_id, "btc-up");
+        assert_eq!(hedge.btc_price, dec!(0.62));
+    }
+
+    #[tokio::test]
+    async fn hedge_candidate_is_none_when_the_opposing_combination_is_unavailable() {
+        let detector = ArbitrageDetector::new(0.01);
+        let sol_up = token("sol-up", Some(dec!(0.6)), Some(dec!(0.65)));
+        let btc_down = token("btc-down", Some(dec!(0.2)), Some(dec!(0.3)));
+
+        let snapshot = snapshot(Some(sol_up), Some(btc_down));
+        let opportunities = detector.detect_opportunities(&snapshot, mid_period_end()).await;
+
+        assert_eq!(opportunities.len(), 1);
+        assert!(opportunities[0].hedge_candidate.is_none());
+    }
+
+    #[tokio::test]
+    async fn disabling_a_strategy_suppresses_its_opportunities() {
+        let detector = ArbitrageDetector::new(0.01);
+        detector.set_strategy_enabled(STRATEGY_SOL_UP_BTC_DOWN, false).await.unwrap();
+
+        let sol_up = token("sol-up", Some(dec!(0.6)), Some(dec!(0.65)));
+        let btc_down = token("btc-down", Some(dec!(0.2)), Some(dec!(0.3)));
+        let snapshot = snapshot(Some(sol_up), Some(btc_down));
+
+        let opportunities = detector.detect_opportunities(&snapshot, mid_period_end()).await;
+
+        assert!(opportunities.is_empty());
+    }
+
+    #[tokio::test]
+    async fn re_enabling_a_strategy_restores_its_opportunities() {
+        let detector = ArbitrageDetector::new(0.01);
+        detector.set_strategy_enabled(STRATEGY_SOL_UP_BTC_DOWN, false).await.unwrap();
+        detector.set_strategy_enabled(STRATEGY_SOL_UP_BTC_DOWN, true).await.unwrap();
+
+        let sol_up = token("sol-up", Some(dec!(0.6)), Some(dec!(0.65)));
+        let btc_down = token("btc-down", Some(dec!(0.2)), Some(dec!(0.3)));
+        let snapshot = snapshot(Some(sol_up), Some(btc_down));
+
+        let opportunities = detector.detect_opportunities(&snapshot, mid_period_end()).await;
+
+        assert_eq!(opportunities.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn unknown_strategy_name_is_rejected() {
+        let detector = ArbitrageDetector::new(0.01);
+        let result = detector.set_strategy_enabled("not_a_real_strategy", false).await;
+
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn disabling_an_asset_suppresses_all_of_its_opportunities() {
+        let detector = ArbitrageDetector::new(0.01);
+        detector.set_asset_enabled(ASSET_SOL, false).await.unwrap();
+
+        let sol_up = token("sol-up", Some(dec!(0.6)), Some(dec!(0.65)));
+        let btc_down = token("btc-down", Some(dec!(0.2)), Some(dec!(0.3)));
+        let snapshot = snapshot(Some(sol_up), Some(btc_down));
+
+        let opportunities = detector.detect_opportunities(&snapshot, mid_period_end()).await;
+
+        assert!(opportunities.is_empty());
+    }
+
+    #[tokio::test]
+    async fn re_enabling_an_asset_restores_its_opportunities() {
+        let detector = ArbitrageDetector::new(0.01);
+        detector.set_asset_enabled(ASSET_BTC, false).await.unwrap();
+        detector.set_asset_enabled(ASSET_BTC, true).await.unwrap();
+
+        let sol_up = token("sol-up", Some(dec!(0.6)), Some(dec!(0.65)));
+        let btc_down = token("btc-down", Some(dec!(0.2)), Some(dec!(0.3)));
+        let snapshot = snapshot(Some(sol_up), Some(btc_down));
+
+        let opportunities = detector.detect_opportunities(&snapshot, mid_period_end()).await;
+
+        assert_eq!(opportunities.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn with_assets_enabled_starts_an_asset_disabled_before_any_runtime_toggle() {
+        let detector = ArbitrageDetector::new(0.01).with_assets_enabled(false, true);
+
+        let sol_up = token("sol-up", Some(dec!(0.6)), Some(dec!(0.65)));
+        let btc_down = token("btc-down", Some(dec!(0.2)), Some(dec!(0.3)));
+        let snapshot = snapshot(Some(sol_up), Some(btc_down));
+
+        let opportunities = detector.detect_opportunities(&snapshot, mid_period_end()).await;
+
+        assert!(opportunities.is_empty());
+    }
+
+    #[tokio::test]
+    async fn unknown_asset_name_is_rejected() {
+        let detector = ArbitrageDetector::new(0.01);
+        let result = detector.set_asset_enabled("doge", false).await;
+
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn with_strategies_enabled_starts_sol_up_btc_down_disabled() {
+        let detector = ArbitrageDetector::new(0.01).with_strategies_enabled(false, true);
+
+        let sol_up = token("sol-up", Some(dec!(0.6)), Some(dec!(0.65)));
+        let btc_down = token("btc-down", Some(dec!(0.2)), Some(dec!(0.3)));
+        let sol_down = token("sol-down", Some(dec!(0.3)), Some(dec!(0.35)));
+        let btc_up = token("btc-up", Some(dec!(0.6)), Some(dec!(0.62)));
+
+        let mut snapshot = snapshot(Some(sol_up), Some(btc_down));
+        snapshot.sol_market.down_token = Some(sol_down);
+        snapshot.btc_market.up_token = Some(btc_up);
+
+        let opportunities = detector.detect_opportunities(&snapshot, mid_period_end()).await;
+
+        assert!(!opportunities.is_empty());
+        assert!(opportunities.iter().all(|o| o.strategy == STRATEGY_SOL_DOWN_BTC_UP));
+    }
+
+    #[tokio::test]
+    async fn with_strategies_enabled_starts_sol_down_btc_up_disabled() {
+        let detector = ArbitrageDetector::new(0.01).with_strategies_enabled(true, false);
+
+        let sol_up = token("sol-up", Some(dec!(0.6)), Some(dec!(0.65)));
+        let btc_down = token("btc-down", Some(dec!(0.2)), Some(dec!(0.3)));
+        let sol_down = token("sol-down", Some(dec!(0.3)), Some(dec!(0.35)));
+        let btc_up = token("btc-up", Some(dec!(0.6)), Some(dec!(0.62)));
+
+        let mut snapshot = snapshot(Some(sol_up), Some(btc_down));
+        snapshot.sol_market.down_token = Some(sol_down);
+        snapshot.btc_market.up_token = Some(btc_up);
+
+        let opportunities = detector.detect_opportunities(&snapshot, mid_period_end()).await;
+
+        assert!(!opportunities.is_empty());
+        assert!(opportunities.iter().all(|o| o.strategy == STRATEGY_SOL_UP_BTC_DOWN));
+    }
+
+    #[tokio::test]
+    async fn with_strategies_enabled_leaves_a_custom_leg_combination_unaffected() {
+        let detector = ArbitrageDetector::new(0.01)
+            .with_leg_combinations(vec![LegCombination::new(true, true)])
+            .with_strategies_enabled(false, false);
+
+        let sol_up = token("sol-up", Some(dec!(0.6)), Some(dec!(0.65)));
+        let btc_up = token("btc-up", Some(dec!(0.2)), Some(dec!(0.3)));
+
+        let mut snapshot = snapshot(Some(sol_up), None);
+        snapshot.btc_market.up_token = Some(btc_up);
+
+        let opportunities = detector.detect_opportunities(&snapshot, mid_period_end()).await;
+
+        assert_eq!(opportunities.len(), 1);
+    }
+
+    #[test]
+    fn breakeven_price_move_pct_is_the_edge_as_a_fraction_of_cost() {
+        let pct = breakeven_price_move_pct(dec!(0.05), dec!(0.95));
+        assert_eq!(pct, dec!(0.05) / dec!(0.95));
+    }
+
+    #[test]
+    fn breakeven_price_move_pct_is_zero_for_a_zero_cost_book() {
+        let pct = breakeven_price_move_pct(dec!(1.0), dec!(0.0));
+        assert_eq!(pct, dec!(0));
+    }
+
+    #[tokio::test]
+    async fn detected_opportunity_reports_its_breakeven_cushion() {
+        let detector = ArbitrageDetector::new(0.01);
+        let sol_up = token("sol-up", Some(dec!(0.6)), Some(dec!(0.65)));
+        let btc_down = token("btc-down", Some(dec!(0.2)), Some(dec!(0.3)));
+        let snapshot = snapshot(Some(sol_up), Some(btc_down));
+
+        let opportunities = detector.detect_opportunities(&snapshot, mid_period_end()).await;
+
+        assert_eq!(opportunities.len(), 1);
+        let opportunity = &opportunities[0];
+        let expected = opportunity.expected_profit / opportunity.total_cost;
+        assert_eq!(opportunity.breakeven_price_move_pct, expected);
+    }
+
+    #[test]
+    fn interpolate_threshold_uses_early_at_full_time_to_close() {
+        let threshold = interpolate_threshold(dec!(0.05), dec!(0.01), DEFAULT_PERIOD_SECS, DEFAULT_PERIOD_SECS);
+        assert_eq!(threshold, dec!(0.05));
+    }
+
+    #[test]
+    fn interpolate_threshold_uses_late_at_zero_time_to_close() {
+        let threshold = interpolate_threshold(dec!(0.05), dec!(0.01), 0, DEFAULT_PERIOD_SECS);
+        assert_eq!(threshold, dec!(0.01));
+    }
+
+    #[test]
+    fn interpolate_threshold_is_midpoint_at_half_time_to_close() {
+        let threshold = interpolate_threshold(dec!(0.05), dec!(0.01), DEFAULT_PERIOD_SECS / 2, DEFAULT_PERIOD_SECS);
+        assert_eq!(threshold, dec!(0.03));
+    }
+
+    #[test]
+    fn interpolate_threshold_supports_non_15_minute_periods() {
+        let threshold = interpolate_threshold(dec!(0.05), dec!(0.01), 1800, 3600);
+        assert_eq!(threshold, dec!(0.03));
+    }
+
+    #[tokio::test]
+    async fn late_in_period_thin_edge_is_accepted_under_late_threshold() {
+        // early_threshold rejects this edge, late_threshold accepts it.
+        let detector = ArbitrageDetector::with_time_scaled_threshold(0.05, 0.01, DEFAULT_PERIOD_SECS);
+        let sol_up = token("sol-up", Some(dec!(0.6)), Some(dec!(0.65)));
+        let btc_down = token("btc-down", Some(dec!(0.2)), Some(dec!(0.32))); // $0.03 edge
+
+        let snapshot = snapshot(Some(sol_up), Some(btc_down));
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+
+        let opportunities = detector.detect_opportunities(&snapshot, now).await;
+        assert_eq!(opportunities.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn early_in_period_same_thin_edge_is_rejected_under_early_threshold() {
+        let detector = ArbitrageDetector::with_time_scaled_threshold(0.05, 0.01, DEFAULT_PERIOD_SECS);
+        let sol_up = token("sol-up", Some(dec!(0.6)), Some(dec!(0.65)));
+        let btc_down = token("btc-down", Some(dec!(0.2)), Some(dec!(0.32))); // $0.03 edge
+
+        let snapshot = snapshot(Some(sol_up), Some(btc_down));
+
+        let opportunities = detector.detect_opportunities(&snapshot, mid_period_end()).await;
+        assert!(opportunities.is_empty());
+    }
+
+    #[tokio::test]
+    async fn smoothed_confirmation_off_lets_a_raw_only_edge_through() {
+        let detector = ArbitrageDetector::new(0.01);
+        // Raw total cost clears the threshold; smoothed total cost does not.
+        let sol_up = token_with_smoothed_ask("sol-up", dec!(0.6), dec!(0.75));
+        let btc_down = token_with_smoothed_ask("btc-down", dec!(0.3), dec!(0.3));
+
+        let snapshot = snapshot(Some(sol_up), Some(btc_down));
+        let opportunities = detector.detect_opportunities(&snapshot, mid_period_end()).await;
+
+        assert_eq!(opportunities.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn smoothed_confirmation_on_rejects_a_raw_only_spike() {
+        let detector = ArbitrageDetector::new(0.01).with_smoothed_confirmation(true);
+        // Same prices as above: raw edge clears threshold, smoothed doesn't.
+        let sol_up = token_with_smoothed_ask("sol-up", dec!(0.6), dec!(0.75));
+        let btc_down = token_with_smoothed_ask("btc-down", dec!(0.3), dec!(0.3));
+
+        let snapshot = snapshot(Some(sol_up), Some(btc_down));
+        let opportunities = detector.detect_opportunities(&snapshot, mid_period_end()).await;
+
+        assert!(opportunities.is_empty());
+    }
+
+    #[tokio::test]
+    async fn total_cost_below_min_bound_is_rejected() {
+        let detector = ArbitrageDetector::new(0.01).with_total_cost_bounds(0.8, 1.0);
+        // Total cost $0.75, clears the profit threshold and the rug-case
+        // filter (sol_up is above $0.6) but is below the configured floor.
+        let sol_up = token("sol-up", Some(dec!(0.6)), Some(dec!(0.65)));
+        let btc_down = token("btc-down", Some(dec!(0.05)), Some(dec!(0.1)));
+
+        let snapshot = snapshot(Some(sol_up), Some(btc_down));
+        let opportunities = detector.detect_opportunities(&snapshot, mid_period_end()).await;
+
+        assert!(opportunities.is_empty());
+    }
+
+    #[tokio::test]
+    async fn total_cost_exactly_at_min_bound_is_accepted() {
+        let detector = ArbitrageDetector::new(0.01).with_total_cost_bounds(0.75, 1.0);
+        let sol_up = token("sol-up", Some(dec!(0.6)), Some(dec!(0.65)));
+        let btc_down = token("btc-down", Some(dec!(0.05)), Some(dec!(0.1)));
+
+        let snapshot = snapshot(Some(sol_up), Some(btc_down));
+        let opportunities = detector.detect_opportunities(&snapshot, mid_period_end()).await;
+
+        assert_eq!(opportunities.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn total_cost_above_max_bound_is_rejected() {
+        let detector = ArbitrageDetector::new(0.001).with_total_cost_bounds(0.0, 0.8);
+        // Total cost $0.875: clears the tiny profit threshold but exceeds the
+        // configured ceiling.
+        let sol_up = token("sol-up", Some(dec!(0.6)), Some(dec!(0.625)));
+        let btc_down = token("btc-down", Some(dec!(0.2)), Some(dec!(0.25)));
+
+        let snapshot = snapshot(Some(sol_up), Some(btc_down));
+        let opportunities = detector.detect_opportunities(&snapshot, mid_period_end()).await;
+
+        assert!(opportunities.is_empty());
+    }
+
+    #[tokio::test]
+    async fn total_cost_exactly_at_max_bound_is_accepted() {
+        let detector = ArbitrageDetector::new(0.001).with_total_cost_bounds(0.0, 0.875);
+        let sol_up = token("sol-up", Some(dec!(0.6)), Some(dec!(0.625)));
+        let btc_down = token("btc-down", Some(dec!(0.2)), Some(dec!(0.25)));
+
+        let snapshot = snapshot(Some(sol_up), Some(btc_down));
+        let opportunities = detector.detect_opportunities(&snapshot, mid_period_end()).await;
+
+        assert_eq!(opportunities.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn default_total_cost_bounds_preserve_prior_behavior() {
+        let detector = ArbitrageDetector::new(0.01);
+        let sol_up = token("sol-up", Some(dec!(0.6)), Some(dec!(0.65)));
+        let btc_down = token("btc-down", Some(dec!(0.2)), Some(dec!(0.3)));
+
+        let snapshot = snapshot(Some(sol_up), Some(btc_down));
+        let opportunities = detector.detect_opportunities(&snapshot, mid_period_end()).await;
+
+        assert_eq!(opportunities.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn a_near_miss_below_the_log_threshold_is_still_not_traded() {
+        // Total cost $0.95 -> $0.05 edge, below the $0.10 trade threshold
+        // and below a $0.08 log threshold too, so nothing should log or trade.
+        let detector = ArbitrageDetector::new(0.10).with_log_profit_threshold(Some(0.08));
+        let sol_up = token("sol-up", Some(dec!(0.6)), Some(dec!(0.65)));
+        let btc_down = token("btc-down", Some(dec!(0.25)), Some(dec!(0.3)));
+
+        let snapshot = snapshot(Some(sol_up), Some(btc_down));
+        let opportunities = detector.detect_opportunities(&snapshot, mid_period_end()).await;
+
+        assert!(opportunities.is_empty());
+    }
+
+    #[tokio::test]
+    async fn a_near_miss_above_the_log_threshold_is_logged_but_still_not_traded() {
+        // Total cost $0.95 -> $0.05 edge, below the $0.10 trade threshold
+        // but above a $0.01 log threshold: should be logged as a near-miss
+        // without becoming a tradeable opportunity.
+        let detector = ArbitrageDetector::new(0.10).with_log_profit_threshold(Some(0.01));
+        let sol_up = token("sol-up", Some(dec!(0.6)), Some(dec!(0.65)));
+        let btc_down = token("btc-down", Some(dec!(0.25)), Some(dec!(0.3)));
+
+        let snapshot = snapshot(Some(sol_up), Some(btc_down));
+        let opportunities = detector.detect_opportunities(&snapshot, mid_period_end()).await;
+
+        assert!(opportunities.is_empty());
+    }
+
+    #[tokio::test]
+    async fn log_profit_threshold_does_not_affect_a_trade_that_clears_the_trade_threshold() {
+        let detector = ArbitrageDetector::new(0.01).with_log_profit_threshold(Some(0.005));
+        let sol_up = token("sol-up", Some(dec!(0.6)), Some(dec!(0.65)));
+        let btc_down = token("btc-down", Some(dec!(0.2)), Some(dec!(0.3)));
+
+        let snapshot = snapshot(Some(sol_up), Some(btc_down));
+        let opportunities = detector.detect_opportunities(&snapshot, mid_period_end()).await;
+
+        assert_eq!(opportunities.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn custom_leg_combinations_replace_the_default_hedges() {
+        // Configure only SOL Up + BTC Up (not one of the default hedges).
+        let detector = ArbitrageDetector::new(0.01).with_leg_combinations(vec![LegCombination::new(true, true)]);
+        let sol_up = token("sol-up", Some(dec!(0.6)), Some(dec!(0.65)));
+        let btc_up = token("btc-up", Some(dec!(0.2)), Some(dec!(0.3)));
+
+        let mut snapshot = snapshot(Some(sol_up), None);
+        snapshot.btc_market.up_token = Some(btc_up);
+
+        let opportunities = detector.detect_opportunities(&snapshot, mid_period_end()).await;
+
+        assert_eq!(opportunities.len(), 1);
+        assert_eq!(opportunities[0].strategy, "sol_up_btc_up");
+    }
+
+    #[tokio::test]
+    async fn custom_leg_combinations_drop_the_default_hedges_not_configured() {
+        let detector = ArbitrageDetector::new(0.01).with_leg_combinations(vec![LegCombination::new(true, true)]);
+        // Only SOL Up + BTC Down data present, which is no longer configured.
+        let sol_up = token("sol-up", Some(dec!(0.6)), Some(dec!(0.65)));
+        let btc_down = token("btc-down", Some(dec!(0.2)), Some(dec!(0.3)));
+
+        let snapshot = snapshot(Some(sol_up), Some(btc_down));
+        let opportunities = detector.detect_opportunities(&snapshot, mid_period_end()).await;
+
+        assert!(opportunities.is_empty());
+    }
+
+    #[tokio::test]
+    async fn smoothed_confirmation_on_accepts_an_edge_that_survives_smoothing() {
+        let detector = ArbitrageDetector::new(0.01).with_smoothed_confirmation(true);
+        let sol_up = token_with_smoothed_ask("sol-up", dec!(0.6), dec!(0.6));
+        let btc_down = token_with_smoothed_ask("btc-down", dec!(0.3), dec!(0.3));
+
+        let snapshot = snapshot(Some(sol_up), Some(btc_down));
+        let opportunities = detector.detect_opportunities(&snapshot, mid_period_end()).await;
+
+        assert_eq!(opportunities.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn last_trade_price_band_disabled_by_default_ignores_a_wildly_off_ask() {
+        let detector = ArbitrageDetector::new(0.01);
+        let sol_up = token_with_last("sol-up", dec!(0.65), dec!(0.2));
+        let btc_down = token("btc-down", Some(dec!(0.2)), Some(dec!(0.3)));
+
+        let snapshot = snapshot(Some(sol_up), Some(btc_down));
+        let opportunities = detector.detect_opportunities(&snapshot, mid_period_end()).await;
+
+        assert_eq!(opportunities.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn last_trade_price_band_rejects_an_ask_far_from_the_last_trade() {
+        let detector = ArbitrageDetector::new(0.01).with_last_trade_price_band(Some(0.05));
+        // Ask $0.65 vs last trade $0.2 is far outside a 5% band.
+        let sol_up = token_with_last("sol-up", dec!(0.65), dec!(0.2));
+        let btc_down = token("btc-down", Some(dec!(0.2)), Some(dec!(0.3)));
+
+        let snapshot = snapshot(Some(sol_up), Some(btc_down));
+        let opportunities = detector.detect_opportunities(&snapshot, mid_period_end()).await;
+
+        assert!(opportunities.is_empty());
+    }
+
+    #[tokio::test]
+    async fn last_trade_price_band_accepts_an_ask_within_the_band() {
+        let detector = ArbitrageDetector::new(0.01).with_last_trade_price_band(Some(0.05));
+        // Ask $0.63 vs last trade $0.65 is within a 5% band.
+        let sol_up = token_with_last("sol-up", dec!(0.63), dec!(0.65));
+        let btc_down = token("btc-down", Some(dec!(0.2)), Some(dec!(0.3)));
+
+        let snapshot = snapshot(Some(sol_up), Some(btc_down));
+        let opportunities = detector.detect_opportunities(&snapshot, mid_period_end()).await;
+
+        assert_eq!(opportunities.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn last_trade_price_band_is_permissive_when_last_trade_is_unavailable() {
+        let detector = ArbitrageDetector::new(0.01).with_last_trade_price_band(Some(0.05));
+        // No `last` set on either leg (e.g. the band check was just enabled
+        // and last-trade fetches haven't populated the field yet).
+        let sol_up = token("sol-up", Some(dec!(0.6)), Some(dec!(0.65)));
+        let btc_down = token("btc-down", Some(dec!(0.2)), Some(dec!(0.3)));
+
+        let snapshot = snapshot(Some(sol_up), Some(btc_down));
+        let opportunities = detector.detect_opportunities(&snapshot, mid_period_end()).await;
+
+        assert_eq!(opportunities.len(), 1);
+    }
+}
@@ -1,6 +1,7 @@
+use anyhow::Context;
 use clap::Parser;
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
@@ -9,15 +10,103 @@ pub struct Args {
     #[arg(short, long, default_value_t = true)]
     pub simulation: bool,
 
-    /// Configuration file path
+    /// Configuration file path. Format is chosen from the extension
+    /// (`.toml`, `.yaml`/`.yml`, or JSON otherwise) - if the file doesn't
+    /// exist yet, a fresh default config is written in that same format.
     #[arg(short, long, default_value = "config.json")]
     pub config: PathBuf,
+
+    /// Append every market snapshot to this JSONL file for later backtesting
+    #[arg(long)]
+    pub record: Option<PathBuf>,
+
+    /// Append every settled trade to this JSONL file for later `--replay`
+    /// auditing.
+    #[arg(long)]
+    pub trade_log: Option<PathBuf>,
+
+    /// Re-settle a trade-history file (written via `--trade-log`) against
+    /// each trade's actual on-chain resolution and report any mismatches
+    /// with what was recorded at settlement time, then exit. Read-only.
+    #[arg(long)]
+    pub replay: Option<PathBuf>,
+
+    /// Skip the startup self-test that runs `settlement_profit` against a
+    /// table of known scenarios before the monitor loop starts. On by
+    /// default since a regression in the settlement math is a money-losing
+    /// bug; skip only for fast iteration in a trusted dev environment.
+    #[arg(long, default_value_t = false)]
+    pub skip_self_test: bool,
+
+    /// If startup market discovery fails, keep retrying with backoff for up
+    /// to this many seconds before giving up, instead of exiting
+    /// immediately. Useful for an unattended restart that lands a few
+    /// seconds before a new period's markets exist. Unset (the default)
+    /// fails fast on the first discovery error, the original behavior.
+    #[arg(long)]
+    pub wait_for_markets: Option<u64>,
+
+    /// Persist lifetime totals (profit, trades, wins, losses, deployed
+    /// capital) to this JSON file, rewritten on each settlement. Loaded on
+    /// startup so the displayed lifetime numbers survive a restart. Unset
+    /// (the default) keeps lifetime stats in memory only, the original
+    /// behavior.
+    #[arg(long)]
+    pub stats_file: Option<PathBuf>,
+
+    /// Required before production mode (`--simulation=false`) places its
+    /// first real order, as a deliberate friction step given the
+    /// total-loss risk of the both-lose outcome. Without it, production
+    /// mode falls back to an interactive "yes" confirmation on a TTY, or
+    /// refuses to start at all when stdin isn't a TTY. Has no effect in
+    /// simulation mode.
+    #[arg(long, default_value_t = false)]
+    pub i_understand_real_money: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
     pub polymarket: PolymarketConfig,
     pub trading: TradingConfig,
+    /// OpenTelemetry trace export settings, only meaningful when this binary
+    /// is built with the `otel` feature (`cargo build --features otel`) -
+    /// see `crate::telemetry`. `#[serde(default)]` so an existing config
+    /// file without a `[tracing]` section keeps loading unchanged.
+    #[serde(default)]
+    pub tracing: TracingConfig,
+}
+
+/// Configuration for the optional OpenTelemetry trace export covering the
+/// detection -> execution -> settlement trade lifecycle. Only takes effect
+/// when this binary is built with the `otel` feature; on a build without
+/// it, `enabled` is accepted (so config files are portable across builds)
+/// but ignored, and no OTLP exporter is ever constructed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TracingConfig {
+    /// Whether to initialize the OTLP trace exporter at startup. `false`
+    /// (the default) matches the original behavior of no tracing overhead
+    /// or external dependency at all.
+    #[serde(default)]
+    pub enabled: bool,
+    /// gRPC endpoint of the OTLP collector to export spans to, e.g.
+    /// `http://localhost:4317`. Required when `enabled` is `true`.
+    #[serde(default)]
+    pub otlp_endpoint: Option<String>,
+    /// `service.name` resource attribute attached to every exported span,
+    /// so traces from multiple bot instances are distinguishable in a
+    /// shared collector.
+    #[serde(default = "default_otel_service_name")]
+    pub service_name: String,
+}
+
+impl Default for TracingConfig {
+    fn default() -> Self {
+        Self { enabled: false, otlp_endpoint: None, service_name: default_otel_service_name() }
+    }
+}
+
+fn default_otel_service_name() -> String {
+    "polymarket-arbitrage-bot".to_string()
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -25,16 +114,730 @@ pub struct PolymarketConfig {
     pub gamma_api_url: String,
     pub clob_api_url: String,
     pub ws_url: String,
+    /// Trading credential sent with order placement/cancellation and
+    /// account-data calls (`place_order`, `validate_order`, `get_positions`,
+    /// `get_trade_history`, `get_order_status`, `cancel_all_orders`). Kept
+    /// separate from `data_api_key` so a monitoring-only deployment can hold
+    /// read access without also holding the ability to trade.
     pub api_key: Option<String>,
+    /// Read-only credential sent with market/price data calls (`get_price`,
+    /// `get_market`, `get_orderbook`, `get_midpoint`, `get_last_trade_price`).
+    /// Gamma discovery calls (`get_all_active_markets`, `get_market_by_slug`)
+    /// never carry it. `None` (the default)
+    /// sends those requests unauthenticated, the original behavior - set
+    /// this only if Polymarket requires or rate-limits reads by key.
+    #[serde(default)]
+    pub data_api_key: Option<String>,
+    /// Maximum number of requests to the Gamma/CLOB APIs allowed in flight
+    /// at once, bounding concurrency independent of any per-request rate
+    /// limiting. Applies across all `PolymarketApi` methods.
+    #[serde(default = "default_max_concurrent_requests")]
+    pub max_concurrent_requests: usize,
+    /// Timeout, in milliseconds, applied specifically to order-placement
+    /// requests instead of the longer client-wide timeout used for reads.
+    /// A volatile period boundary should fail fast so rollback logic can
+    /// kick in rather than blocking on a slow CLOB.
+    #[serde(default = "default_order_timeout_ms")]
+    pub order_timeout_ms: u64,
+    /// Log the full raw response body of every gamma/CLOB request at debug
+    /// level, across all `PolymarketApi` methods. Invaluable for diagnosing
+    /// schema drift when parsing fails mid-period, but noisy - off by
+    /// default.
+    #[serde(default)]
+    pub log_raw_responses: bool,
+    /// Explicit HTTP/HTTPS proxy URL (optionally with embedded `user:pass@`
+    /// credentials) for reaching Polymarket from regions that require one.
+    /// When unset, the standard `HTTP_PROXY`/`HTTPS_PROXY` environment
+    /// variables are still honored, since that's `reqwest`'s default
+    /// behavior.
+    #[serde(default)]
+    pub http_proxy: Option<String>,
+    /// Explicit SOCKS5 proxy URL (optionally with embedded credentials).
+    /// Applies alongside `http_proxy` if both are set.
+    #[serde(default)]
+    pub socks_proxy: Option<String>,
+    /// Rolling API failure budget: if configured, the bot exits with a
+    /// nonzero code (after logging the breach) once more than
+    /// `max_failure_rate` of API calls fail within `window_secs`, so a
+    /// supervisor can restart it or alert instead of it spinning forever
+    /// against a fully-down API. Disabled (`None`) by default.
+    #[serde(default)]
+    pub failure_budget: Option<FailureBudgetSettings>,
+    /// Hard cap on total API calls allowed within a single
+    /// `TradingConfig::period_duration_secs`-long period, resetting on
+    /// rollover. Distinct from `failure_budget` (which tracks failure rate,
+    /// not volume): this is a budget-aware degradation mode for deployments
+    /// on a strict rate quota - once exhausted, non-essential calls (e.g.
+    /// extra price polls) are suppressed until the next period while
+    /// essential ones (e.g. settlement checks near close) still go through.
+    /// `None` (the default) disables the cap.
+    #[serde(default)]
+    pub max_calls_per_period: Option<usize>,
+    /// Funder/maker address for orders placed through a Polymarket proxy
+    /// wallet, where the signing key differs from the wallet that actually
+    /// holds funds. Included in the signed order body and the `POLY_ADDRESS`
+    /// header on every order-placement/validation request. `None` (the
+    /// default) preserves the original behavior of signing and funding from
+    /// the same address.
+    #[serde(default)]
+    pub funder_address: Option<String>,
+    /// Custom User-Agent sent with every gamma/CLOB request. Defaults to
+    /// `<crate name>/<version>` when unset - useful for diagnostics if an API
+    /// gateway behaves differently by User-Agent, or if Polymarket support
+    /// asks for an identifying value while investigating an issue.
+    #[serde(default)]
+    pub user_agent: Option<String>,
+    /// Extra static headers applied to every gamma/CLOB request. Validated
+    /// at startup, so a malformed header name/value fails fast rather than
+    /// surfacing as a mysterious connection error on the first request.
+    #[serde(default)]
+    pub extra_headers: std::collections::HashMap<String, String>,
+    /// Simulation-only chaos testing: when set, `PolymarketApi` randomly
+    /// injects failures (timeouts, 429s, 5xx, malformed bodies) into the
+    /// price/market/book/order endpoints, so retry/circuit-breaker/
+    /// failure-budget logic can be exercised without needing the real API to
+    /// misbehave. `None` (the default) disables it entirely. Startup fails
+    /// if set outside simulation mode.
+    #[serde(default)]
+    pub chaos: Option<ChaosSettings>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChaosSettings {
+    /// Fraction (0.0-1.0) of calls to the covered endpoints that get a
+    /// randomly injected failure instead of actually reaching the network.
+    pub failure_rate: f64,
+    /// Fixes the RNG seed behind which calls fail and which failure kind is
+    /// injected, so a chaos-testing run is reproducible. `None` seeds from
+    /// entropy.
+    #[serde(default)]
+    pub seed: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FailureBudgetSettings {
+    /// Fraction (0.0-1.0) of calls in the window that must fail to trip
+    /// the budget.
+    pub max_failure_rate: f64,
+    /// Length of the trailing window, in seconds, over which the failure
+    /// rate is computed.
+    pub window_secs: u64,
+    /// Minimum number of calls observed within the window before the
+    /// budget can be breached, so a handful of calls right after startup
+    /// can't trip a 100% failure rate on their own.
+    #[serde(default = "default_failure_budget_min_samples")]
+    pub min_samples: usize,
+    /// How often, in milliseconds, the breach check runs in the background.
+    #[serde(default = "default_failure_budget_check_interval_ms")]
+    pub check_interval_ms: u64,
+}
+
+fn default_failure_budget_min_samples() -> usize {
+    10
+}
+
+fn default_failure_budget_check_interval_ms() -> u64 {
+    10_000
+}
+
+fn default_max_concurrent_requests() -> usize {
+    8
+}
+
+fn default_order_timeout_ms() -> u64 {
+    3000
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TradingConfig {
     pub min_profit_threshold: f64,
     pub max_position_size: f64,
+    /// Cap each leg's size at this fraction of the resting orderbook depth
+    /// available at or below the opportunity's price (via `get_orderbook`),
+    /// on top of `max_position_size`. Guards against a trade being most of
+    /// a thin 15m market's book and moving the price against itself. `None`
+    /// (the default) disables the check and skips the extra orderbook
+    /// fetches entirely.
+    #[serde(default)]
+    pub max_depth_fraction: Option<f64>,
+    /// Minimum resting ask depth (in units, via `get_orderbook`) required on
+    /// each leg before an opportunity is considered tradeable at all,
+    /// regardless of top-of-book price. Fresh 15m markets often quote a
+    /// tight spread with almost nothing behind it for the first few
+    /// seconds; this complements the post-rollover grace period by gating
+    /// on actual liquidity rather than just elapsed time. `0.0` (the
+    /// default) disables the check and skips the extra orderbook fetches
+    /// entirely.
+    #[serde(default)]
+    pub min_book_depth: f64,
     pub sol_condition_id: Option<String>,
     pub btc_condition_id: Option<String>,
     pub check_interval_ms: u64,
+    /// When true, floor the computed unit count to a whole number and
+    /// recompute the investment from that instead of trading fractional units.
+    #[serde(default)]
+    pub round_units_to_whole: bool,
+    /// Minimum tradeable order size in units. If flooring units to a whole
+    /// number drops below this, the opportunity is skipped rather than
+    /// placing a dust-sized order.
+    #[serde(default = "default_min_order_size")]
+    pub min_order_size: f64,
+    /// Estimated on-chain gas/fee cost to redeem winning tokens, subtracted
+    /// once per settled trade (not per leg) from realized profit.
+    #[serde(default)]
+    pub redemption_cost_estimate: f64,
+    /// If true, `min_profit_threshold` used at detection time is raised by
+    /// `redemption_cost_estimate` so edges too thin to cover redemption
+    /// costs are never traded in the first place.
+    #[serde(default)]
+    pub require_profit_above_redemption_cost: bool,
+    /// Maximum dollar notional per child order. When set and
+    /// `enable_order_splitting` is true, a position larger than this is
+    /// split into multiple smaller child orders placed sequentially.
+    #[serde(default)]
+    pub max_order_notional: Option<f64>,
+    #[serde(default)]
+    pub enable_order_splitting: bool,
+    /// Delay between placing successive child order slices, in milliseconds.
+    #[serde(default)]
+    pub order_split_delay_ms: u64,
+    /// Maximum randomized delay (in milliseconds) inserted between placing
+    /// the SOL leg and the BTC leg of a trade, sampled uniformly from
+    /// [0, max] per slice. Placing both legs via a single `tokio::join!`
+    /// call can signal arbitrage activity to the venue or other bots; a
+    /// small stagger trades a little price-movement risk on the second leg
+    /// for reduced signaling. Defaults to 0 (simultaneous placement, the
+    /// original behavior). See `Trader::execute_real_trade`'s inter-leg
+    /// re-check for how a moved market during the delay is handled.
+    #[serde(default)]
+    pub inter_leg_delay_max_ms: u64,
+    /// When set, resolved token/condition IDs are cached to this file so a
+    /// restart within the same period can warm-start instead of paying for
+    /// fresh CLOB `get_market` calls before the first snapshot.
+    #[serde(default)]
+    pub token_cache_path: Option<PathBuf>,
+    /// Outcome labels (case-insensitive, substring match) that identify a
+    /// token as the "up" side of a market. Defaults cover Polymarket's
+    /// usual "Up"/"1" labeling; override for markets that label outcomes
+    /// differently (e.g. "Yes"/"No").
+    #[serde(default = "default_up_outcome_keywords")]
+    pub up_outcome_keywords: Vec<String>,
+    /// Outcome labels that identify a token as the "down" side of a market.
+    #[serde(default = "default_down_outcome_keywords")]
+    pub down_outcome_keywords: Vec<String>,
+    /// Minimum profit threshold used right before a period closes, when a
+    /// thinner edge is safer to take because resolution is imminent. The
+    /// detector linearly interpolates between this and `min_profit_threshold`
+    /// (used right after a period opens) based on time-to-close. Defaults to
+    /// `min_profit_threshold` when unset, preserving a constant threshold.
+    #[serde(default)]
+    pub late_profit_threshold: Option<f64>,
+    /// Lower threshold, independent of `min_profit_threshold`/
+    /// `late_profit_threshold`, above which `detect_opportunities` logs an
+    /// opportunity even though it's too thin to trade. Lets an operator see
+    /// how often edges appear just below the trading cutoff, to inform
+    /// tuning that cutoff. `None` (the default) disables near-miss logging.
+    #[serde(default)]
+    pub log_profit_threshold: Option<f64>,
+    /// Simulation-only stress test: when set, `check_market_result_cached`
+    /// overrides a closed market's resolution to "both legs lose" with this
+    /// probability (0.0-1.0), regardless of the real winner, so drawdown/
+    /// loss-limiting behavior can be exercised under an artificially bad
+    /// streak. Startup fails if set outside simulation mode.
+    #[serde(default)]
+    pub adversarial_loss_probability: Option<f64>,
+    /// Length of a market period in seconds. Polymarket's up/down markets
+    /// aren't always 15 minutes (there are hourly variants too); this is
+    /// the single source of truth for period-boundary math, slug timestamp
+    /// rounding, and the settlement-check age threshold.
+    #[serde(default = "default_period_duration_secs")]
+    pub period_duration_secs: u64,
+    /// How much the re-verified edge (at execution time) is allowed to have
+    /// shrunk from `expected_profit` before `execute_arbitrage` aborts the
+    /// trade as stale. A last-look re-fetch happens right before order
+    /// placement to guard against latency between detection and execution.
+    #[serde(default = "default_last_look_tolerance")]
+    pub last_look_tolerance: f64,
+    /// Smoothing factor (0.0-1.0) for the exponential moving average the
+    /// monitor maintains over each token's bid/ask. Higher values track raw
+    /// prices more closely; lower values filter out more single-tick noise.
+    #[serde(default = "default_price_ema_alpha")]
+    pub price_ema_alpha: f64,
+    /// When true, the detector also requires the *smoothed* total cost to
+    /// clear the profit threshold before producing an opportunity, in
+    /// addition to the raw total cost. Raw prices are still used to size
+    /// and execute the trade; this only gates detection against transient
+    /// single-tick spikes.
+    #[serde(default)]
+    pub require_smoothed_confirmation: bool,
+    /// Interval, in milliseconds, between heartbeat log lines summarizing
+    /// current period, latest prices, pending trade count, lifetime profit,
+    /// and uptime. Keeps quiet periods visible without cranking log level.
+    #[serde(default = "default_heartbeat_interval_ms")]
+    pub heartbeat_interval_ms: u64,
+    /// When true, an opportunity is still eligible for execution even if one
+    /// or both legs' price came from the `/midpoint` fallback rather than a
+    /// real bid/ask. Off by default: a midpoint can understate the true cost
+    /// to trade, so midpoint-derived opportunities are detected and logged
+    /// but not executed unless this is explicitly enabled.
+    #[serde(default)]
+    pub trust_midpoint_for_execution: bool,
+    /// Number of recent (timestamp, bid, ask) samples the monitor keeps per
+    /// token for debugging and local charting. Set to 0 to disable. Purely
+    /// observability - not consulted by trading logic.
+    #[serde(default = "default_price_history_len")]
+    pub price_history_len: usize,
+    /// Minimum acceptable combined ask cost for the two legs of an
+    /// opportunity, independent of `min_profit_threshold`. Rejects
+    /// suspiciously cheap combinations (illiquid/stale book) rather than
+    /// just requiring a large enough profit. Defaults to 0.0 (no floor).
+    #[serde(default = "default_min_total_cost")]
+    pub min_total_cost: f64,
+    /// Maximum acceptable combined ask cost for the two legs of an
+    /// opportunity, independent of `min_profit_threshold`. Defaults to 1.0,
+    /// i.e. no restriction beyond the standard "total cost under a dollar"
+    /// check.
+    #[serde(default = "default_max_total_cost")]
+    pub max_total_cost: f64,
+    /// Time-in-force applied to both arbitrage legs in `execute_real_trade`:
+    /// "GTC", "FOK", or "IOC" per the CLOB spec. FOK is recommended so a
+    /// leg that can't fill immediately and completely is rejected outright
+    /// rather than left resting and creating an unhedged position. Defaults
+    /// to "GTC", preserving the original lingering-limit-order behavior.
+    #[serde(default = "default_order_time_in_force")]
+    pub order_time_in_force: String,
+    /// How far apart (in seconds) the SOL and BTC markets' `end_date_iso`
+    /// may be before the monitor warns about period-boundary drift. The two
+    /// markets are assumed to share a period, so a wider gap than this means
+    /// one could close while the other is still open, breaking the
+    /// both-closed settlement assumption.
+    #[serde(default = "default_period_boundary_tolerance_secs")]
+    pub period_boundary_tolerance_secs: u64,
+    /// When true, `execute_real_trade` validates both legs of a slice
+    /// against the CLOB's dry-run validation endpoint before placing either,
+    /// skipping the slice if either leg would be rejected (price off tick,
+    /// below min size, market not accepting orders, insufficient balance).
+    /// Cheaper than placing both and rolling back a lone fill. Off by
+    /// default since it adds a network round-trip per slice.
+    #[serde(default)]
+    pub validate_orders_before_placement: bool,
+    /// Lower bound of the sane band a fetched bid/ask must fall within.
+    /// Outcome tokens are bounded in [0, 1]; a data glitch returning a price
+    /// outside a band slightly inside that range is rejected and logged
+    /// rather than fed into arbitrage detection.
+    #[serde(default = "default_min_sane_price")]
+    pub min_sane_price: f64,
+    /// Upper bound of the sane band a fetched bid/ask must fall within. See
+    /// `min_sane_price`.
+    #[serde(default = "default_max_sane_price")]
+    pub max_sane_price: f64,
+    /// How `MarketMonitor::fetch_token_price` handles a venue returning a
+    /// BUY (ask) price below the SELL (bid) price - a data inconsistency
+    /// that would otherwise produce a `TokenPrice` with bid > ask. Defaults
+    /// to dropping the leg for that tick (falling through to the midpoint
+    /// fallback) rather than propagating an inconsistent price into the
+    /// detector.
+    #[serde(default)]
+    pub price_inversion_policy: crate::monitor::PriceInversionPolicy,
+    /// Which endpoint `MarketMonitor::fetch_token_price` populates
+    /// `TokenPrice` from: the `/price` endpoint (two calls, one per side,
+    /// falling back to the midpoint), the orderbook's top bid/ask (one call,
+    /// reflects what's actually resting on the book right now), or the
+    /// midpoint directly (cheapest, but doesn't distinguish bid from ask).
+    /// Detection can afford the price endpoint's extra round-trips and
+    /// benefits from its freshness; execution cares more about what's
+    /// actually fillable on the book right now. Defaults to the price
+    /// endpoint, the original behavior.
+    #[serde(default)]
+    pub price_source_preference: crate::monitor::PriceSourcePreference,
+    /// After this many consecutive price-fetch failures on a single leg,
+    /// `MarketMonitor::fetch_market_data` forces an early token re-refresh
+    /// (ignoring the normal once-per-`period_duration_secs` timer), on the
+    /// theory that a persistently-failing leg has a stale/wrong cached token
+    /// ID rather than just a flaky endpoint. `None` (the default) disables
+    /// this and preserves the old behavior of only refreshing once per
+    /// period.
+    #[serde(default)]
+    pub max_consecutive_price_failures: Option<u32>,
+    /// Secondary price source `MarketMonitor::fetch_token_price` cross-checks
+    /// the primary (`price_source_preference`) price against on each tick.
+    /// `None` (the default) disables the check and its extra per-token fetch
+    /// entirely. Catches one source silently going stale while the other
+    /// keeps updating.
+    #[serde(default)]
+    pub cross_check_source: Option<crate::monitor::PriceSourcePreference>,
+    /// Maximum fractional disagreement between the primary and
+    /// `cross_check_source` prices before a leg is flagged unreliable and
+    /// logged. Loose enough by default not to fire on ordinary
+    /// spread-driven differences between sources.
+    #[serde(default = "default_cross_check_tolerance_pct")]
+    pub cross_check_tolerance_pct: f64,
+    /// When true, a leg whose cross-source check exceeds `cross_check_tolerance_pct`
+    /// is dropped for that tick (as if its fetch had failed) instead of only
+    /// being logged. `false` by default - flag but don't act.
+    #[serde(default)]
+    pub skip_trading_on_cross_check_mismatch: bool,
+    /// UTC time-of-day windows `execute_arbitrage` is allowed to place
+    /// trades in. Empty (the default) means no restriction. Monitoring and
+    /// settlement run 24/7 regardless; opportunities detected outside a
+    /// window are logged but not executed.
+    #[serde(default)]
+    pub trading_windows: Vec<TradingWindow>,
+    /// Hard ceiling on cumulative capital ever deployed across all trades,
+    /// independent of `max_order_notional`/`max_position_size`. Once
+    /// crossed, `execute_arbitrage` stops entering new trades - existing
+    /// pending trades still settle normally. `None` (the default) means no
+    /// lifetime cap.
+    #[serde(default)]
+    pub max_lifetime_deployed: Option<f64>,
+    /// When false (the default), `refresh_market_tokens` skips a market
+    /// whose `is_50_50_outcome` flag is false and logs a warning instead of
+    /// mapping its tokens to up/down - a market that isn't a simple binary
+    /// may be a different instrument type our up/down settlement logic
+    /// isn't built for. Set true to force-trade such markets anyway.
+    #[serde(default)]
+    pub allow_non_50_50_markets: bool,
+    /// Which (SOL outcome, BTC outcome) combinations the detector evaluates,
+    /// replacing the two fixed hedges with a configurable set. Defaults to
+    /// the original SOL-Up/BTC-Down and SOL-Down/BTC-Up pairing.
+    #[serde(default = "default_leg_combinations")]
+    pub leg_combinations: Vec<LegCombination>,
+    /// Fraction (0.0-1.0) of detected opportunities that are actually
+    /// executed as real trades; the rest are logged as skipped-by-sampling.
+    /// Lets an operator ramp a new strategy up gradually in production
+    /// instead of committing to full frequency immediately. Has no effect
+    /// in simulation mode, where every opportunity still runs. Defaults to
+    /// 1.0 (trade everything, the original behavior).
+    #[serde(default = "default_trade_sample_rate")]
+    pub trade_sample_rate: f64,
+    /// Fixes the RNG seed behind `trade_sample_rate`'s per-opportunity coin
+    /// flip, so a backtest/replay run can reproduce exactly which
+    /// opportunities were sampled. `None` (the default) seeds from OS
+    /// entropy.
+    #[serde(default)]
+    pub trade_sample_seed: Option<u64>,
+    /// After a period rollover, hold off entering trades for this many
+    /// milliseconds while the newly-discovered market's book stabilizes.
+    /// Opportunities detected during the grace period are logged but not
+    /// executed. Defaults to 0 (trade immediately, the original behavior).
+    #[serde(default)]
+    pub post_rollover_grace_ms: u64,
+    /// When false, the detector skips every opportunity involving the SOL
+    /// market while the monitor keeps polling its prices for observability.
+    /// Lets an operator stop trading one misbehaving asset (bad liquidity,
+    /// delayed resolution) without restarting or affecting the other asset.
+    /// Defaults to true (trade normally).
+    #[serde(default = "default_asset_enabled")]
+    pub sol_enabled: bool,
+    /// Same as `sol_enabled`, for the BTC market.
+    #[serde(default = "default_asset_enabled")]
+    pub btc_enabled: bool,
+    /// How often, in milliseconds, the background task checks pending
+    /// trades for settlement. Thirty seconds of latency here matters for
+    /// capital efficiency in a 15-minute market, so this is tunable
+    /// separately from the discovery check. Defaults to 30000 (30s), the
+    /// original hardcoded value.
+    #[serde(default = "default_settlement_check_interval_ms")]
+    pub settlement_check_interval_ms: u64,
+    /// How long, in milliseconds, the settlement poller sleeps when there
+    /// are no pending trades at all - nothing to react to, so waking on the
+    /// busy `settlement_check_interval_ms` cadence would just spin the timer
+    /// for no reason. Once a trade is pending, the poller adapts: it sleeps
+    /// until the soonest one's settlement window opens rather than ticking
+    /// on a fixed interval the whole time, then falls back to
+    /// `settlement_check_interval_ms` once that window is open. Defaults to
+    /// 300000 (5 minutes).
+    #[serde(default = "default_settlement_idle_check_interval_ms")]
+    pub settlement_idle_check_interval_ms: u64,
+    /// How often, in milliseconds, the background task checks for a new
+    /// period and (re-)discovers markets. Defaults to 60000 (60s), the
+    /// original hardcoded value.
+    #[serde(default = "default_discovery_check_interval_ms")]
+    pub discovery_check_interval_ms: u64,
+    /// Taker fee charged per leg at entry, in basis points of that leg's
+    /// notional (e.g. `10.0` = 0.10%). Applied identically in simulation and
+    /// production so simulated P&L is directly comparable to what a live
+    /// trade would actually net; folded into the trade's `investment_amount`
+    /// at entry so it flows through the existing settlement math without
+    /// changing `settlement_profit`'s signature. Defaults to 0.0 (no fee,
+    /// the original simulation behavior).
+    #[serde(default)]
+    pub taker_fee_bps: f64,
+    /// Maximum age, in seconds, a `market_cache` entry is kept before it's
+    /// evicted on the next cache insert. Each period's condition IDs are
+    /// distinct, so without eviction the cache grows by two entries every
+    /// period for the life of the process - fine for a short run, a slow
+    /// leak for a multi-day unattended one. Defaults to 3600 (1 hour), well
+    /// beyond the 60-second freshness TTL used for cache hits, so this only
+    /// trims markets that are long since settled and no longer looked up.
+    #[serde(default = "default_market_cache_max_age_secs")]
+    pub market_cache_max_age_secs: u64,
+    /// When false, `detect_opportunities` never emits the SOL-Up + BTC-Down
+    /// hedge. Lets an operator with a directional view (or one hedge that's
+    /// historically underperformed) restrict trading to a single direction
+    /// without the full pluggable-strategy machinery. Defaults to true
+    /// (trade both directions, the original behavior).
+    #[serde(default = "default_strategy_enabled")]
+    pub enable_sol_up_btc_down: bool,
+    /// Same as `enable_sol_up_btc_down`, for the SOL-Down + BTC-Up hedge.
+    #[serde(default = "default_strategy_enabled")]
+    pub enable_sol_down_btc_up: bool,
+    /// Opt-in sanity check: when set, `check_arbitrage` rejects a leg unless
+    /// its ask is within this fraction of that token's last traded price
+    /// (e.g. `0.05` allows the ask to sit up to 5% away). Catches a stale or
+    /// manipulated quote that's wildly out of line with what actually just
+    /// traded. `None` (the default) disables the check entirely, which also
+    /// spares `MarketMonitor::fetch_token_price` the extra CLOB request per
+    /// token per tick that populating `TokenPrice::last` would otherwise
+    /// cost.
+    #[serde(default)]
+    pub last_trade_price_band_pct: Option<f64>,
+    /// On graceful shutdown (Ctrl+C), how long to keep polling
+    /// `check_pending_trades` for near-to-settle trades to resolve and be
+    /// booked before exiting, rather than leaving them dangling for the
+    /// startup recovery path. Polled at `settlement_check_interval_ms`.
+    /// `None` (the default) skips the wait entirely, preserving the
+    /// original immediate-exit behavior. A trade that doesn't settle within
+    /// the timeout falls back to `recover_resolved_trades_on_startup` on
+    /// the next run.
+    #[serde(default)]
+    pub shutdown_settlement_wait_secs: Option<u64>,
+    /// Maximum number of claimed trades `settle_claimed_trades` will check
+    /// and settle concurrently in a single tick, via a bounded
+    /// `buffer_unordered`. A backlog of many pending trades settling all at
+    /// once could burst a large number of `get_market` calls together; this
+    /// caps that burst independently of `PolymarketApi`'s own global
+    /// concurrency limit, trading settlement latency for API pressure.
+    /// Defaults to 4.
+    #[serde(default = "default_settlement_concurrency")]
+    pub settlement_concurrency: usize,
+    /// Opt-in check: after a real order fills, `execute_real_trade` compares
+    /// its realized average fill price against the price the opportunity was
+    /// detected at, and flags the trade if the slippage exceeds this fraction
+    /// (e.g. `0.02` allows up to 2% worse than detected before flagging).
+    /// `None` (the default) disables the check entirely - slippage-adjusted
+    /// limit prices already bound the worst case per order, so this is for
+    /// operators who additionally want to catch a market's microstructure
+    /// degrading across many trades before it erodes the edge. Realized
+    /// slippage is recorded on the trade regardless of whether this is set.
+    #[serde(default)]
+    pub max_fill_slippage_pct: Option<f64>,
+    /// When true, a fill slippage breach (see `max_fill_slippage_pct`) stops
+    /// `execute_arbitrage` from entering any further trades until the
+    /// process is restarted, rather than only warning. Existing pending
+    /// trades still settle normally. Defaults to false: flag and keep
+    /// trading, since a single bad fill isn't necessarily evidence the
+    /// market has actually turned.
+    #[serde(default)]
+    pub halt_trading_on_slippage_breach: bool,
+    /// Opt-in deadman's switch: when set, a watchdog task cancels every open
+    /// order via `PolymarketApi::cancel_all_orders` and exits the process if
+    /// `MarketMonitor::seconds_since_last_tick` exceeds this many seconds -
+    /// the monitor loop having stalled (hung, deadlocked, or the process
+    /// otherwise wedged) is exactly when resting orders are most dangerous,
+    /// since nothing is left watching them to react to a fill or a moved
+    /// market. `None` (the default) disables the watchdog entirely.
+    #[serde(default)]
+    pub watchdog_stall_threshold_secs: Option<u64>,
+    /// Opt-in tail hedge: when set, entering a trade also buys a position in
+    /// the opposing outcome combination (see `HedgeCandidate`), sized as this
+    /// fraction of the trade's dollar expected profit rather than its
+    /// principal - a small tax on the edge that caps the loss in the
+    /// catastrophic both-legs-lose case. E.g. `0.5` spends half the expected
+    /// profit on the hedge. `None` (the default) disables hedging entirely,
+    /// the original behavior. Has no effect when a trade's opportunity has no
+    /// `hedge_candidate` (the opposing pair's prices weren't both available
+    /// at detection).
+    #[serde(default)]
+    pub tail_hedge_fraction: Option<f64>,
+    /// Path to a JSON file used to coordinate multiple `Trader` instances
+    /// (see `crate::shared_state::FileSharedState`) against a shared
+    /// deployed-capital limit and to dedupe trades on the same market pair
+    /// across instances. `None` (the default) is fully standalone - no
+    /// coordination, the original behavior.
+    #[serde(default)]
+    pub shared_state_path: Option<PathBuf>,
+    /// Shared deployed-capital ceiling enforced across every instance
+    /// pointed at the same `shared_state_path`, analogous to
+    /// `max_lifetime_deployed` but tracked centrally instead of
+    /// per-process. Required (checked at startup) when `shared_state_path`
+    /// is set.
+    #[serde(default)]
+    pub max_shared_deployed: Option<f64>,
+    /// How long, in milliseconds, `shared_state_path`'s coordination
+    /// reserve/release calls wait to acquire the on-disk lock before giving
+    /// up. Defaults to 5000 (5s).
+    #[serde(default = "default_shared_state_lock_timeout_ms")]
+    pub shared_state_lock_timeout_ms: u64,
+    /// Tick size used to quantize order prices (see `crate::order_format`)
+    /// before they're sent to the CLOB. `validate_order` still catches a
+    /// price that ends up off-tick server-side; this only controls how a
+    /// price is rounded locally beforehand. Defaults to 0.01, Polymarket's
+    /// standard tick.
+    #[serde(default = "default_price_tick_size")]
+    pub price_tick_size: rust_decimal::Decimal,
+    /// Lot size used to quantize order sizes (see `crate::order_format`)
+    /// before they're sent to the CLOB. Defaults to 0.000001, matching the
+    /// six decimal places order sizes were previously formatted to.
+    #[serde(default = "default_size_lot_size")]
+    pub size_lot_size: rust_decimal::Decimal,
+    /// How `price_tick_size`/`size_lot_size` quantization resolves a value
+    /// that isn't already on a tick/lot boundary. Defaults to
+    /// `Conservative` (round away from us rather than toward us), the
+    /// original effective behavior.
+    #[serde(default)]
+    pub price_rounding_mode: crate::order_format::RoundingMode,
+}
+
+fn default_market_cache_max_age_secs() -> u64 {
+    3600
+}
+
+fn default_settlement_concurrency() -> usize {
+    4
+}
+
+fn default_strategy_enabled() -> bool {
+    true
+}
+
+fn default_settlement_check_interval_ms() -> u64 {
+    30_000
+}
+
+fn default_settlement_idle_check_interval_ms() -> u64 {
+    300_000
+}
+
+fn default_discovery_check_interval_ms() -> u64 {
+    60_000
+}
+
+fn default_asset_enabled() -> bool {
+    true
+}
+
+/// A configurable (SOL outcome, BTC outcome) pairing to buy together. Values
+/// are "Up" or "Down" (case-insensitive); see `LegCombination::parse`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct LegCombination {
+    pub sol_outcome: String,
+    pub btc_outcome: String,
+}
+
+impl LegCombination {
+    /// Parses `sol_outcome`/`btc_outcome` into `(sol_up, btc_up)` bools for
+    /// `arbitrage::LegCombination`. Fails on anything other than "Up" or
+    /// "Down" (case-insensitive, surrounding whitespace ignored).
+    pub fn parse(&self) -> anyhow::Result<(bool, bool)> {
+        Ok((Self::parse_outcome(&self.sol_outcome)?, Self::parse_outcome(&self.btc_outcome)?))
+    }
+
+    fn parse_outcome(outcome: &str) -> anyhow::Result<bool> {
+        match outcome.trim().to_uppercase().as_str() {
+            "UP" => Ok(true),
+            "DOWN" => Ok(false),
+            other => anyhow::bail!("invalid leg outcome \"{}\": must be \"Up\" or \"Down\"", other),
+        }
+    }
+}
+
+/// A UTC time-of-day range, e.g. `{"start_utc": "13:00", "end_utc": "21:00"}`.
+/// `end_utc` may be numerically before `start_utc` to represent a window
+/// that crosses midnight, e.g. `{"start_utc": "22:00", "end_utc": "02:00"}`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TradingWindow {
+    pub start_utc: String,
+    pub end_utc: String,
+}
+
+fn default_period_duration_secs() -> u64 {
+    900
+}
+
+fn default_last_look_tolerance() -> f64 {
+    0.0
+}
+
+fn default_price_ema_alpha() -> f64 {
+    0.3
+}
+
+fn default_heartbeat_interval_ms() -> u64 {
+    60_000
+}
+
+fn default_price_history_len() -> usize {
+    200
+}
+
+fn default_min_total_cost() -> f64 {
+    0.0
+}
+
+fn default_max_total_cost() -> f64 {
+    1.0
+}
+
+fn default_order_time_in_force() -> String {
+    "GTC".to_string()
+}
+
+fn default_period_boundary_tolerance_secs() -> u64 {
+    30
+}
+
+fn default_min_order_size() -> f64 {
+    5.0
+}
+
+fn default_min_sane_price() -> f64 {
+    0.001
+}
+
+fn default_cross_check_tolerance_pct() -> f64 {
+    0.10
+}
+
+fn default_max_sane_price() -> f64 {
+    0.999
+}
+
+fn default_up_outcome_keywords() -> Vec<String> {
+    vec!["UP".to_string(), "1".to_string()]
+}
+
+fn default_down_outcome_keywords() -> Vec<String> {
+    vec!["DOWN".to_string(), "0".to_string()]
+}
+
+fn default_trade_sample_rate() -> f64 {
+    1.0
+}
+
+fn default_shared_state_lock_timeout_ms() -> u64 {
+    5000
+}
+
+fn default_price_tick_size() -> rust_decimal::Decimal {
+    rust_decimal_macros::dec!(0.01)
+}
+
+fn default_size_lot_size() -> rust_decimal::Decimal {
+    rust_decimal_macros::dec!(0.000001)
+}
+
+fn default_leg_combinations() -> Vec<LegCombination> {
+    vec![
+        LegCombination {
+            sol_outcome: "Up".to_string(),
+            btc_outcome: "Down".to_string(),
+        },
+        LegCombination {
+            sol_outcome: "Down".to_string(),
+            btc_outcome: "Up".to_string(),
+        },
+    ]
 }
 
 impl Default for Config {
@@ -45,26 +848,149 @@ impl Default for Config {
                 clob_api_url: "https://clob.polymarket.com".to_string(),
                 ws_url: "wss://clob-ws.polymarket.com".to_string(),
                 api_key: None,
+                data_api_key: None,
+                max_concurrent_requests: default_max_concurrent_requests(),
+                order_timeout_ms: default_order_timeout_ms(),
+                log_raw_responses: false,
+                http_proxy: None,
+                socks_proxy: None,
+                failure_budget: None,
+                max_calls_per_period: None,
+                funder_address: None,
+                user_agent: None,
+                extra_headers: std::collections::HashMap::new(),
+                chaos: None,
             },
             trading: TradingConfig {
                 min_profit_threshold: 0.01,
                 max_position_size: 100.0,
+                max_depth_fraction: None,
+                min_book_depth: 0.0,
                 sol_condition_id: None,
                 btc_condition_id: None,
                 check_interval_ms: 1000,
+                round_units_to_whole: false,
+                min_order_size: default_min_order_size(),
+                redemption_cost_estimate: 0.0,
+                require_profit_above_redemption_cost: false,
+                max_order_notional: None,
+                enable_order_splitting: false,
+                order_split_delay_ms: 0,
+                inter_leg_delay_max_ms: 0,
+                token_cache_path: None,
+                up_outcome_keywords: default_up_outcome_keywords(),
+                down_outcome_keywords: default_down_outcome_keywords(),
+                late_profit_threshold: None,
+                log_profit_threshold: None,
+                adversarial_loss_probability: None,
+                period_duration_secs: default_period_duration_secs(),
+                last_look_tolerance: default_last_look_tolerance(),
+                price_ema_alpha: default_price_ema_alpha(),
+                require_smoothed_confirmation: false,
+                heartbeat_interval_ms: default_heartbeat_interval_ms(),
+                trust_midpoint_for_execution: false,
+                price_history_len: default_price_history_len(),
+                min_total_cost: default_min_total_cost(),
+                max_total_cost: default_max_total_cost(),
+                order_time_in_force: default_order_time_in_force(),
+                period_boundary_tolerance_secs: default_period_boundary_tolerance_secs(),
+                validate_orders_before_placement: false,
+                min_sane_price: default_min_sane_price(),
+                max_sane_price: default_max_sane_price(),
+                price_inversion_policy: crate::monitor::PriceInversionPolicy::default(),
+                price_source_preference: crate::monitor::PriceSourcePreference::default(),
+                max_consecutive_price_failures: None,
+                cross_check_source: None,
+                cross_check_tolerance_pct: default_cross_check_tolerance_pct(),
+                skip_trading_on_cross_check_mismatch: false,
+                trading_windows: Vec::new(),
+                max_lifetime_deployed: None,
+                allow_non_50_50_markets: false,
+                leg_combinations: default_leg_combinations(),
+                trade_sample_rate: default_trade_sample_rate(),
+                trade_sample_seed: None,
+                post_rollover_grace_ms: 0,
+                sol_enabled: default_asset_enabled(),
+                btc_enabled: default_asset_enabled(),
+                settlement_check_interval_ms: default_settlement_check_interval_ms(),
+                settlement_idle_check_interval_ms: default_settlement_idle_check_interval_ms(),
+                discovery_check_interval_ms: default_discovery_check_interval_ms(),
+                taker_fee_bps: 0.0,
+                market_cache_max_age_secs: default_market_cache_max_age_secs(),
+                enable_sol_up_btc_down: default_strategy_enabled(),
+                enable_sol_down_btc_up: default_strategy_enabled(),
+                last_trade_price_band_pct: None,
+                shutdown_settlement_wait_secs: None,
+                settlement_concurrency: default_settlement_concurrency(),
+                max_fill_slippage_pct: None,
+                halt_trading_on_slippage_breach: false,
+                watchdog_stall_threshold_secs: None,
+                tail_hedge_fraction: None,
+                shared_state_path: None,
+                max_shared_deployed: None,
+                shared_state_lock_timeout_ms: default_shared_state_lock_timeout_ms(),
+                price_tick_size: default_price_tick_size(),
+                size_lot_size: default_size_lot_size(),
+                price_rounding_mode: crate::order_format::RoundingMode::default(),
             },
+            tracing: TracingConfig::default(),
+        }
+    }
+}
+
+/// Which serde format a config file is read/written in, chosen from its
+/// path's extension. JSON is the default for any extension `load` doesn't
+/// recognize (including none at all), preserving the original behavior for
+/// existing configs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ConfigFormat {
+    Json,
+    Toml,
+    Yaml,
+}
+
+impl ConfigFormat {
+    fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("toml") => ConfigFormat::Toml,
+            Some("yaml") | Some("yml") => ConfigFormat::Yaml,
+            _ => ConfigFormat::Json,
+        }
+    }
+
+    fn parse(&self, content: &str) -> anyhow::Result<Config> {
+        match self {
+            ConfigFormat::Json => Ok(serde_json::from_str(content)?),
+            ConfigFormat::Toml => Ok(toml::from_str(content)?),
+            ConfigFormat::Yaml => Ok(serde_yaml::from_str(content)?),
+        }
+    }
+
+    fn serialize(&self, config: &Config) -> anyhow::Result<String> {
+        match self {
+            ConfigFormat::Json => Ok(serde_json::to_string_pretty(config)?),
+            ConfigFormat::Toml => Ok(toml::to_string_pretty(config)?),
+            ConfigFormat::Yaml => Ok(serde_yaml::to_string(config)?),
         }
     }
 }
 
 impl Config {
+    /// Loads a config from `path`, or writes and returns a fresh default one
+    /// if it doesn't exist yet. The format - JSON, TOML, or YAML - is chosen
+    /// from `path`'s extension (`.toml`, `.yaml`/`.yml`, anything else falls
+    /// back to JSON), so a freshly-created default config is written in
+    /// whichever format the caller asked for by naming the file.
     pub fn load(path: &PathBuf) -> anyhow::Result<Self> {
+        let format = ConfigFormat::from_path(path);
         if path.exists() {
             let content = std::fs::read_to_string(path)?;
-            Ok(serde_json::from_str(&content)?)
+            format.parse(&content).with_context(|| {
+                format!("failed to parse {:?} as {:?} config", path, format)
+            })
         } else {
             let config = Config::default();
-            let content = serde_json::to_string_pretty(&config)?;
+            let content = format.serialize(&config)?;
             std::fs::write(path, content)?;
             Ok(config)
         }
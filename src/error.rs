@@ -0,0 +1,70 @@
+use thiserror::Error;
+
+/// Errors returned by `PolymarketApi`, granular enough for callers to branch
+/// on kind — e.g. back off and retry on `RateLimited`, trip a circuit
+/// breaker after repeated `Http`/`Network` failures — rather than matching
+/// on message text.
+#[derive(Debug, Error)]
+pub enum ApiError {
+    #[error("rate limited by upstream API")]
+    RateLimited,
+
+    #[error("HTTP {status} error: {body}")]
+    Http { status: u16, body: String },
+
+    #[error("authentication failed (HTTP {status}): {body}")]
+    Unauthorized { status: u16, body: String },
+
+    #[error("failed to parse response: {0}")]
+    Parse(String),
+
+    #[error("network request failed: {0}")]
+    Network(String),
+
+    #[error("invalid response format: {0}")]
+    InvalidResponse(String),
+
+    #[error("invalid configuration: {0}")]
+    Config(String),
+}
+
+impl From<reqwest::Error> for ApiError {
+    fn from(err: reqwest::Error) -> Self {
+        ApiError::Network(err.to_string())
+    }
+}
+
+/// Errors returned by `Trader`, layered on top of `ApiError` so trading
+/// logic can distinguish an upstream API failure from a domain condition
+/// like a closed market or a book too thin to fill.
+#[derive(Debug, Error)]
+pub enum TraderError {
+    #[error(transparent)]
+    Api(#[from] ApiError),
+
+    #[error("market {condition_id} is closed")]
+    MarketClosed { condition_id: String },
+
+    #[error("insufficient liquidity for token {token_id}")]
+    InsufficientLiquidity { token_id: String },
+
+    #[error("no pending trade found for key {key}")]
+    TradeNotFound { key: String },
+
+    #[error("{operation} is only available in simulation mode")]
+    NotSimulationMode { operation: String },
+}
+
+/// Errors from the optional shared-state backend (see `crate::shared_state`),
+/// coordinating capital/dedup across multiple `Trader` instances.
+#[derive(Debug, Error)]
+pub enum SharedStateError {
+    #[error("failed to acquire shared-state lock within {timeout_ms}ms")]
+    LockTimeout { timeout_ms: u64 },
+
+    #[error("shared-state I/O error: {0}")]
+    Io(String),
+
+    #[error("failed to parse shared-state file: {0}")]
+    Parse(String),
+}
@@ -0,0 +1,15 @@
+pub mod api;
+pub mod arbitrage;
+pub mod book_feed;
+pub mod config;
+pub mod error;
+pub mod models;
+pub mod monitor;
+pub mod order_format;
+pub mod recorder;
+pub mod replay;
+pub mod shared_state;
+pub mod stats;
+pub mod telemetry;
+pub mod trade_log;
+pub mod trader;
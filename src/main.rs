@@ -1,19 +1,17 @@
-mod api;
-mod arbitrage;
-mod config;
-mod models;
-mod monitor;
-mod trader;
+use polymarket_arbitrage_bot::{api, arbitrage, config, models, monitor, recorder, replay, shared_state, telemetry, trade_log, trader};
 
 use anyhow::{Context, Result};
 use clap::Parser;
 use config::{Args, Config};
-use log::{info, warn};
+use log::{debug, info, warn};
+use std::io::IsTerminal;
 use std::sync::Arc;
 
 use api::PolymarketApi;
 use arbitrage::ArbitrageDetector;
 use monitor::MarketMonitor;
+use recorder::SnapshotRecorder;
+use trade_log::TradeLogger;
 use trader::Trader;
 
 #[tokio::main]
@@ -24,20 +22,145 @@ async fn main() -> Result<()> {
 
     let args = Args::parse();
     let config = Config::load(&args.config)?;
+    log_effective_config_summary(&args, &config);
+
+    if config.trading.adversarial_loss_probability.is_some() && !args.simulation {
+        anyhow::bail!(
+            "adversarial_loss_probability is a simulation-only stress test and cannot be set in production mode"
+        );
+    }
+
+    if !(0.0..=1.0).contains(&config.trading.trade_sample_rate) {
+        anyhow::bail!(
+            "trade_sample_rate must be between 0.0 and 1.0, got {}",
+            config.trading.trade_sample_rate
+        );
+    }
+
+    if let Some(chaos) = &config.polymarket.chaos {
+        if !args.simulation {
+            anyhow::bail!(
+                "polymarket.chaos is a simulation-only stress test and cannot be enabled in production mode"
+            );
+        }
+        if !(0.0..=1.0).contains(&chaos.failure_rate) {
+            anyhow::bail!(
+                "polymarket.chaos.failure_rate must be between 0.0 and 1.0, got {}",
+                chaos.failure_rate
+            );
+        }
+    }
+
+    if config.trading.shared_state_path.is_some() && config.trading.max_shared_deployed.is_none() {
+        anyhow::bail!(
+            "trading.max_shared_deployed must be set when trading.shared_state_path is configured"
+        );
+    }
+
+    if let Some(funder_address) = &config.polymarket.funder_address {
+        if !api::is_valid_evm_address(funder_address) {
+            anyhow::bail!(
+                "polymarket.funder_address '{}' is not a valid EVM address (expected 0x followed by 40 hex digits)",
+                funder_address
+            );
+        }
+    }
+
+    if !args.simulation && config.polymarket.api_key.is_none() {
+        anyhow::bail!(
+            "polymarket.api_key (trading credentials) must be set before running with --simulation=false"
+        );
+    }
+
+    if args.skip_self_test {
+        warn!("⚠️  Skipping settlement self-test (--skip-self-test)");
+    } else {
+        trader::run_settlement_self_test()
+            .map_err(anyhow::Error::msg)
+            .context("Settlement self-test failed; refusing to start")?;
+        info!("✅ Settlement self-test passed");
+    }
+
+    telemetry::init(&config.tracing).context("failed to initialize OpenTelemetry trace export")?;
 
     info!("🚀 Starting Polymarket Arbitrage Bot");
     info!("Mode: {}", if args.simulation { "SIMULATION" } else { "PRODUCTION" });
+    confirm_real_money_trading(&args, &config)?;
 
     // Initialize API client
-    let api = Arc::new(PolymarketApi::new(
+    let mut api_client = PolymarketApi::with_config(
         config.polymarket.gamma_api_url.clone(),
         config.polymarket.clob_api_url.clone(),
         config.polymarket.api_key.clone(),
-    ));
+        config.polymarket.max_concurrent_requests,
+        config.polymarket.order_timeout_ms,
+    )
+    .with_log_raw_responses(config.polymarket.log_raw_responses)
+    .with_data_api_key(config.polymarket.data_api_key.clone())
+    .with_funder_address(config.polymarket.funder_address.clone())
+    .with_user_agent_and_headers(
+        config.polymarket.user_agent.clone(),
+        config.polymarket.extra_headers.clone(),
+    )
+    .context("failed to configure User-Agent/headers")?
+    .with_proxies(
+        config.polymarket.http_proxy.as_deref(),
+        config.polymarket.socks_proxy.as_deref(),
+    )
+    .context("failed to configure HTTP proxy")?;
+
+    if let Some(budget) = &config.polymarket.failure_budget {
+        api_client = api_client.with_failure_budget(api::FailureBudgetConfig {
+            max_failure_rate: budget.max_failure_rate,
+            window: std::time::Duration::from_secs(budget.window_secs),
+            min_samples: budget.min_samples,
+        });
+    }
+
+    if let Some(max_calls_per_period) = config.polymarket.max_calls_per_period {
+        api_client = api_client.with_call_budget(api::CallBudgetConfig {
+            max_calls_per_period,
+            period_secs: config.trading.period_duration_secs,
+        });
+    }
+
+    if let Some(chaos) = &config.polymarket.chaos {
+        warn!("☢️  Chaos testing enabled: injecting failures at a {:.0}% rate into the price/market/book/order endpoints", chaos.failure_rate * 100.0);
+        api_client = api_client.with_chaos_testing(api::ChaosConfig {
+            failure_rate: chaos.failure_rate,
+            seed: chaos.seed,
+        });
+    }
+
+    let api = Arc::new(api_client);
+
+    if let Some(budget) = config.polymarket.failure_budget.clone() {
+        let api_for_budget = api.clone();
+        let check_interval = std::time::Duration::from_millis(budget.check_interval_ms);
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(check_interval);
+            loop {
+                interval.tick().await;
+                if api_for_budget.failure_budget_breached() {
+                    log::error!(
+                        "💥 API failure budget breached (>{:.0}% of calls failed over the trailing {}s window) - exiting so a supervisor can restart",
+                        budget.max_failure_rate * 100.0,
+                        budget.window_secs
+                    );
+                    std::process::exit(1);
+                }
+            }
+        });
+    }
+
+    if let Some(path) = &args.replay {
+        info!("🔍 Replaying trade history from {}", path.display());
+        return replay::replay_trade_history(&api, path).await;
+    }
 
     // Get market data for SOL and BTC markets
-    let (sol_market_data, btc_market_data) = 
-        get_or_discover_markets(&api, &config).await?;
+    let (sol_market_data, btc_market_data) =
+        discover_markets_with_optional_wait(&api, &config, args.wait_for_markets).await?;
 
     info!("SOL Market: {} (Condition ID: {})", sol_market_data.slug, sol_market_data.condition_id);
     info!("BTC Market: {} (Condition ID: {})", btc_market_data.slug, btc_market_data.condition_id);
@@ -48,16 +171,125 @@ async fn main() -> Result<()> {
         sol_market_data,
         btc_market_data,
         config.trading.check_interval_ms,
+        config.trading.period_duration_secs,
+        config.trading.token_cache_path.clone(),
+        config.trading.up_outcome_keywords.clone(),
+        config.trading.down_outcome_keywords.clone(),
+        config.trading.price_ema_alpha,
+        config.trading.price_history_len,
+        config.trading.period_boundary_tolerance_secs,
+        config.trading.min_sane_price,
+        config.trading.max_sane_price,
+        config.trading.allow_non_50_50_markets,
+        config.trading.price_inversion_policy,
+        config.trading.last_trade_price_band_pct.is_some(),
+        config.trading.price_source_preference,
+        config.trading.max_consecutive_price_failures,
+        config.trading.cross_check_source,
+        config.trading.cross_check_tolerance_pct,
+        config.trading.skip_trading_on_cross_check_mismatch,
     );
     let monitor_arc = Arc::new(monitor);
 
-    let detector = ArbitrageDetector::new(config.trading.min_profit_threshold);
+    match monitor_arc.try_warm_start().await {
+        Ok(true) => info!("♻️  Warm-started token IDs from cache"),
+        Ok(false) => {}
+        Err(e) => warn!("Failed to warm-start token cache: {}", e),
+    }
+
+    let redemption_buffer = if config.trading.require_profit_above_redemption_cost {
+        config.trading.redemption_cost_estimate
+    } else {
+        0.0
+    };
+    let early_threshold = config.trading.min_profit_threshold + redemption_buffer;
+    let late_threshold = config
+        .trading
+        .late_profit_threshold
+        .unwrap_or(config.trading.min_profit_threshold)
+        + redemption_buffer;
+    let leg_combinations = parse_leg_combinations(&config.trading.leg_combinations)?;
+    let detector = ArbitrageDetector::with_time_scaled_threshold(
+        early_threshold,
+        late_threshold,
+        config.trading.period_duration_secs,
+    )
+    .with_smoothed_confirmation(config.trading.require_smoothed_confirmation)
+    .with_total_cost_bounds(config.trading.min_total_cost, config.trading.max_total_cost)
+    .with_leg_combinations(leg_combinations)
+    .with_assets_enabled(config.trading.sol_enabled, config.trading.btc_enabled)
+    .with_strategies_enabled(config.trading.enable_sol_up_btc_down, config.trading.enable_sol_down_btc_up)
+    .with_last_trade_price_band(config.trading.last_trade_price_band_pct)
+    .with_log_profit_threshold(config.trading.log_profit_threshold);
+    let trade_logger = match &args.trade_log {
+        Some(path) => {
+            let logger = Arc::new(TradeLogger::new(path)?);
+            info!("🧾 Logging settled trades to {}", path.display());
+
+            let flush_logger = logger.clone();
+            tokio::spawn(async move {
+                let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(5));
+                loop {
+                    interval.tick().await;
+                    if let Err(e) = flush_logger.flush().await {
+                        warn!("Failed to flush trade logger: {}", e);
+                    }
+                }
+            });
+
+            Some(logger)
+        }
+        None => None,
+    };
+    if let Some(path) = &args.stats_file {
+        info!("📊 Persisting lifetime stats to {}", path.display());
+    }
+    let shared_state_backend: Option<Arc<dyn shared_state::SharedStateBackend>> =
+        config.trading.shared_state_path.clone().map(|path| {
+            info!("🤝 Coordinating shared bankroll/dedup with other instances via {}", path.display());
+            Arc::new(shared_state::FileSharedState::with_lock_timeout(
+                path,
+                std::time::Duration::from_millis(config.trading.shared_state_lock_timeout_ms),
+            )) as Arc<dyn shared_state::SharedStateBackend>
+        });
     let trader = Trader::new(
         api.clone(),
         config.trading.clone(),
         args.simulation,
+        trade_logger,
+        args.stats_file.clone(),
+        shared_state_backend,
     );
 
+    if let Err(e) = trader.reconcile_positions().await {
+        warn!("Failed to reconcile pending trades against on-exchange positions: {}", e);
+    }
+
+    if let Err(e) = trader.recover_resolved_trades_on_startup().await {
+        warn!("Failed to recover already-resolved pending trades on startup: {}", e);
+    }
+
+    let recorder = match &args.record {
+        Some(path) => {
+            let recorder = Arc::new(SnapshotRecorder::new(path)?);
+            info!("📼 Recording market snapshots to {}", path.display());
+
+            let flush_recorder = recorder.clone();
+            tokio::spawn(async move {
+                let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(5));
+                loop {
+                    interval.tick().await;
+                    if let Err(e) = flush_recorder.flush().await {
+                        warn!("Failed to flush snapshot recorder: {}", e);
+                    }
+                }
+            });
+
+            Some(recorder)
+        }
+        None => None,
+    };
+
     // Start monitoring
     let detector_clone = detector.clone();
     let trader_arc = Arc::new(trader);
@@ -65,99 +297,475 @@ async fn main() -> Result<()> {
     let monitor_for_trading = monitor_arc.clone();
     let api_for_discovery = api.clone();
     
-    // Start a background task to check pending trades periodically
-    // Check every 30 seconds to catch market closures quickly (markets close after 15 minutes)
+    // Start a background task to check pending trades for settlement. The
+    // sleep between checks adapts to `Trader::next_settlement_check_delay`
+    // rather than ticking on a fixed interval: idle (settlement_idle_check_
+    // interval_ms) when nothing is pending, settlement_check_interval_ms
+    // once a trade's settlement window is actually open, so quiet periods
+    // don't wake this task for nothing.
     let trader_check = trader_clone.clone();
     tokio::spawn(async move {
-        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(30)); // Check every 30 seconds
         loop {
-            interval.tick().await;
+            tokio::time::sleep(trader_check.next_settlement_check_delay().await).await;
             if let Err(e) = trader_check.check_pending_trades().await {
                 warn!("Error checking pending trades: {}", e);
             }
         }
     });
 
+    // Start a heartbeat task that logs a concise health summary at a
+    // configurable interval, so quiet periods (no trades) still show the
+    // bot is alive without cranking log level to debug.
+    let monitor_for_heartbeat = monitor_arc.clone();
+    let trader_for_heartbeat = trader_clone.clone();
+    let start_time = std::time::Instant::now();
+    let heartbeat_interval_ms = config.trading.heartbeat_interval_ms;
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(tokio::time::Duration::from_millis(heartbeat_interval_ms));
+        loop {
+            interval.tick().await;
+
+            let period_end = monitor_for_heartbeat.current_period_end_unix().await;
+            let (total_profit, trades_executed) = trader_for_heartbeat.get_stats().await;
+            let pending_trades = trader_for_heartbeat.pending_trade_count().await;
+            let total_deployed = trader_for_heartbeat.total_deployed().await;
+            let trades_skipped_by_sampling = trader_for_heartbeat.trades_skipped_by_sampling();
+            let sol_btc_correlation = monitor_for_heartbeat.sol_btc_price_correlation().await;
+            let (prices, sol_up_history_summary) = match monitor_for_heartbeat.latest_snapshot().await {
+                Some(snapshot) => {
+                    let history_summary = match snapshot.sol_market.up_token.as_ref() {
+                        Some(t) => {
+                            let history = monitor_for_heartbeat.price_history(&t.token_id).await;
+                            match history.last() {
+                                Some(latest) => format!(
+                                    "{} samples, latest @{} bid={:?} ask={:?}",
+                                    history.len(), latest.timestamp_unix, latest.bid, latest.ask
+                                ),
+                                None => "0 samples".to_string(),
+                            }
+                        }
+                        None => "0 samples".to_string(),
+                    };
+                    (
+                        format!(
+                            "SOL up/down={:?}/{:?} BTC up/down={:?}/{:?}",
+                            snapshot.sol_market.up_token.as_ref().map(|t| t.ask_price()),
+                            snapshot.sol_market.down_token.as_ref().map(|t| t.ask_price()),
+                            snapshot.btc_market.up_token.as_ref().map(|t| t.ask_price()),
+                            snapshot.btc_market.down_token.as_ref().map(|t| t.ask_price()),
+                        ),
+                        history_summary,
+                    )
+                }
+                None => ("no snapshot yet".to_string(), "0 samples".to_string()),
+            };
+
+            info!(
+                "💓 heartbeat | uptime={}s | period_end={} | {} (sol_up_history: {}) | pending_trades={} | trades={} | skipped_by_sampling={} | lifetime_profit=${:.4} | lifetime_deployed=${:.4}",
+                start_time.elapsed().as_secs(),
+                period_end,
+                prices,
+                sol_up_history_summary,
+                pending_trades,
+                trades_executed,
+                trades_skipped_by_sampling,
+                total_profit,
+                total_deployed,
+            );
+
+            match sol_btc_correlation {
+                Some(correlation) => info!(
+                    "🔗 sol/btc implied correlation={:.3} (positive means they've been moving together - the both-lose scenario for either hedge leg combination)",
+                    correlation
+                ),
+                None => debug!("🔗 sol/btc implied correlation: not enough overlapping price history yet"),
+            }
+
+            let detailed_stats = trader_for_heartbeat.get_detailed_stats().await;
+            for (strategy, stats) in detailed_stats {
+                info!(
+                    "   📈 {} | profit=${:.4} | trades={} | win_rate={:.1}%",
+                    strategy,
+                    stats.profit,
+                    stats.trades_executed,
+                    stats.win_rate() * 100.0,
+                );
+            }
+        }
+    });
+
     // Start a background task to detect new 15-minute periods and discover new markets
     let monitor_for_period_check = monitor_arc.clone();
     let api_for_period_check = api.clone();
+    let period_secs_for_period_check = config.trading.period_duration_secs;
+    let discovery_check_interval = tokio::time::Duration::from_millis(config.trading.discovery_check_interval_ms);
     tokio::spawn(async move {
-        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(60)); // Check every minute
+        let mut interval = tokio::time::interval(discovery_check_interval);
         loop {
             interval.tick().await;
-            
-            // Check if we need to discover new markets (new period started)
+
+            // Check if we need to (re-)discover markets: either a new period
+            // just started, or one side's discovery failed earlier in the
+            // current period and still needs a retry.
             if monitor_for_period_check.should_discover_new_markets().await {
-                info!("🔄 New 15-minute period detected! Discovering new markets...");
-                
+                let (need_sol, need_btc) = monitor_for_period_check.markets_pending_discovery().await;
+                info!("🔄 New market period detected! Discovering markets (sol={}, btc={})...", need_sol, need_btc);
+
                 let current_time = std::time::SystemTime::now()
                     .duration_since(std::time::UNIX_EPOCH)
                     .unwrap()
                     .as_secs();
-                
+
                 let mut seen_ids = std::collections::HashSet::new();
                 // Get current condition IDs to avoid duplicates
                 let (sol_id, btc_id) = monitor_for_period_check.get_current_condition_ids().await;
                 seen_ids.insert(sol_id);
                 seen_ids.insert(btc_id);
-                
-                // Discover new markets for current period
-                match discover_market(&api_for_period_check, "SOL", "sol", current_time, &mut seen_ids).await {
-                    Ok(sol_market) => {
-                        seen_ids.insert(sol_market.condition_id.clone());
-                        match discover_market(&api_for_period_check, "BTC", "btc", current_time, &mut seen_ids).await {
-                            Ok(btc_market) => {
-                                if let Err(e) = monitor_for_period_check.update_markets(sol_market, btc_market).await {
-                                    warn!("Failed to update markets: {}", e);
-                                }
+
+                // Discover and update whichever market(s) are still
+                // outstanding. Each side is applied independently so a
+                // failure on one doesn't discard a successful discovery on
+                // the other; a side that fails stays flagged as pending and
+                // is retried on the next tick.
+                if need_sol {
+                    match discover_market(&api_for_period_check, "SOL", "sol", current_time, period_secs_for_period_check, &mut seen_ids).await {
+                        Ok(sol_market) => {
+                            seen_ids.insert(sol_market.condition_id.clone());
+                            if let Err(e) = monitor_for_period_check.update_sol_market(sol_market).await {
+                                warn!("Failed to update SOL market: {}", e);
                             }
-                            Err(e) => warn!("Failed to discover new BTC market: {}", e),
                         }
+                        Err(e) => warn!("Failed to discover new SOL market, will retry next tick: {}", e),
+                    }
+                }
+
+                if need_btc {
+                    match discover_market(&api_for_period_check, "BTC", "btc", current_time, period_secs_for_period_check, &mut seen_ids).await {
+                        Ok(btc_market) => {
+                            if let Err(e) = monitor_for_period_check.update_btc_market(btc_market).await {
+                                warn!("Failed to update BTC market: {}", e);
+                            }
+                        }
+                        Err(e) => warn!("Failed to discover new BTC market, will retry next tick: {}", e),
                     }
-                    Err(e) => warn!("Failed to discover new SOL market: {}", e),
                 }
             }
         }
     });
     
-    monitor_arc.start_monitoring(move |snapshot| {
+    // Deadman's switch: if the monitor loop stalls for longer than
+    // `watchdog_stall_threshold_secs`, cancel every resting order and exit
+    // rather than leave orders unattended by a hung process. Disabled by
+    // default since most operators run under a process supervisor that
+    // already restarts a dead process - this is for the more dangerous case
+    // of a process that's still running but no longer making progress.
+    // Production-only: simulation mode never places real orders, so there's
+    // nothing to cancel, and `polymarket.api_key` (needed to authenticate
+    // the cancel call) is permitted even in simulation - firing this against
+    // a real account's resting orders just because the local loop stalled
+    // would be exactly the live side effect simulation mode is meant to rule out.
+    if !args.simulation {
+        if let Some(stall_threshold_secs) = config.trading.watchdog_stall_threshold_secs {
+            let monitor_for_watchdog = monitor_arc.clone();
+            let api_for_watchdog = api.clone();
+            let poll_interval = tokio::time::Duration::from_secs((stall_threshold_secs / 4).max(1));
+            tokio::spawn(async move {
+                let mut interval = tokio::time::interval(poll_interval);
+                loop {
+                    interval.tick().await;
+
+                    if let Some(stalled_secs) = monitor_for_watchdog.seconds_since_last_tick().await {
+                        if stalled_secs >= stall_threshold_secs {
+                            log::error!(
+                                "🐕 Watchdog: monitor loop hasn't ticked in {}s (threshold {}s) - canceling all open orders and exiting",
+                                stalled_secs, stall_threshold_secs
+                            );
+                            if let Err(e) = api_for_watchdog.cancel_all_orders().await {
+                                log::error!("🐕 Watchdog: failed to cancel open orders during stall shutdown: {}", e);
+                            }
+                            std::process::exit(1);
+                        }
+                    }
+                }
+            });
+        }
+    }
+
+    let monitor_for_threshold = monitor_arc.clone();
+    let monitoring = monitor_arc.start_monitoring(move |snapshot| {
         let detector = detector_clone.clone();
         let trader = trader_clone.clone();
-        
+        let recorder = recorder.clone();
+        let monitor_for_threshold = monitor_for_threshold.clone();
+
         async move {
-            let opportunities = detector.detect_opportunities(&snapshot);
-            
+            if let Some(recorder) = &recorder {
+                if let Err(e) = recorder.record(&snapshot).await {
+                    warn!("Failed to record snapshot: {}", e);
+                }
+            }
+
+            let period_end_unix = monitor_for_threshold.current_period_end_unix().await;
+            let opportunities = detector.detect_opportunities(&snapshot, period_end_unix).await;
+            let ms_since_rollover = monitor_for_threshold.ms_since_last_rollover().await;
+            let seconds_until_accepting_orders = monitor_for_threshold.seconds_until_accepting_orders().await;
+
             for opportunity in opportunities {
-                if let Err(e) = trader.execute_arbitrage(&opportunity).await {
+                if let Err(e) = trader
+                    .execute_arbitrage(&opportunity, ms_since_rollover, seconds_until_accepting_orders)
+                    .await
+                {
                     warn!("Error executing trade: {}", e);
                 }
             }
         }
-    }).await;
+    });
+
+    tokio::select! {
+        _ = monitoring => {}
+        _ = tokio::signal::ctrl_c() => {
+            info!("🛑 Shutdown signal received");
+            shutdown_and_wait_for_settlement(&trader_arc, &config).await;
+        }
+    }
+
+    telemetry::shutdown();
 
     Ok(())
 }
 
+/// Runs on Ctrl+C: if `shutdown_settlement_wait_secs` is configured, polls
+/// pending trades for up to that long so near-to-settle trades get booked
+/// before the process exits, rather than left for the next startup's
+/// recovery pass. A no-op wait (returns immediately) when unconfigured,
+/// preserving the original immediate-exit behavior.
+async fn shutdown_and_wait_for_settlement(trader: &Trader, config: &Config) {
+    let Some(timeout_secs) = config.trading.shutdown_settlement_wait_secs else {
+        return;
+    };
+
+    let pending = trader.pending_trade_count().await;
+    if pending == 0 {
+        return;
+    }
+
+    info!("⏳ Waiting up to {}s for {} pending trade(s) to settle before exiting...", timeout_secs, pending);
+    let poll_interval = tokio::time::Duration::from_millis(config.trading.settlement_check_interval_ms);
+    let remaining = trader
+        .wait_for_pending_settlement(tokio::time::Duration::from_secs(timeout_secs), poll_interval)
+        .await;
+
+    if remaining == 0 {
+        info!("✅ All pending trades settled before shutdown");
+    } else {
+        warn!("⚠️  {} trade(s) still pending after the shutdown wait; will be picked up by startup recovery on next run", remaining);
+    }
+}
+
+/// Logs a single structured summary of the *effective* configuration - the
+/// file as read by `Config::load`, plus the CLI mode flag - right after it's loaded,
+/// before any validation runs. An operator scanning startup logs should be
+/// able to catch a misread config (wrong endpoint, a cap left unbounded, a
+/// kill-switch left disabled) without re-deriving it from the raw file.
+/// Anything that could be a credential (API keys, proxy URLs, custom
+/// headers) is reported as present/absent only, never by value.
+fn log_effective_config_summary(args: &Args, config: &Config) {
+    info!(
+        "📋 Config: gamma_api={} clob_api={} ws={}",
+        config.polymarket.gamma_api_url, config.polymarket.clob_api_url, config.polymarket.ws_url
+    );
+    info!(
+        "📋 Config: mode={} api_key={} data_api_key={} funder_address={}",
+        if args.simulation { "SIMULATION" } else { "PRODUCTION" },
+        if config.polymarket.api_key.is_some() { "set" } else { "not set" },
+        if config.polymarket.data_api_key.is_some() { "set" } else { "not set" },
+        config.polymarket.funder_address.as_deref().unwrap_or("not set"),
+    );
+    info!(
+        "📋 Config: min_profit_threshold={} max_position_size=${:.2} max_order_notional={} period_duration_secs={} max_lifetime_deployed={}",
+        config.trading.min_profit_threshold,
+        config.trading.max_position_size,
+        config
+            .trading
+            .max_order_notional
+            .map(|v| format!("${:.2}", v))
+            .unwrap_or_else(|| "unbounded".to_string()),
+        config.trading.period_duration_secs,
+        config
+            .trading
+            .max_lifetime_deployed
+            .map(|v| format!("${:.2}", v))
+            .unwrap_or_else(|| "unbounded".to_string()),
+    );
+    info!(
+        "📋 Config: kill-switches max_fill_slippage_pct={} halt_trading_on_slippage_breach={} max_consecutive_price_failures={} watchdog_stall_threshold_secs={}",
+        config
+            .trading
+            .max_fill_slippage_pct
+            .map(|v| format!("{:.2}%", v * 100.0))
+            .unwrap_or_else(|| "disabled".to_string()),
+        config.trading.halt_trading_on_slippage_breach,
+        config
+            .trading
+            .max_consecutive_price_failures
+            .map(|v| v.to_string())
+            .unwrap_or_else(|| "disabled".to_string()),
+        config
+            .trading
+            .watchdog_stall_threshold_secs
+            .map(|v| v.to_string())
+            .unwrap_or_else(|| "disabled".to_string()),
+    );
+    if config.polymarket.http_proxy.is_some() || config.polymarket.socks_proxy.is_some() {
+        info!(
+            "📋 Config: http_proxy={} socks_proxy={}",
+            if config.polymarket.http_proxy.is_some() { "set" } else { "not set" },
+            if config.polymarket.socks_proxy.is_some() { "set" } else { "not set" },
+        );
+    }
+    if !config.polymarket.extra_headers.is_empty() {
+        let mut header_names: Vec<&str> = config.polymarket.extra_headers.keys().map(String::as_str).collect();
+        header_names.sort_unstable();
+        info!("📋 Config: extra_headers set for [{}] (values redacted)", header_names.join(", "));
+    }
+}
+
+/// Guards against an accidental `--simulation=false` flip: production mode
+/// refuses to place its first real order until this returns Ok, requiring
+/// either the explicit `--i-understand-real-money` flag or an interactive
+/// "yes" typed on a TTY. Always logs the position limits governing the run
+/// first, so the confirmation isn't given blind. A no-op in simulation mode.
+fn confirm_real_money_trading(args: &Args, config: &Config) -> Result<()> {
+    if args.simulation {
+        return Ok(());
+    }
+
+    warn!(
+        "⚠️  PRODUCTION MODE: real funds are at risk. max_position_size=${:.2} max_lifetime_deployed={}",
+        config.trading.max_position_size,
+        config
+            .trading
+            .max_lifetime_deployed
+            .map(|v| format!("${:.2}", v))
+            .unwrap_or_else(|| "unbounded".to_string())
+    );
+
+    if args.i_understand_real_money {
+        info!("✅ Real-money trading acknowledged via --i-understand-real-money");
+        return Ok(());
+    }
+
+    if !std::io::stdin().is_terminal() {
+        anyhow::bail!(
+            "Refusing to start in production mode without --i-understand-real-money (stdin is not a TTY, so an interactive confirmation isn't possible)"
+        );
+    }
+
+    println!("Type \"yes\" to confirm you want to trade real money, or anything else to abort:");
+    let mut input = String::new();
+    std::io::stdin()
+        .read_line(&mut input)
+        .context("Failed to read confirmation from stdin")?;
+    if input.trim() != "yes" {
+        anyhow::bail!("Real-money trading not confirmed; exiting");
+    }
+
+    info!("✅ Real-money trading confirmed interactively");
+    Ok(())
+}
+
+/// How long to wait before the first startup discovery retry when
+/// `--wait-for-markets` is set. Short enough to converge quickly once a new
+/// period's markets go live, long enough not to hammer the Gamma API while
+/// waiting. Doubles after each further failed attempt, up to
+/// `MAX_DISCOVERY_RETRY_INTERVAL`.
+const DISCOVERY_RETRY_INTERVAL: std::time::Duration = std::time::Duration::from_secs(10);
+const MAX_DISCOVERY_RETRY_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// Wraps `get_or_discover_markets` with an optional retry-with-backoff loop.
+/// `wait_for_markets_secs: None` preserves the original fail-fast behavior -
+/// a single attempt, erroring out immediately. `Some(secs)` keeps retrying,
+/// with a delay starting at `DISCOVERY_RETRY_INTERVAL` and doubling up to
+/// `MAX_DISCOVERY_RETRY_INTERVAL`, until either discovery succeeds or
+/// `secs` have elapsed, logging each failed attempt, so a bot started
+/// slightly before a new period's markets exist doesn't die needlessly.
+async fn discover_markets_with_optional_wait(
+    api: &PolymarketApi,
+    config: &Config,
+    wait_for_markets_secs: Option<u64>,
+) -> Result<(models::Market, models::Market)> {
+    let Some(wait_secs) = wait_for_markets_secs else {
+        return get_or_discover_markets(api, config).await;
+    };
+
+    let deadline = std::time::Instant::now() + std::time::Duration::from_secs(wait_secs);
+    let mut attempt: u32 = 1;
+    let mut retry_delay = DISCOVERY_RETRY_INTERVAL;
+
+    loop {
+        match get_or_discover_markets(api, config).await {
+            Ok(markets) => return Ok(markets),
+            Err(e) => {
+                let now = std::time::Instant::now();
+                if now >= deadline {
+                    return Err(e).context(format!(
+                        "market discovery still failing after waiting up to {}s (--wait-for-markets)",
+                        wait_secs
+                    ));
+                }
+                warn!(
+                    "⏳ Market discovery attempt {} failed, retrying in {}s (--wait-for-markets: {}s remaining budget): {}",
+                    attempt,
+                    retry_delay.as_secs(),
+                    deadline.saturating_duration_since(now).as_secs(),
+                    e
+                );
+                tokio::time::sleep(retry_delay).await;
+                retry_delay = (retry_delay * 2).min(MAX_DISCOVERY_RETRY_INTERVAL);
+                attempt += 1;
+            }
+        }
+    }
+}
+
 async fn get_or_discover_markets(
     api: &PolymarketApi,
     config: &Config,
-) -> Result<(crate::models::Market, crate::models::Market)> {
-    use crate::models::Market;
-    
+) -> Result<(models::Market, models::Market)> {
+    if let (Some(sol_condition_id), Some(btc_condition_id)) = (
+        &config.trading.sol_condition_id,
+        &config.trading.btc_condition_id,
+    ) {
+        if sol_condition_id == btc_condition_id {
+            anyhow::bail!(
+                "sol_condition_id and btc_condition_id in config.json are the same: {}",
+                sol_condition_id
+            );
+        }
+
+        let sol_market = market_from_condition_id(api, "SOL", sol_condition_id).await
+            .context("Failed to validate configured sol_condition_id")?;
+        let btc_market = market_from_condition_id(api, "BTC", btc_condition_id).await
+            .context("Failed to validate configured btc_condition_id")?;
+
+        return Ok((sol_market, btc_market));
+    }
+
     let current_time = std::time::SystemTime::now()
         .duration_since(std::time::UNIX_EPOCH)
         .unwrap()
         .as_secs();
-    
+    let period_secs = config.trading.period_duration_secs;
+
     // Try multiple discovery methods - use a set to track seen IDs
     let mut seen_ids = std::collections::HashSet::new();
-    
-    // Use exact slug pattern: sol-updown-15m-{timestamp} and btc-updown-15m-{timestamp}
-    let sol_market = discover_market(api, "SOL", "sol", current_time, &mut seen_ids).await
+
+    // Use exact slug pattern: sol-updown-{duration}-{timestamp} and btc-updown-{duration}-{timestamp}
+    let sol_market = discover_market(api, "SOL", "sol", current_time, period_secs, &mut seen_ids).await
         .context("Failed to discover SOL market")?;
     seen_ids.insert(sol_market.condition_id.clone());
-    
-    let btc_market = discover_market(api, "BTC", "btc", current_time, &mut seen_ids).await
+
+    let btc_market = discover_market(api, "BTC", "btc", current_time, period_secs, &mut seen_ids).await
         .context("Failed to discover BTC market")?;
 
     if sol_market.condition_id == btc_market.condition_id {
@@ -167,39 +775,166 @@ async fn get_or_discover_markets(
     Ok((sol_market, btc_market))
 }
 
+/// Validates and converts the configured leg combinations into the
+/// detector's internal representation. Each combination's outcome strings
+/// must parse as "Up"/"Down", the list must be non-empty, and no two
+/// combinations may resolve to the same (sol_up, btc_up) pair.
+fn parse_leg_combinations(configured: &[config::LegCombination]) -> Result<Vec<arbitrage::LegCombination>> {
+    if configured.is_empty() {
+        anyhow::bail!("config.trading.leg_combinations must not be empty");
+    }
+
+    let mut seen = std::collections::HashSet::new();
+    let mut combinations = Vec::with_capacity(configured.len());
+    for combo in configured {
+        let (sol_up, btc_up) = combo.parse()?;
+        if !seen.insert((sol_up, btc_up)) {
+            anyhow::bail!(
+                "duplicate leg combination: sol_outcome={}, btc_outcome={}",
+                combo.sol_outcome,
+                combo.btc_outcome
+            );
+        }
+        combinations.push(arbitrage::LegCombination::new(sol_up, btc_up));
+    }
+
+    Ok(combinations)
+}
+
+/// Build a `Market` from an operator-supplied condition ID, bypassing slug
+/// discovery entirely. Validates the market actually resolves and is active
+/// so a stale or mistyped ID fails fast at startup instead of silently
+/// monitoring a dead market.
+async fn market_from_condition_id(
+    api: &PolymarketApi,
+    market_name: &str,
+    condition_id: &str,
+) -> Result<models::Market> {
+    use models::Market;
+
+    let details = api
+        .get_market(condition_id)
+        .await
+        .with_context(|| format!("Failed to fetch {} market for condition_id {}", market_name, condition_id))?;
+
+    if !details.active || details.closed {
+        anyhow::bail!(
+            "Configured {} condition_id {} does not resolve to an active market (active={}, closed={})",
+            market_name,
+            condition_id,
+            details.active,
+            details.closed
+        );
+    }
+
+    log::info!(
+        "Using configured {} condition_id: {} | slug: {}",
+        market_name,
+        condition_id,
+        details.market_slug
+    );
+
+    Ok(Market {
+        condition_id: details.condition_id,
+        market_id: None,
+        question: details.question,
+        slug: details.market_slug,
+        resolution_source: None,
+        end_date_iso: Some(details.end_date_iso),
+        end_date_iso_alt: None,
+        active: details.active,
+        closed: details.closed,
+        tokens: None,
+        clob_token_ids: None,
+        outcomes: None,
+    })
+}
+
+/// Page size and overall cap for `discover_market`'s Method 3 active-markets
+/// scan - large enough to cover a normal number of concurrently active
+/// markets without unbounded paging if the active list is unusually large.
+const ACTIVE_MARKETS_SCAN_PAGE_SIZE: u32 = 100;
+const ACTIVE_MARKETS_SCAN_MAX_TOTAL: u32 = 1000;
+
+/// Polymarket's slug convention labels the market duration in minutes, e.g.
+/// "15m" for 900-second periods or "60m" for hourly ones.
+fn duration_slug_label(period_secs: u64) -> String {
+    format!("{}m", period_secs / 60)
+}
+
+/// True when `slug` ends with the exact `-{expected_timestamp}` suffix it
+/// was looked up by. `get_market_by_slug` is expected to return an exact
+/// match, but a fuzzy match or event-ordering quirk on the API side could
+/// hand back a market from a different period; a settlement bot silently
+/// tracking the wrong 15-minute window produces bizarre, hard-to-diagnose
+/// results, so this is checked explicitly rather than trusted.
+fn slug_matches_expected_period(slug: &str, expected_timestamp: u64) -> bool {
+    slug.ends_with(&format!("-{}", expected_timestamp))
+}
+
 async fn discover_market(
     api: &PolymarketApi,
     market_name: &str,
     slug_prefix: &str,
     current_time: u64,
+    period_secs: u64,
     seen_ids: &mut std::collections::HashSet<String>,
-) -> Result<crate::models::Market> {
-    use crate::models::Market;
-    
-    // Method 1: Try to get by slug with current timestamp (rounded to nearest 15min)
-    // Pattern: btc-updown-15m-{timestamp} or sol-updown-15m-{timestamp}
-    let rounded_time = (current_time / 900) * 900; // Round to nearest 15 minutes
-    let slug = format!("{}-updown-15m-{}", slug_prefix, rounded_time);
-    
+) -> Result<models::Market> {
+    let duration_label = duration_slug_label(period_secs);
+
+    // Method 1: Try to get by slug with current timestamp (rounded to the period boundary)
+    // Pattern: btc-updown-{duration}-{timestamp} or sol-updown-{duration}-{timestamp}
+    let rounded_time = (current_time / period_secs) * period_secs;
+    let slug = format!("{}-updown-{}-{}", slug_prefix, duration_label, rounded_time);
+
     if let Ok(market) = api.get_market_by_slug(&slug).await {
-        if !seen_ids.contains(&market.condition_id) && market.active && !market.closed {
+        if !slug_matches_expected_period(&market.slug, rounded_time) {
+            log::warn!(
+                "{} market lookup for slug {} returned mismatched slug {} (expected suffix -{}); rejecting",
+                market_name, slug, market.slug, rounded_time
+            );
+        } else if !seen_ids.contains(&market.condition_id) && market.active && !market.closed {
             log::info!("Found {} market by slug: {} | Condition ID: {}", market_name, market.slug, market.condition_id);
             return Ok(market);
         }
     }
-    
+
     // Method 2: Try a few recent timestamps in case the current one doesn't exist yet
     for offset in 1..=3 {
-        let try_time = rounded_time - (offset * 900); // Try previous 15-minute intervals
-        let try_slug = format!("{}-updown-15m-{}", slug_prefix, try_time);
+        let try_time = rounded_time - (offset * period_secs); // Try previous periods
+        let try_slug = format!("{}-updown-{}-{}", slug_prefix, duration_label, try_time);
         log::info!("Trying previous {} market by slug: {}", market_name, try_slug);
         if let Ok(market) = api.get_market_by_slug(&try_slug).await {
-            if !seen_ids.contains(&market.condition_id) && market.active && !market.closed {
+            if !slug_matches_expected_period(&market.slug, try_time) {
+                log::warn!(
+                    "{} market lookup for slug {} returned mismatched slug {} (expected suffix -{}); rejecting",
+                    market_name, try_slug, market.slug, try_time
+                );
+            } else if !seen_ids.contains(&market.condition_id) && market.active && !market.closed {
                 log::info!("Found {} market by slug: {} | Condition ID: {}", market_name, market.slug, market.condition_id);
                 return Ok(market);
             }
         }
     }
-    
-    anyhow::bail!("Could not find active {} 15-minute up/down market. Please set condition_id in config.json", market_name)
+
+    // Method 3: Fall back to scanning all active markets for a slug that
+    // matches this market's naming convention, in case the expected
+    // periodic slug isn't resolvable by direct lookup yet (e.g. Gamma's
+    // slug index lags event creation). Paginates rather than a single page
+    // so a match isn't missed just because other markets sort ahead of it.
+    match api.get_all_active_markets(ACTIVE_MARKETS_SCAN_PAGE_SIZE, ACTIVE_MARKETS_SCAN_MAX_TOTAL).await {
+        Ok(markets) => {
+            let prefix = format!("{}-updown-{}-", slug_prefix, duration_label);
+            if let Some(market) = markets
+                .into_iter()
+                .find(|m| m.slug.starts_with(&prefix) && !seen_ids.contains(&m.condition_id) && m.active && !m.closed)
+            {
+                log::info!("Found {} market by scanning active markets: {} | Condition ID: {}", market_name, market.slug, market.condition_id);
+                return Ok(market);
+            }
+        }
+        Err(e) => log::warn!("Failed to scan active markets as a discovery fallback: {}", e),
+    }
+
+    anyhow::bail!("Could not find active {} {} up/down market. Please set condition_id in config.json", market_name, duration_label)
 }
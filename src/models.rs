@@ -48,6 +48,21 @@ pub struct TokenPrice {
     pub token_id: String,
     pub bid: Option<Decimal>,
     pub ask: Option<Decimal>,
+    /// Exponentially-smoothed bid, maintained across snapshots to filter out
+    /// single-tick spikes. `None` until the monitor has an EMA history for
+    /// this token.
+    pub smoothed_bid: Option<Decimal>,
+    /// Exponentially-smoothed ask, same rationale as `smoothed_bid`.
+    pub smoothed_ask: Option<Decimal>,
+    /// True when this price came from the `/midpoint` fallback because both
+    /// side-specific `get_price` calls failed, rather than from a real
+    /// bid/ask. Midpoint prices can understate the true cost to trade, so
+    /// callers may want to trust them for detection but not execution.
+    pub is_midpoint_derived: bool,
+    /// Last traded price for this token, fetched only when
+    /// `TradingConfig::last_trade_price_band_pct` is configured. `None` when
+    /// the band check is disabled or the fetch failed.
+    pub last: Option<Decimal>,
 }
 
 impl TokenPrice {
@@ -63,6 +78,42 @@ impl TokenPrice {
     pub fn ask_price(&self) -> Decimal {
         self.ask.unwrap_or(Decimal::ZERO)
     }
+
+    /// Smoothed ask, falling back to the raw ask when no EMA history exists
+    /// yet (e.g. the first tick for a newly-discovered token).
+    pub fn smoothed_ask_price(&self) -> Decimal {
+        self.smoothed_ask.unwrap_or_else(|| self.ask_price())
+    }
+
+    /// True when the book is crossed or locked (bid >= ask), which usually
+    /// indicates a transient data glitch rather than a real opportunity.
+    pub fn is_crossed(&self) -> bool {
+        match (self.bid, self.ask) {
+            (Some(bid), Some(ask)) => bid >= ask,
+            _ => false,
+        }
+    }
+
+    /// True when every populated side (bid and/or ask) falls within
+    /// `[min, max]`. Outcome tokens are bounded in [0, 1], so a price
+    /// outside a band slightly inside that range usually means the upstream
+    /// API returned corrupt data rather than a real quote.
+    pub fn is_within_sane_bounds(&self, min: Decimal, max: Decimal) -> bool {
+        self.bid.is_none_or(|bid| bid >= min && bid <= max) && self.ask.is_none_or(|ask| ask >= min && ask <= max)
+    }
+
+    /// True when `ask` is within `band_pct` of `last` (e.g. `band_pct =
+    /// 0.05` allows the ask to sit up to 5% away from the last trade price
+    /// in either direction). Permissive when there's nothing to compare —
+    /// no ask, no last trade price, or a zero last trade price — since this
+    /// is a sanity check layered on top of the normal bid/ask, not a hard
+    /// requirement that a token has traded recently.
+    pub fn is_within_last_trade_band(&self, band_pct: Decimal) -> bool {
+        match (self.ask, self.last) {
+            (Some(ask), Some(last)) if !last.is_zero() => ((ask - last).abs() / last) <= band_pct,
+            _ => true,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -73,6 +124,23 @@ pub struct OrderRequest {
     pub price: String,
     #[serde(rename = "type")]
     pub order_type: String, // "LIMIT" or "MARKET"
+    /// "GTC", "FOK", or "IOC" per the CLOB spec. Arbitrage legs want FOK/IOC
+    /// so a partial fill on one leg can never leave the position unhedged;
+    /// GTC preserves the original lingering-limit-order behavior.
+    pub time_in_force: String,
+    /// Funder/maker address to fill the order from, for proxy-wallet setups
+    /// where the signing key differs from the wallet holding funds. `None`
+    /// signs and funds from the same address, the original behavior.
+    pub funder: Option<String>,
+}
+
+/// Result of `PolymarketApi::validate_order`: whether the CLOB would accept
+/// the order as-is, and if not, why (price off tick, below min size, market
+/// not accepting orders, insufficient balance, etc.).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderValidation {
+    pub valid: bool,
+    pub reason: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -80,6 +148,44 @@ pub struct OrderResponse {
     pub order_id: Option<String>,
     pub status: String,
     pub message: Option<String>,
+    /// The realized average fill price, when the CLOB reports one. Absent
+    /// for order states that haven't filled anything yet (e.g. still
+    /// resting on the book) or if the response simply doesn't include it -
+    /// callers that want to check fill slippage should treat a missing
+    /// value as "unknown", not "filled at the requested price".
+    #[serde(default)]
+    pub avg_fill_price: Option<Decimal>,
+}
+
+/// An on-exchange position as reported by the CLOB, used to reconcile the
+/// bot's in-memory `pending_trades` against reality on startup.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Position {
+    #[serde(rename = "asset")]
+    pub token_id: String,
+    pub size: Decimal,
+}
+
+/// A single fill from the CLOB's trade history.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TradeHistoryEntry {
+    pub id: String,
+    #[serde(rename = "asset_id")]
+    pub token_id: String,
+    pub side: String,
+    pub size: Decimal,
+    pub price: Decimal,
+}
+
+/// A single (timestamp, bid, ask) observation for a token, kept in a
+/// bounded per-token ring buffer (`MarketMonitor::price_history`) for
+/// debugging price behavior and simple local charting. Not consulted by any
+/// trading logic.
+#[derive(Debug, Clone)]
+pub struct PriceSample {
+    pub timestamp_unix: u64,
+    pub bid: Option<Decimal>,
+    pub ask: Option<Decimal>,
 }
 
 #[derive(Debug, Clone)]
@@ -100,17 +206,103 @@ pub struct ArbitrageOpportunity {
     pub btc_down_token_id: String,
     pub sol_condition_id: String,
     pub btc_condition_id: String,
+    /// True when either leg's price came from the `/midpoint` fallback
+    /// rather than a real bid/ask. Execution should treat this cautiously
+    /// unless midpoint trust is explicitly enabled.
+    pub is_midpoint_derived: bool,
+    /// Which strategy produced this opportunity (see `arbitrage::STRATEGY_*`
+    /// constants), threaded through to settlement for per-strategy P&L
+    /// attribution.
+    pub strategy: String,
+    /// Fraction the combined ask cost could rise before this trade stops
+    /// being profitable, e.g. `0.05` means the two legs' prices could climb
+    /// a combined 5% before `total_cost` reaches $1 and the edge is gone.
+    /// A more intuitive risk figure than the raw `expected_profit` dollar
+    /// amount, since it's normalized to how much room there is rather than
+    /// how big the edge looks.
+    pub breakeven_price_move_pct: Decimal,
+    /// Prices/token ids for the opposing outcome combination (e.g.
+    /// SOL-Down+BTC-Up when this opportunity buys SOL-Up+BTC-Down), read
+    /// from the same detection snapshot. Used to size an optional tail
+    /// hedge against the catastrophic both-legs-lose case - see
+    /// `TradingConfig::tail_hedge_fraction`. `None` when the opposing pair's
+    /// prices weren't both available in the snapshot.
+    pub hedge_candidate: Option<HedgeCandidate>,
+}
+
+/// The opposing outcome combination's prices/token ids for an
+/// `ArbitrageOpportunity`, carried alongside it so a tail hedge can be sized
+/// without a second round-trip to fetch prices already in hand at
+/// detection time.
+#[derive(Debug, Clone)]
+pub struct HedgeCandidate {
+    pub sol_token_id: String,
+    pub sol_price: Decimal,
+    pub btc_token_id: String,
+    pub btc_price: Decimal,
 }
 
 #[derive(Debug, Clone)]
 pub struct PendingTrade {
+    pub trade_id: String,
     pub sol_token_id: String,
     pub btc_token_id: String,
     pub sol_condition_id: String,
     pub btc_condition_id: String,
     pub investment_amount: f64,
     pub units: f64,
-    pub timestamp: std::time::Instant,
+    /// Unix epoch seconds the trade was entered, used for settlement-age
+    /// checks. Deliberately wall-clock based rather than `Instant`, so age
+    /// is computed correctly even if the process was suspended (e.g. a
+    /// laptop sleeping through a period boundary) between entry and check.
+    pub timestamp: u64,
+    /// SOL leg ask price at the moment the opportunity was detected/entered.
+    pub entry_sol_price: Decimal,
+    /// BTC leg ask price at the moment the opportunity was detected/entered.
+    pub entry_btc_price: Decimal,
+    /// SOL leg's realized average fill price, when known. `None` in
+    /// simulation (nothing was actually filled) or if the CLOB's order
+    /// response didn't report one. Compared against `entry_sol_price` for
+    /// slippage enforcement and carried into `TradeLogEntry` at settlement
+    /// for later analysis.
+    pub sol_fill_price: Option<Decimal>,
+    /// Same as `sol_fill_price`, for the BTC leg.
+    pub btc_fill_price: Option<Decimal>,
+    /// Strategy that produced this trade, carried over from the originating
+    /// `ArbitrageOpportunity` for per-strategy P&L attribution at settlement.
+    pub strategy: String,
+    /// `ArbitrageOpportunity::expected_profit` at entry, scaled by `units`
+    /// (and accumulated the same way `units`/`investment_amount` are, if
+    /// more units are folded into the same trade before settlement). Diffed
+    /// against the actual settled profit to track how optimistic the
+    /// detector's model is - see `StrategyStats::avg_profit_divergence`.
+    pub expected_profit: f64,
+    /// Set while `check_pending_trades` is settling this trade (both legs'
+    /// markets closed, sell/redeem in flight) but before it has been removed
+    /// from `pending_trades`. Lets settlement release the pending-trades lock
+    /// during the network round-trips it needs without a second settlement
+    /// attempt (a concurrent poller run, or a future websocket-driven
+    /// resolution event) double-booking profit for the same trade.
+    pub settling: bool,
+    /// Tail hedge bought alongside this trade in the opposing outcome
+    /// combination (see `TradingConfig::tail_hedge_fraction`), `None` if
+    /// hedging is disabled or no viable opposing price was available.
+    pub hedge: Option<HedgeLeg>,
+}
+
+/// A tail hedge sized against a `PendingTrade`'s catastrophic both-legs-lose
+/// case: a small position in the opposing outcome combination, funded from
+/// the trade's expected profit rather than its principal. Settled by
+/// netting its own P&L (via `settlement_profit`, with each leg's result
+/// inverted relative to the main trade's) into the trade's actual profit.
+#[derive(Debug, Clone)]
+pub struct HedgeLeg {
+    pub sol_token_id: String,
+    pub btc_token_id: String,
+    pub sol_price: Decimal,
+    pub btc_price: Decimal,
+    pub units: f64,
+    pub investment_amount: f64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
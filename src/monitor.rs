@@ -1,12 +1,30 @@
-use crate::api::PolymarketApi;
+use crate::api::PriceSource;
 use crate::models::*;
-use anyhow::Result;
+use anyhow::{Context, Result};
 use log::{debug, info, warn};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::path::PathBuf;
 use std::sync::Arc;
 use tokio::time::{sleep, Duration};
 
+/// On-disk representation of the resolved token/condition IDs for the
+/// current period, used to warm-start a restart without paying for two
+/// fresh CLOB `get_market` calls before the first snapshot.
+#[derive(Debug, Serialize, Deserialize)]
+struct PersistedTokenCache {
+    period: u64,
+    sol_condition_id: String,
+    btc_condition_id: String,
+    sol_up_token_id: Option<String>,
+    sol_down_token_id: Option<String>,
+    btc_up_token_id: Option<String>,
+    btc_down_token_id: Option<String>,
+}
+
 pub struct MarketMonitor {
-    api: Arc<PolymarketApi>,
+    api: Arc<dyn PriceSource>,
     sol_market: Arc<tokio::sync::Mutex<crate::models::Market>>,
     btc_market: Arc<tokio::sync::Mutex<crate::models::Market>>,
     check_interval: Duration,
@@ -15,31 +33,438 @@ pub struct MarketMonitor {
     sol_down_token_id: Arc<tokio::sync::Mutex<Option<String>>>,
     btc_up_token_id: Arc<tokio::sync::Mutex<Option<String>>>,
     btc_down_token_id: Arc<tokio::sync::Mutex<Option<String>>>,
-    last_market_refresh: Arc<tokio::sync::Mutex<Option<std::time::Instant>>>,
-    current_period_timestamp: Arc<tokio::sync::Mutex<u64>>, // Track current 15-minute period
+    // Unix epoch seconds parsed from each market's `accepting_order_timestamp`
+    // (via `parse_iso8601_to_unix_secs`), refreshed alongside the token IDs.
+    // `None` when the field is absent or unparseable; a timestamp already in
+    // the past is kept as-is and simply ignored. Exposed via
+    // `seconds_until_accepting_orders` so the trade gate can hold off on a
+    // freshly-opened market that isn't accepting orders yet.
+    sol_accepting_order_timestamp: Arc<tokio::sync::Mutex<Option<u64>>>,
+    btc_accepting_order_timestamp: Arc<tokio::sync::Mutex<Option<u64>>>,
+    // Unix epoch seconds, not `Instant` - see `unix_now_secs`.
+    last_market_refresh: Arc<tokio::sync::Mutex<Option<u64>>>,
+    current_period_timestamp: Arc<tokio::sync::Mutex<u64>>, // Track current period start
+    // Whether the SOL/BTC market has been (re-)discovered for
+    // `current_period_timestamp` yet. Reset to false for both markets when a
+    // new period is first observed, and flipped to true independently as
+    // each side's discovery succeeds, so a flaky discovery call on one side
+    // doesn't strand the other on stale data for the whole period.
+    sol_updated_for_period: Arc<tokio::sync::Mutex<bool>>,
+    btc_updated_for_period: Arc<tokio::sync::Mutex<bool>>,
+    // Unix epoch seconds of the most recent period rollover, or `None` if
+    // one hasn't happened yet this run. Exposed via `ms_since_last_rollover`
+    // so the trade gate can hold off on freshly-opened, still-glitchy books.
+    last_rollover_unix: Arc<tokio::sync::Mutex<Option<u64>>>,
+    // Length of a market period in seconds (e.g. 900 for 15-minute markets).
+    period_secs: u64,
+    // How far apart (in seconds) the SOL and BTC markets' end times may be
+    // before a period-boundary drift warning is logged.
+    period_boundary_tolerance_secs: u64,
+    // When set, resolved token IDs are persisted here so a restart within
+    // the same period can warm-start instead of re-discovering.
+    token_cache_path: Option<PathBuf>,
+    // Keyword lists used to classify a token's outcome label as up or down.
+    up_outcome_keywords: Vec<String>,
+    down_outcome_keywords: Vec<String>,
+    // Exponential moving average of (bid, ask) per token_id, used to filter
+    // single-tick spikes out of the smoothed prices exposed in snapshots.
+    price_ema: Arc<tokio::sync::Mutex<HashMap<String, EmaState>>>,
+    price_ema_alpha: Decimal,
+    // Most recent successfully-fetched snapshot, exposed for status/heartbeat
+    // reporting without requiring callers to wait for the next fetch cycle.
+    latest_snapshot: Arc<tokio::sync::Mutex<Option<MarketSnapshot>>>,
+    // Bounded ring buffer of recent (timestamp, bid, ask) samples per
+    // token_id, for debugging price behavior and simple local charting. Not
+    // consulted by any trading logic.
+    price_history: Arc<tokio::sync::Mutex<HashMap<String, VecDeque<PriceSample>>>>,
+    price_history_len: usize,
+    // Sane band a fetched bid/ask must fall within; anything outside is
+    // rejected and logged rather than fed into arbitrage detection.
+    min_sane_price: Decimal,
+    max_sane_price: Decimal,
+    // When false, `refresh_market_tokens` skips mapping a market's tokens to
+    // up/down if its `is_50_50_outcome` flag is false, since our settlement
+    // logic assumes a simple binary outcome.
+    allow_non_50_50_markets: bool,
+    // How `fetch_token_price` handles a BUY/SELL price inversion (bid > ask).
+    price_inversion_policy: PriceInversionPolicy,
+    // When true, `fetch_token_price` also fetches each token's last traded
+    // price so `TradingConfig::last_trade_price_band_pct` can be enforced.
+    // Kept as an explicit flag (rather than always fetching) so sites that
+    // don't use the band check don't pay for an extra CLOB request per
+    // token per tick.
+    last_trade_price_band_check: bool,
+    // Which endpoint `fetch_token_price` populates `TokenPrice` from - see
+    // `PriceSourcePreference`.
+    price_source_preference: PriceSourcePreference,
+    // Number of consecutive price-fetch failures per leg since its last
+    // successful fetch. Reset to zero on any success; compared against
+    // `max_consecutive_price_failures` to force an early token re-refresh
+    // once a leg looks persistently broken.
+    sol_up_consecutive_failures: Arc<tokio::sync::Mutex<u32>>,
+    sol_down_consecutive_failures: Arc<tokio::sync::Mutex<u32>>,
+    btc_up_consecutive_failures: Arc<tokio::sync::Mutex<u32>>,
+    btc_down_consecutive_failures: Arc<tokio::sync::Mutex<u32>>,
+    // After this many consecutive price-fetch failures on a single leg,
+    // `fetch_market_data` forces `refresh_market_tokens` to run immediately
+    // (ignoring the normal `period_secs` timer), on the theory that a
+    // persistently-failing leg has a stale/wrong cached token ID rather than
+    // just a flaky endpoint. `None` disables this and preserves the old
+    // behavior of only refreshing once per period.
+    max_consecutive_price_failures: Option<u32>,
+    // Secondary price source `fetch_token_price` cross-checks the primary
+    // (`price_source_preference`) source's price against, per tick. `None`
+    // (the default) disables the check and its extra fetch entirely.
+    cross_check_source: Option<PriceSourcePreference>,
+    // Maximum fractional disagreement between the primary and
+    // `cross_check_source` prices before a leg is flagged unreliable and
+    // logged. Loose enough by default not to fire on ordinary
+    // spread-driven differences between sources.
+    cross_check_tolerance_pct: f64,
+    // When true, a leg that fails the cross-source check is dropped for
+    // this tick (as if its fetch had failed) instead of merely being logged.
+    skip_trading_on_cross_check_mismatch: bool,
+    // Unix epoch seconds at the start of the most recent `start_monitoring`
+    // loop iteration, updated every tick regardless of whether that tick's
+    // fetch succeeded. `None` before the loop has run at all. Exposed via
+    // `seconds_since_last_tick` so an external watchdog can tell a hung loop
+    // (this monitor is still constructed but no longer iterating) apart from
+    // one that's simply having every fetch fail.
+    last_loop_tick: Arc<tokio::sync::Mutex<Option<u64>>>,
+}
+
+/// How `fetch_token_price` handles a venue returning a BUY (ask) price below
+/// the SELL (bid) price - a data inconsistency that would otherwise produce
+/// a `TokenPrice` with bid > ask and confuse `mid_price`/spread logic
+/// downstream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PriceInversionPolicy {
+    /// Discard both sides for this tick, same as if BUY and SELL had both
+    /// failed to fetch - falls through to the midpoint fallback.
+    #[default]
+    Drop,
+    /// Swap bid and ask, on the theory the venue simply mislabeled which
+    /// side was which.
+    Swap,
+    /// Keep the ask and clamp the bid down to match it, discarding the
+    /// wider/inconsistent bid rather than the leg entirely.
+    Clamp,
+}
+
+/// Which endpoint `fetch_token_price` populates `TokenPrice` from. The CLOB
+/// price endpoint, the orderbook top-of-book, and the midpoint all answer
+/// "what's this token worth" slightly differently, and which one is the
+/// better fit depends on what the price is used for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PriceSourcePreference {
+    /// Two calls to the `/price` endpoint (one per side), falling back to
+    /// the midpoint if both fail. The original behavior - a good fit for
+    /// detection, where the two extra round-trips per token per tick are
+    /// affordable and the price endpoint tends to be the venue's most
+    /// current view.
+    #[default]
+    PriceEndpoint,
+    /// A single call to the orderbook and its top bid/ask. Reflects what's
+    /// actually resting on the book right now, which is what execution
+    /// cares about - the price endpoint can lag a fast-moving book by a
+    /// tick or two.
+    OrderbookTop,
+    /// A single call to `/midpoint`. Cheapest of the three (one request,
+    /// always populated), at the cost of not distinguishing bid from ask -
+    /// only `ask` is populated (with the midpoint) and `bid` is left unset,
+    /// so spread-based logic has no bid to work with.
+    Midpoint,
+}
+
+/// Resolves a BUY/SELL inversion (`bid > ask`) per `policy`, returning the
+/// corrected `(bid, ask)` or `None` if the leg should be dropped. Returns
+/// the inputs unchanged when there's no inversion to resolve.
+fn resolve_price_inversion(
+    bid: Decimal,
+    ask: Decimal,
+    policy: PriceInversionPolicy,
+) -> Option<(Decimal, Decimal)> {
+    if bid <= ask {
+        return Some((bid, ask));
+    }
+    match policy {
+        PriceInversionPolicy::Drop => None,
+        PriceInversionPolicy::Swap => Some((ask, bid)),
+        PriceInversionPolicy::Clamp => Some((ask, ask)),
+    }
 }
 
+/// Running EMA of a token's bid and ask, either of which may be absent if
+/// that side of the book hasn't had a sample yet.
+type EmaState = (Option<Decimal>, Option<Decimal>);
+
 #[derive(Debug, Clone)]
 pub struct MarketSnapshot {
     pub sol_market: MarketData,
     pub btc_market: MarketData,
-    pub timestamp: std::time::Instant,
+    /// Unix epoch seconds the snapshot was taken, not `Instant` - see
+    /// `unix_now_secs`.
+    pub timestamp: u64,
+}
+
+/// Current wall-clock time as unix epoch seconds. Used instead of
+/// `Instant::now()` for anything whose age must still be correct after a
+/// system sleep/suspend - `Instant` is monotonic and frozen during
+/// suspend, so an `elapsed()` age computed from it under-counts the true
+/// wall-clock gap after a resume.
+fn unix_now_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+/// Round a unix timestamp down to the start of its period.
+pub fn period_of(unix_secs: u64, period_secs: u64) -> u64 {
+    (unix_secs / period_secs) * period_secs
+}
+
+/// Whether `now` falls in a different period than `stored`.
+pub fn is_new_period(now: u64, stored: u64, period_secs: u64) -> bool {
+    period_of(now, period_secs) != stored
+}
+
+/// Update an exponential moving average with a new raw sample. `None` prior
+/// state (e.g. the token's first tick) just seeds the EMA with the raw
+/// value instead of blending it, so a fresh token isn't smoothed toward
+/// zero before it has any history.
+fn ema_update(prev: Option<Decimal>, raw: Decimal, alpha: Decimal) -> Decimal {
+    match prev {
+        Some(prev) => alpha * raw + (Decimal::ONE - alpha) * prev,
+        None => raw,
+    }
+}
+
+/// Classify a token's outcome label as the "up" or "down" side of the
+/// market, using configurable keyword lists so markets that label their
+/// outcomes differently (e.g. "Yes"/"No") can still be matched. Matching is
+/// case-insensitive and trims whitespace; keywords may be full labels or
+/// substrings of the outcome text. Returns `None` if no keyword matches.
+pub fn classify_outcome(outcome: &str, up_keywords: &[String], down_keywords: &[String]) -> Option<bool> {
+    let outcome_normalized = outcome.trim().to_uppercase();
+
+    if up_keywords
+        .iter()
+        .any(|k| outcome_normalized.contains(&k.trim().to_uppercase()))
+    {
+        return Some(true);
+    }
+    if down_keywords
+        .iter()
+        .any(|k| outcome_normalized.contains(&k.trim().to_uppercase()))
+    {
+        return Some(false);
+    }
+
+    None
+}
+
+/// Check whether the SOL and BTC market data share a token ID. At a
+/// rollover glitch a stale/misresolved token ID can end up cached for both
+/// markets, which would make a "hedge" two copies of the same position
+/// carrying full directional risk instead of an offsetting pair. Returns
+/// the offending token ID if the two markets are not disjoint.
+fn find_duplicate_token_id(sol_market: &MarketData, btc_market: &MarketData) -> Option<String> {
+    let sol_ids = [sol_market.up_token.as_ref(), sol_market.down_token.as_ref()];
+    let btc_ids = [btc_market.up_token.as_ref(), btc_market.down_token.as_ref()];
+
+    for sol_token in sol_ids.into_iter().flatten() {
+        for btc_token in btc_ids.into_iter().flatten() {
+            if sol_token.token_id == btc_token.token_id {
+                return Some(sol_token.token_id.clone());
+            }
+        }
+    }
+
+    None
+}
+
+/// Mid price for a sample, falling back to whichever side is present if
+/// only one is. `None` if neither side was recorded.
+fn sample_mid_price(sample: &PriceSample) -> Option<f64> {
+    use rust_decimal::prelude::ToPrimitive;
+    let mid = match (sample.bid, sample.ask) {
+        (Some(bid), Some(ask)) => (bid + ask) / rust_decimal::Decimal::TWO,
+        (Some(bid), None) => bid,
+        (None, Some(ask)) => ask,
+        (None, None) => return None,
+    };
+    mid.to_f64()
+}
+
+/// Pearson correlation coefficient between two tokens' tick-to-tick price
+/// changes. Samples are paired by position - both tokens are fetched in the
+/// same `fetch_market_data` tick via `tokio::join!`, so index `i` in each
+/// history corresponds to the same tick - and pairs where either side is
+/// missing a usable price are dropped before differencing. Returns `None`
+/// if fewer than 3 paired samples remain or either series is constant
+/// (zero variance makes correlation undefined).
+fn price_correlation(a: &[PriceSample], b: &[PriceSample]) -> Option<f64> {
+    let len = a.len().min(b.len());
+    let mut prices_a = Vec::with_capacity(len);
+    let mut prices_b = Vec::with_capacity(len);
+    for i in 0..len {
+        if let (Some(pa), Some(pb)) = (sample_mid_price(&a[i]), sample_mid_price(&b[i])) {
+            prices_a.push(pa);
+            prices_b.push(pb);
+        }
+    }
+    if prices_a.len() < 3 {
+        return None;
+    }
+
+    let deltas_a: Vec<f64> = prices_a.windows(2).map(|w| w[1] - w[0]).collect();
+    let deltas_b: Vec<f64> = prices_b.windows(2).map(|w| w[1] - w[0]).collect();
+
+    let n = deltas_a.len() as f64;
+    let mean_a = deltas_a.iter().sum::<f64>() / n;
+    let mean_b = deltas_b.iter().sum::<f64>() / n;
+
+    let mut covariance = 0.0;
+    let mut variance_a = 0.0;
+    let mut variance_b = 0.0;
+    for i in 0..deltas_a.len() {
+        let da = deltas_a[i] - mean_a;
+        let db = deltas_b[i] - mean_b;
+        covariance += da * db;
+        variance_a += da * da;
+        variance_b += db * db;
+    }
+
+    if variance_a <= 0.0 || variance_b <= 0.0 {
+        return None;
+    }
+    Some(covariance / (variance_a.sqrt() * variance_b.sqrt()))
+}
+
+/// Days since the Unix epoch (1970-01-01) for a proleptic Gregorian
+/// calendar date, via Howard Hinnant's `days_from_civil` algorithm. Used by
+/// `parse_iso8601_to_unix_secs` instead of pulling in a date/time crate for
+/// what's otherwise a single field we only ever compare, never display.
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = if y >= 0 { y } else { y - 399 } / 400;
+    let yoe = y - era * 400; // [0, 399]
+    let mp = (month as i64 + 9) % 12; // [0, 11], Mar=0 .. Feb=11
+    let doy = (153 * mp + 2) / 5 + day as i64 - 1; // [0, 365]
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy; // [0, 146096]
+    era * 146097 + doe - 719468
+}
+
+/// Parse a Polymarket `endDateISO`-style timestamp (`YYYY-MM-DDTHH:MM:SSZ`,
+/// optionally with fractional seconds) into Unix seconds. Returns `None` for
+/// anything that doesn't match, rather than failing loudly - this feeds the
+/// period-boundary drift check and the accepting-orders trade gate, not
+/// settlement logic.
+fn parse_iso8601_to_unix_secs(s: &str) -> Option<u64> {
+    let s = s.trim().strip_suffix('Z').unwrap_or(s.trim());
+    let (date_part, time_part) = s.split_once('T')?;
+
+    let mut date_fields = date_part.split('-');
+    let year: i64 = date_fields.next()?.parse().ok()?;
+    let month: u32 = date_fields.next()?.parse().ok()?;
+    let day: u32 = date_fields.next()?.parse().ok()?;
+    if date_fields.next().is_some() || !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+
+    let time_part = time_part.split('.').next()?; // drop fractional seconds
+    let mut time_fields = time_part.split(':');
+    let hour: u32 = time_fields.next()?.parse().ok()?;
+    let minute: u32 = time_fields.next()?.parse().ok()?;
+    let second: u32 = time_fields.next()?.parse().ok()?;
+    if time_fields.next().is_some() || hour > 23 || minute > 59 || second > 60 {
+        return None;
+    }
+
+    let days = days_from_civil(year, month, day);
+    let secs = days * 86_400 + hour as i64 * 3600 + minute as i64 * 60 + second as i64;
+    u64::try_from(secs).ok()
+}
+
+/// A market's resolution time as Unix seconds, preferring `end_date_iso`
+/// and falling back to `end_date_iso_alt`. `None` if neither is set or
+/// parseable.
+fn market_end_time(market: &Market) -> Option<u64> {
+    market
+        .end_date_iso
+        .as_deref()
+        .or(market.end_date_iso_alt.as_deref())
+        .and_then(parse_iso8601_to_unix_secs)
+}
+
+/// Difference in seconds between the SOL and BTC markets' end times
+/// (positive if SOL ends later), or `None` if either market's end time is
+/// missing/unparseable and the drift can't be checked.
+fn period_boundary_drift_secs(sol_market: &Market, btc_market: &Market) -> Option<i64> {
+    let sol_end = market_end_time(sol_market)?;
+    let btc_end = market_end_time(btc_market)?;
+    Some(sol_end as i64 - btc_end as i64)
+}
+
+/// Warn if the SOL and BTC markets' end times differ by more than
+/// `tolerance_secs`. The bot assumes both markets share a single period and
+/// settles them together once both report closed; if their real boundaries
+/// are offset, one could close while the other is still open, which this
+/// surfaces instead of leaving as confusing settlement behavior.
+fn warn_on_period_boundary_drift(sol_market: &Market, btc_market: &Market, tolerance_secs: u64) {
+    match period_boundary_drift_secs(sol_market, btc_market) {
+        Some(drift) if drift.unsigned_abs() > tolerance_secs => {
+            warn!(
+                "⚠️  SOL and BTC market period boundaries differ by {}s (tolerance {}s): SOL ends {:?}, BTC ends {:?}. \
+                 One market may close while the other is still open.",
+                drift,
+                tolerance_secs,
+                sol_market.end_date_iso.as_deref().or(sol_market.end_date_iso_alt.as_deref()),
+                btc_market.end_date_iso.as_deref().or(btc_market.end_date_iso_alt.as_deref()),
+            );
+        }
+        Some(_) => {}
+        None => debug!("Could not compare SOL/BTC period boundaries: missing or unparseable end_date_iso"),
+    }
 }
 
 impl MarketMonitor {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
-        api: Arc<PolymarketApi>,
+        api: Arc<dyn PriceSource>,
         sol_market: crate::models::Market,
         btc_market: crate::models::Market,
         check_interval_ms: u64,
+        period_secs: u64,
+        token_cache_path: Option<PathBuf>,
+        up_outcome_keywords: Vec<String>,
+        down_outcome_keywords: Vec<String>,
+        price_ema_alpha: f64,
+        price_history_len: usize,
+        period_boundary_tolerance_secs: u64,
+        min_sane_price: f64,
+        max_sane_price: f64,
+        allow_non_50_50_markets: bool,
+        price_inversion_policy: PriceInversionPolicy,
+        last_trade_price_band_check: bool,
+        price_source_preference: PriceSourcePreference,
+        max_consecutive_price_failures: Option<u32>,
+        cross_check_source: Option<PriceSourcePreference>,
+        cross_check_tolerance_pct: f64,
+        skip_trading_on_cross_check_mismatch: bool,
     ) -> Self {
-        // Calculate current 15-minute period timestamp
+        // Calculate current period start timestamp
         let current_time = std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
             .unwrap()
             .as_secs();
-        let current_period = (current_time / 900) * 900; // Round to nearest 15 minutes
-        
+        let current_period = period_of(current_time, period_secs);
+
+        warn_on_period_boundary_drift(&sol_market, &btc_market, period_boundary_tolerance_secs);
+
         Self {
             api,
             sol_market: Arc::new(tokio::sync::Mutex::new(sol_market)),
@@ -49,50 +474,357 @@ impl MarketMonitor {
             sol_down_token_id: Arc::new(tokio::sync::Mutex::new(None)),
             btc_up_token_id: Arc::new(tokio::sync::Mutex::new(None)),
             btc_down_token_id: Arc::new(tokio::sync::Mutex::new(None)),
+            sol_accepting_order_timestamp: Arc::new(tokio::sync::Mutex::new(None)),
+            btc_accepting_order_timestamp: Arc::new(tokio::sync::Mutex::new(None)),
             last_market_refresh: Arc::new(tokio::sync::Mutex::new(None)),
             current_period_timestamp: Arc::new(tokio::sync::Mutex::new(current_period)),
+            sol_updated_for_period: Arc::new(tokio::sync::Mutex::new(true)),
+            btc_updated_for_period: Arc::new(tokio::sync::Mutex::new(true)),
+            last_rollover_unix: Arc::new(tokio::sync::Mutex::new(None)),
+            period_secs,
+            period_boundary_tolerance_secs,
+            token_cache_path,
+            up_outcome_keywords,
+            down_outcome_keywords,
+            price_ema: Arc::new(tokio::sync::Mutex::new(HashMap::new())),
+            price_ema_alpha: Decimal::from_f64_retain(price_ema_alpha).unwrap_or(rust_decimal_macros::dec!(0.3)),
+            latest_snapshot: Arc::new(tokio::sync::Mutex::new(None)),
+            price_history: Arc::new(tokio::sync::Mutex::new(HashMap::new())),
+            price_history_len,
+            min_sane_price: Decimal::from_f64_retain(min_sane_price).unwrap_or(rust_decimal_macros::dec!(0.001)),
+            max_sane_price: Decimal::from_f64_retain(max_sane_price).unwrap_or(rust_decimal_macros::dec!(0.999)),
+            allow_non_50_50_markets,
+            price_inversion_policy,
+            last_trade_price_band_check,
+            price_source_preference,
+            sol_up_consecutive_failures: Arc::new(tokio::sync::Mutex::new(0)),
+            sol_down_consecutive_failures: Arc::new(tokio::sync::Mutex::new(0)),
+            btc_up_consecutive_failures: Arc::new(tokio::sync::Mutex::new(0)),
+            btc_down_consecutive_failures: Arc::new(tokio::sync::Mutex::new(0)),
+            max_consecutive_price_failures,
+            cross_check_source,
+            cross_check_tolerance_pct,
+            skip_trading_on_cross_check_mismatch,
+            last_loop_tick: Arc::new(tokio::sync::Mutex::new(None)),
+        }
+    }
+
+    /// Most recently fetched market snapshot, if any, for status/heartbeat
+    /// reporting.
+    pub async fn latest_snapshot(&self) -> Option<MarketSnapshot> {
+        self.latest_snapshot.lock().await.clone()
+    }
+
+    /// Seconds since `start_monitoring`'s loop last started an iteration, or
+    /// `None` if it hasn't run yet. Ticks regardless of whether that
+    /// iteration's fetch succeeded, so this reflects the loop actually still
+    /// being scheduled - not whether market data is fresh - which is what a
+    /// stall watchdog needs to distinguish a hung process from one merely
+    /// hitting a run of fetch errors.
+    pub async fn seconds_since_last_tick(&self) -> Option<u64> {
+        self.last_loop_tick.lock().await.map(|tick| unix_now_secs().saturating_sub(tick))
+    }
+
+    /// Append a price sample to `token_id`'s ring buffer, evicting the
+    /// oldest sample once it's at capacity. Deliberately cheap (a single
+    /// push/pop pair, no I/O) so it doesn't slow down `fetch_market_data`.
+    async fn record_price_sample(&self, token_id: &str, bid: Option<Decimal>, ask: Option<Decimal>) {
+        if self.price_history_len == 0 {
+            return;
+        }
+
+        let timestamp_unix = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+
+        let mut history = self.price_history.lock().await;
+        let buffer = history.entry(token_id.to_string()).or_default();
+        buffer.push_back(PriceSample { timestamp_unix, bid, ask });
+        while buffer.len() > self.price_history_len {
+            buffer.pop_front();
+        }
+    }
+
+    /// Recent (timestamp, bid, ask) samples recorded for a token, oldest
+    /// first and bounded by `price_history_len`. Empty if the token hasn't
+    /// been sampled yet. For debugging price behavior and local charting,
+    /// not consulted by trading logic.
+    pub async fn price_history(&self, token_id: &str) -> Vec<PriceSample> {
+        self.price_history
+            .lock()
+            .await
+            .get(token_id)
+            .map(|buffer| buffer.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Realized correlation between SOL's and BTC's "Up" token prices over
+    /// the current `price_history` window, as a Pearson correlation
+    /// coefficient of tick-to-tick price changes in `[-1.0, 1.0]`. Both
+    /// hedge strategies (`STRATEGY_SOL_UP_BTC_DOWN`, `STRATEGY_SOL_DOWN_BTC_UP`)
+    /// implicitly bet that SOL and BTC move in *opposite* directions often
+    /// enough to be safe; a strongly positive correlation here means they've
+    /// actually been moving together, which is exactly the both-lose
+    /// scenario for either strategy. Returns `None` if either token hasn't
+    /// been sampled yet or there isn't enough overlapping history to say
+    /// anything meaningful.
+    pub async fn sol_btc_price_correlation(&self) -> Option<f64> {
+        let sol_up_token_id = self.sol_up_token_id.lock().await.clone()?;
+        let btc_up_token_id = self.btc_up_token_id.lock().await.clone()?;
+        let sol_history = self.price_history(&sol_up_token_id).await;
+        let btc_history = self.price_history(&btc_up_token_id).await;
+        price_correlation(&sol_history, &btc_history)
+    }
+
+    /// Attempt to warm-start token IDs from the on-disk cache instead of
+    /// paying for two fresh CLOB `get_market` calls. Returns `true` if the
+    /// cache was present, matched the current period and current condition
+    /// IDs, and was applied; `false` otherwise (caller should fall back to
+    /// normal discovery via `fetch_market_data`).
+    pub async fn try_warm_start(&self) -> Result<bool> {
+        let Some(cache_path) = &self.token_cache_path else {
+            return Ok(false);
+        };
+        if !cache_path.exists() {
+            return Ok(false);
+        }
+
+        let content = std::fs::read_to_string(cache_path)
+            .with_context(|| format!("Failed to read token cache {}", cache_path.display()))?;
+        let cached: PersistedTokenCache = serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse token cache {}", cache_path.display()))?;
+
+        let current_time = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        let current_period = period_of(current_time, self.period_secs);
+        let (sol_condition_id, btc_condition_id) = self.get_current_condition_ids().await;
+
+        if cached.period != current_period
+            || cached.sol_condition_id != sol_condition_id
+            || cached.btc_condition_id != btc_condition_id
+        {
+            debug!("Token cache is stale (different period or market), falling back to discovery");
+            return Ok(false);
         }
+
+        *self.sol_up_token_id.lock().await = cached.sol_up_token_id;
+        *self.sol_down_token_id.lock().await = cached.sol_down_token_id;
+        *self.btc_up_token_id.lock().await = cached.btc_up_token_id;
+        *self.btc_down_token_id.lock().await = cached.btc_down_token_id;
+        *self.last_market_refresh.lock().await = Some(unix_now_secs());
+
+        info!("Warm-started token IDs from cache at {}", cache_path.display());
+        Ok(true)
+    }
+
+    /// Persist the currently resolved token/condition IDs so a restart
+    /// within the same period can warm-start via `try_warm_start`.
+    async fn save_token_cache(&self) {
+        let Some(cache_path) = &self.token_cache_path else {
+            return;
+        };
+
+        let (sol_condition_id, btc_condition_id) = self.get_current_condition_ids().await;
+        let cache = PersistedTokenCache {
+            period: *self.current_period_timestamp.lock().await,
+            sol_condition_id,
+            btc_condition_id,
+            sol_up_token_id: self.sol_up_token_id.lock().await.clone(),
+            sol_down_token_id: self.sol_down_token_id.lock().await.clone(),
+            btc_up_token_id: self.btc_up_token_id.lock().await.clone(),
+            btc_down_token_id: self.btc_down_token_id.lock().await.clone(),
+        };
+
+        let result = serde_json::to_string_pretty(&cache)
+            .map_err(anyhow::Error::from)
+            .and_then(|content| std::fs::write(cache_path, content).map_err(anyhow::Error::from));
+        if let Err(e) = result {
+            warn!("Failed to write token cache {}: {}", cache_path.display(), e);
+        }
+    }
+
+    /// Roll `current_period_timestamp` forward to `current_time`'s period if
+    /// it hasn't been already, clearing both markets' discovery flags so a
+    /// fresh period starts out needing both sides re-discovered.
+    async fn begin_new_period_if_needed(&self, current_time: u64) {
+        let new_period = period_of(current_time, self.period_secs);
+        let mut stored_period = self.current_period_timestamp.lock().await;
+        if *stored_period != new_period {
+            *stored_period = new_period;
+            *self.sol_updated_for_period.lock().await = false;
+            *self.btc_updated_for_period.lock().await = false;
+            *self.last_rollover_unix.lock().await = Some(current_time);
+        }
+    }
+
+    /// Milliseconds since the current period's rollover was observed, or
+    /// `None` if no rollover has happened yet this run (e.g. still on the
+    /// period the process started in). Feeds `post_rollover_grace_ms` so the
+    /// trade gate can hold off trading a market whose book just opened.
+    pub async fn ms_since_last_rollover(&self) -> Option<u64> {
+        let rollover_at = (*self.last_rollover_unix.lock().await)?;
+        Some(unix_now_secs().saturating_sub(rollover_at).saturating_mul(1000))
+    }
+
+    /// Seconds until both markets' `accepting_order_timestamp` have passed,
+    /// or `None` once neither side is still waiting (including markets that
+    /// don't report the field at all - it's optional and most established
+    /// markets have already opened by the time we discover them). A
+    /// freshly-opened 15m market can report a timestamp slightly in the
+    /// future; placing orders before then is a guaranteed rejection, so the
+    /// trade gate uses this to hold off until the later of the two sides
+    /// opens.
+    pub async fn seconds_until_accepting_orders(&self) -> Option<u64> {
+        let now = unix_now_secs();
+        let sol_at = *self.sol_accepting_order_timestamp.lock().await;
+        let btc_at = *self.btc_accepting_order_timestamp.lock().await;
+        [sol_at, btc_at].into_iter().flatten().filter(|&at| at > now).map(|at| at - now).max()
     }
 
-    /// Update markets when a new 15-minute period starts
-    pub async fn update_markets(&self, sol_market: crate::models::Market, btc_market: crate::models::Market) -> Result<()> {
-        info!("🔄 Updating to new 15-minute period markets...");
+    /// Update the SOL market when a new period starts, or to retry a SOL
+    /// discovery that failed earlier in the current period.
+    pub async fn update_sol_market(&self, sol_market: crate::models::Market) -> Result<()> {
         info!("New SOL Market: {} ({})", sol_market.slug, sol_market.condition_id);
-        info!("New BTC Market: {} ({})", btc_market.slug, btc_market.condition_id);
-        
+
+        let current_time = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        self.begin_new_period_if_needed(current_time).await;
+
         *self.sol_market.lock().await = sol_market;
-        *self.btc_market.lock().await = btc_market;
-        
+
         // Reset token IDs - will be refreshed on next fetch
         *self.sol_up_token_id.lock().await = None;
         *self.sol_down_token_id.lock().await = None;
-        *self.btc_up_token_id.lock().await = None;
-        *self.btc_down_token_id.lock().await = None;
+        *self.sol_accepting_order_timestamp.lock().await = None;
         *self.last_market_refresh.lock().await = None;
-        
-        // Update current period timestamp
+        *self.sol_updated_for_period.lock().await = true;
+
+        let sol_market = self.sol_market.lock().await.clone();
+        let btc_market = self.btc_market.lock().await.clone();
+        warn_on_period_boundary_drift(&sol_market, &btc_market, self.period_boundary_tolerance_secs);
+
+        Ok(())
+    }
+
+    /// Update the BTC market when a new period starts, or to retry a BTC
+    /// discovery that failed earlier in the current period.
+    pub async fn update_btc_market(&self, btc_market: crate::models::Market) -> Result<()> {
+        info!("New BTC Market: {} ({})", btc_market.slug, btc_market.condition_id);
+
         let current_time = std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
             .unwrap()
             .as_secs();
-        let new_period = (current_time / 900) * 900;
-        *self.current_period_timestamp.lock().await = new_period;
-        
+        self.begin_new_period_if_needed(current_time).await;
+
+        *self.btc_market.lock().await = btc_market;
+
+        // Reset token IDs - will be refreshed on next fetch
+        *self.btc_up_token_id.lock().await = None;
+        *self.btc_down_token_id.lock().await = None;
+        *self.btc_accepting_order_timestamp.lock().await = None;
+        *self.last_market_refresh.lock().await = None;
+        *self.btc_updated_for_period.lock().await = true;
+
+        let sol_market = self.sol_market.lock().await.clone();
+        let btc_market = self.btc_market.lock().await.clone();
+        warn_on_period_boundary_drift(&sol_market, &btc_market, self.period_boundary_tolerance_secs);
+
         Ok(())
     }
 
-    /// Check if we need to discover new markets (new 15-minute period started)
-    pub async fn should_discover_new_markets(&self) -> bool {
+    /// Force both markets onto operator-supplied condition IDs, bypassing
+    /// slug discovery for the current period. A manual escape hatch for when
+    /// discovery's slug heuristics pick the wrong market; there's no HTTP
+    /// control surface wired up to call this yet, so it's driven directly
+    /// for now. Fetches and validates both markets before applying either
+    /// side, so a bad ID leaves the current markets untouched rather than
+    /// overriding one side and not the other.
+    pub async fn override_markets(&self, sol_condition_id: &str, btc_condition_id: &str) -> Result<()> {
+        let sol_market = self.market_from_condition_id("SOL", sol_condition_id).await?;
+        let btc_market = self.market_from_condition_id("BTC", btc_condition_id).await?;
+
+        warn!(
+            "Manual override: forcing SOL market to condition_id {} and BTC market to condition_id {}",
+            sol_condition_id, btc_condition_id
+        );
+
+        self.update_sol_market(sol_market).await?;
+        self.update_btc_market(btc_market).await?;
+        Ok(())
+    }
+
+    /// Fetch and validate `condition_id` for `override_markets`, building the
+    /// `Market` `update_sol_market`/`update_btc_market` expect. Requires the
+    /// market to be active, not closed, and accepting orders - the same bar
+    /// automatic discovery holds candidates to - so an operator can't
+    /// override onto a dead or not-yet-open market by mistake.
+    async fn market_from_condition_id(&self, market_name: &str, condition_id: &str) -> Result<crate::models::Market> {
+        let details = self
+            .api
+            .get_market(condition_id)
+            .await
+            .with_context(|| format!("failed to fetch {} market for condition_id {}", market_name, condition_id))?;
+
+        if !details.active || details.closed || !details.accepting_orders {
+            anyhow::bail!(
+                "condition_id {} for {} does not resolve to an active, accepting-orders market (active={}, closed={}, accepting_orders={})",
+                condition_id,
+                market_name,
+                details.active,
+                details.closed,
+                details.accepting_orders
+            );
+        }
+
+        Ok(crate::models::Market {
+            condition_id: details.condition_id,
+            market_id: None,
+            question: details.question,
+            slug: details.market_slug,
+            resolution_source: None,
+            end_date_iso: Some(details.end_date_iso),
+            end_date_iso_alt: None,
+            active: details.active,
+            closed: details.closed,
+            tokens: None,
+            clob_token_ids: None,
+            outcomes: None,
+        })
+    }
+
+    /// Whether the SOL and BTC markets still need (re-)discovery for the
+    /// current period, e.g. `(true, false)` if only the BTC side is
+    /// outstanding. Both are true as soon as a new period is observed, and
+    /// flip to false independently as `update_sol_market`/`update_btc_market`
+    /// succeed.
+    pub async fn markets_pending_discovery(&self) -> (bool, bool) {
         let current_time = std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
             .unwrap()
             .as_secs();
-        let current_period = (current_time / 900) * 900;
-        
+
         let stored_period = *self.current_period_timestamp.lock().await;
-        
-        // If current period is different from stored period, we need new markets
-        current_period != stored_period
+        if is_new_period(current_time, stored_period, self.period_secs) {
+            return (true, true);
+        }
+
+        (
+            !*self.sol_updated_for_period.lock().await,
+            !*self.btc_updated_for_period.lock().await,
+        )
+    }
+
+    /// Check if we need to discover new markets (new period started, or a
+    /// prior period's discovery is still incomplete for one side).
+    pub async fn should_discover_new_markets(&self) -> bool {
+        let (need_sol, need_btc) = self.markets_pending_discovery().await;
+        need_sol || need_btc
     }
 
     /// Get current market condition IDs (for checking if markets are closed)
@@ -102,13 +834,18 @@ impl MarketMonitor {
         (sol, btc)
     }
 
-    /// Refresh market data once per period (15 minutes) to get token IDs
+    /// Unix timestamp at which the current period closes, used to scale the
+    /// arbitrage detector's profit threshold by time-to-close.
+    pub async fn current_period_end_unix(&self) -> u64 {
+        *self.current_period_timestamp.lock().await + self.period_secs
+    }
+
+    /// Refresh market data once per period to get token IDs
     async fn refresh_market_tokens(&self) -> Result<()> {
-        // Check if we need to refresh (every 15 minutes = 900 seconds)
         let should_refresh = {
             let last_refresh = self.last_market_refresh.lock().await;
             last_refresh
-                .map(|last| last.elapsed().as_secs() >= 900)
+                .map(|last| unix_now_secs().saturating_sub(last) >= self.period_secs)
                 .unwrap_or(true)
         };
 
@@ -121,40 +858,73 @@ impl MarketMonitor {
 
         // Get SOL market details
         if let Ok(sol_details) = self.api.get_market(&sol_condition_id).await {
-            for token in &sol_details.tokens {
-                let outcome_upper = token.outcome.to_uppercase();
-                if outcome_upper.contains("UP") || outcome_upper == "1" {
-                    *self.sol_up_token_id.lock().await = Some(token.token_id.clone());
-                    info!("SOL Up token_id: {}", token.token_id);
-                } else if outcome_upper.contains("DOWN") || outcome_upper == "0" {
-                    *self.sol_down_token_id.lock().await = Some(token.token_id.clone());
-                    info!("SOL Down token_id: {}", token.token_id);
+            *self.sol_accepting_order_timestamp.lock().await = sol_details
+                .accepting_order_timestamp
+                .as_deref()
+                .and_then(parse_iso8601_to_unix_secs);
+            if sol_details.is_50_50_outcome || self.allow_non_50_50_markets {
+                for token in &sol_details.tokens {
+                    match classify_outcome(&token.outcome, &self.up_outcome_keywords, &self.down_outcome_keywords) {
+                        Some(true) => {
+                            *self.sol_up_token_id.lock().await = Some(token.token_id.clone());
+                            info!("SOL Up token_id: {}", token.token_id);
+                        }
+                        Some(false) => {
+                            *self.sol_down_token_id.lock().await = Some(token.token_id.clone());
+                            info!("SOL Down token_id: {}", token.token_id);
+                        }
+                        None => {
+                            log::error!("Could not classify SOL outcome \"{}\" as up or down - check outcome keyword config", token.outcome);
+                        }
+                    }
                 }
+            } else {
+                warn!(
+                    "SOL market {} has is_50_50_outcome=false; skipping token mapping since it may not be a simple binary outcome (set allow_non_50_50_markets to force)",
+                    sol_condition_id
+                );
             }
         }
 
         // Get BTC market details
         if let Ok(btc_details) = self.api.get_market(&btc_condition_id).await {
-            for token in &btc_details.tokens {
-                let outcome_upper = token.outcome.to_uppercase();
-                if outcome_upper.contains("UP") || outcome_upper == "1" {
-                    *self.btc_up_token_id.lock().await = Some(token.token_id.clone());
-                    info!("BTC Up token_id: {}", token.token_id);
-                } else if outcome_upper.contains("DOWN") || outcome_upper == "0" {
-                    *self.btc_down_token_id.lock().await = Some(token.token_id.clone());
-                    info!("BTC Down token_id: {}", token.token_id);
+            *self.btc_accepting_order_timestamp.lock().await = btc_details
+                .accepting_order_timestamp
+                .as_deref()
+                .and_then(parse_iso8601_to_unix_secs);
+            if btc_details.is_50_50_outcome || self.allow_non_50_50_markets {
+                for token in &btc_details.tokens {
+                    match classify_outcome(&token.outcome, &self.up_outcome_keywords, &self.down_outcome_keywords) {
+                        Some(true) => {
+                            *self.btc_up_token_id.lock().await = Some(token.token_id.clone());
+                            info!("BTC Up token_id: {}", token.token_id);
+                        }
+                        Some(false) => {
+                            *self.btc_down_token_id.lock().await = Some(token.token_id.clone());
+                            info!("BTC Down token_id: {}", token.token_id);
+                        }
+                        None => {
+                            log::error!("Could not classify BTC outcome \"{}\" as up or down - check outcome keyword config", token.outcome);
+                        }
+                    }
                 }
+            } else {
+                warn!(
+                    "BTC market {} has is_50_50_outcome=false; skipping token mapping since it may not be a simple binary outcome (set allow_non_50_50_markets to force)",
+                    btc_condition_id
+                );
             }
         }
 
-        *self.last_market_refresh.lock().await = Some(std::time::Instant::now());
+        *self.last_market_refresh.lock().await = Some(unix_now_secs());
+        self.save_token_cache().await;
         Ok(())
     }
 
     /// Fetch current market data for both SOL and BTC markets
     /// Uses get_price() endpoint continuously for real-time prices
     pub async fn fetch_market_data(&self) -> Result<MarketSnapshot> {
-        // Refresh token IDs if needed (once per 15-minute period)
+        // Refresh token IDs if needed (once per market period)
         self.refresh_market_tokens().await?;
 
         let (sol_condition_id, btc_condition_id) = self.get_current_condition_ids().await;
@@ -172,6 +942,20 @@ impl MarketMonitor {
             self.fetch_token_price(&btc_down_token_id, "BTC", "Down"),
         );
 
+        self.force_refresh_if_leg_unhealthy(&self.sol_up_consecutive_failures, sol_up_price.is_some(), "SOL", "Up")
+            .await;
+        self.force_refresh_if_leg_unhealthy(&self.sol_down_consecutive_failures, sol_down_price.is_some(), "SOL", "Down")
+            .await;
+        self.force_refresh_if_leg_unhealthy(&self.btc_up_consecutive_failures, btc_up_price.is_some(), "BTC", "Up")
+            .await;
+        self.force_refresh_if_leg_unhealthy(&self.btc_down_consecutive_failures, btc_down_price.is_some(), "BTC", "Down")
+            .await;
+
+        let sol_up_price = self.smooth_token_price(sol_up_price).await;
+        let sol_down_price = self.smooth_token_price(sol_down_price).await;
+        let btc_up_price = self.smooth_token_price(btc_up_price).await;
+        let btc_down_price = self.smooth_token_price(btc_down_price).await;
+
         let sol_market_data = MarketData {
             condition_id: sol_condition_id,
             market_name: "SOL".to_string(),
@@ -186,11 +970,56 @@ impl MarketMonitor {
             down_token: btc_down_price,
         };
 
-        Ok(MarketSnapshot {
+        if let Some(duplicate_token_id) = find_duplicate_token_id(&sol_market_data, &btc_market_data) {
+            log::error!(
+                "Aborting snapshot: token_id {} is cached for both SOL and BTC markets - a rollover glitch would otherwise double-buy the same token",
+                duplicate_token_id
+            );
+            anyhow::bail!("duplicate token_id {} shared by SOL and BTC markets", duplicate_token_id);
+        }
+
+        let snapshot = MarketSnapshot {
             sol_market: sol_market_data,
             btc_market: btc_market_data,
-            timestamp: std::time::Instant::now(),
-        })
+            timestamp: unix_now_secs(),
+        };
+        *self.latest_snapshot.lock().await = Some(snapshot.clone());
+        Ok(snapshot)
+    }
+
+    /// Track one leg's fetch outcome and, once `max_consecutive_price_failures`
+    /// consecutive failures have piled up, force `refresh_market_tokens` to
+    /// run on the very next call instead of waiting out the rest of the
+    /// current period's `period_secs` timer. Self-heals from a bad cached
+    /// token ID mid-period rather than staying blind on that leg until the
+    /// next scheduled refresh. A no-op when `max_consecutive_price_failures`
+    /// isn't configured.
+    async fn force_refresh_if_leg_unhealthy(
+        &self,
+        consecutive_failures: &tokio::sync::Mutex<u32>,
+        succeeded: bool,
+        market_name: &str,
+        outcome: &str,
+    ) {
+        let Some(max_failures) = self.max_consecutive_price_failures else {
+            return;
+        };
+
+        let mut failures = consecutive_failures.lock().await;
+        if succeeded {
+            *failures = 0;
+            return;
+        }
+
+        *failures += 1;
+        if *failures >= max_failures {
+            warn!(
+                "{} {} has failed to fetch a price {} times in a row; forcing an early token re-refresh instead of waiting out the rest of the period",
+                market_name, outcome, *failures
+            );
+            *self.last_market_refresh.lock().await = None;
+            *failures = 0;
+        }
     }
 
     async fn fetch_token_price(
@@ -200,7 +1029,118 @@ impl MarketMonitor {
         outcome: &str,
     ) -> Option<TokenPrice> {
         let token_id = token_id.as_ref()?;
+        let price = self.fetch_token_price_from_source(self.price_source_preference, token_id, market_name, outcome).await?;
+        self.cross_check_price(price, token_id, market_name, outcome).await
+    }
+
+    /// Fetch `token_id`'s price from a specific source, regardless of
+    /// `self.price_source_preference`. Used both for the primary fetch and,
+    /// via `cross_check_price`, for the configured `cross_check_source`.
+    async fn fetch_token_price_from_source(
+        &self,
+        source: PriceSourcePreference,
+        token_id: &str,
+        market_name: &str,
+        outcome: &str,
+    ) -> Option<TokenPrice> {
+        match source {
+            PriceSourcePreference::PriceEndpoint => {
+                self.fetch_token_price_from_price_endpoint(token_id, market_name, outcome).await
+            }
+            PriceSourcePreference::OrderbookTop => {
+                self.fetch_token_price_from_orderbook_top(token_id, market_name, outcome).await
+            }
+            PriceSourcePreference::Midpoint => {
+                self.fetch_token_price_from_midpoint(token_id, market_name, outcome).await
+            }
+        }
+    }
+
+    /// Cross-checks `primary` (already fetched from `price_source_preference`)
+    /// against `cross_check_source`, when configured, and returns `primary`
+    /// unless `skip_trading_on_cross_check_mismatch` is also set and the two
+    /// sources disagree beyond `cross_check_tolerance_pct` - in which case
+    /// the leg is dropped for this tick, same as a fetch failure. A no-op
+    /// (returns `primary` unchanged) when `cross_check_source` isn't
+    /// configured, matches the primary source, or itself fails to fetch,
+    /// since there's then nothing to compare against.
+    async fn cross_check_price(
+        &self,
+        primary: TokenPrice,
+        token_id: &str,
+        market_name: &str,
+        outcome: &str,
+    ) -> Option<TokenPrice> {
+        let Some(secondary_source) = self.cross_check_source else {
+            return Some(primary);
+        };
+        if secondary_source == self.price_source_preference {
+            return Some(primary);
+        }
+
+        let Some(secondary) = self.fetch_token_price_from_source(secondary_source, token_id, market_name, outcome).await else {
+            return Some(primary);
+        };
+
+        if self.prices_agree(&primary, &secondary, secondary_source, market_name, outcome) || !self.skip_trading_on_cross_check_mismatch {
+            return Some(primary);
+        }
+
+        warn!(
+            "{} {} leg dropped for this cycle: primary/secondary price sources disagree beyond tolerance",
+            market_name, outcome
+        );
+        None
+    }
 
+    /// True if `primary` and `secondary`'s mid prices agree within
+    /// `cross_check_tolerance_pct`, logging both values when they don't.
+    /// Permissive (returns `true`) when either side has no price to
+    /// compare, since the leg is still valid on its own with nothing to
+    /// cross-check it against.
+    fn prices_agree(
+        &self,
+        primary: &TokenPrice,
+        secondary: &TokenPrice,
+        secondary_source: PriceSourcePreference,
+        market_name: &str,
+        outcome: &str,
+    ) -> bool {
+        let (Some(primary_mid), Some(secondary_mid)) = (primary.mid_price(), secondary.mid_price()) else {
+            return true;
+        };
+        if primary_mid.is_zero() {
+            return true;
+        }
+
+        let discrepancy_pct = (primary_mid - secondary_mid).abs() / primary_mid;
+        let tolerance = Decimal::from_f64_retain(self.cross_check_tolerance_pct).unwrap_or(Decimal::MAX);
+        if discrepancy_pct <= tolerance {
+            return true;
+        }
+
+        warn!(
+            "{} {} price sources disagree beyond tolerance: {:?}={} vs {:?}={} ({:.2}% > {:.2}% tolerance)",
+            market_name,
+            outcome,
+            self.price_source_preference,
+            primary_mid,
+            secondary_source,
+            secondary_mid,
+            discrepancy_pct * Decimal::from(100),
+            Decimal::from_f64_retain(self.cross_check_tolerance_pct * 100.0).unwrap_or_default()
+        );
+        false
+    }
+
+    /// Two calls to the `/price` endpoint (BUY then SELL), falling back to
+    /// the midpoint if both fail. See `PriceSourcePreference::PriceEndpoint`.
+    async fn fetch_token_price_from_price_endpoint(
+        &self,
+        token_id: &str,
+        market_name: &str,
+        outcome: &str,
+    ) -> Option<TokenPrice> {
         // Get BUY price (ask price - what we pay to buy)
         let buy_price = match self.api.get_price(token_id, "BUY").await {
             Ok(price) => Some(price),
@@ -219,40 +1159,1547 @@ impl MarketMonitor {
             }
         };
 
+        let (buy_price, sell_price) = match (buy_price, sell_price) {
+            (Some(ask), Some(bid)) if bid > ask => {
+                warn!(
+                    "{} {} BUY/SELL price inversion detected (ask={} < bid={}); applying {:?} policy",
+                    market_name, outcome, ask, bid, self.price_inversion_policy
+                );
+                match resolve_price_inversion(bid, ask, self.price_inversion_policy) {
+                    Some((resolved_bid, resolved_ask)) => (Some(resolved_ask), Some(resolved_bid)),
+                    None => (None, None),
+                }
+            }
+            other => other,
+        };
+
         if buy_price.is_some() || sell_price.is_some() {
-            Some(TokenPrice {
-                token_id: token_id.clone(),
+            let last = self.fetch_last_trade_price(token_id, market_name, outcome).await;
+            let price = TokenPrice {
+                token_id: token_id.to_string(),
                 bid: sell_price,
                 ask: buy_price,
-            })
-        } else {
-            None
+                smoothed_bid: None,
+                smoothed_ask: None,
+                is_midpoint_derived: false,
+                last,
+            };
+            return self.finalize_token_price(price, market_name, outcome);
         }
-    }
 
-
-    /// Start monitoring markets continuously
-    /// Returns a callback function that can be used to update markets when new period starts
-    pub async fn start_monitoring<F, Fut>(&self, callback: F)
-    where
-        F: Fn(MarketSnapshot) -> Fut + Send + Sync + 'static,
-        Fut: std::future::Future<Output = ()> + Send + 'static,
-    {
-        info!("Starting market monitoring...");
-        
-        loop {
-            match self.fetch_market_data().await {
-                Ok(snapshot) => {
-                    debug!("Market snapshot updated");
-                    callback(snapshot).await;
-                }
-                Err(e) => {
-                    warn!("Error fetching market data: {}", e);
-                }
+        // Both side-specific prices failed; fall back to the CLOB midpoint so
+        // the leg isn't lost entirely. Only `ask` is populated (not `bid`) so
+        // a midpoint price of e.g. 0.5/0.5 doesn't get flagged as a crossed
+        // book by `TokenPrice::is_crossed`.
+        match self.api.get_midpoint(token_id).await {
+            Ok(mid) => {
+                debug!("{} {} price fallback: using midpoint {} after BUY/SELL both failed", market_name, outcome, mid);
+                let last = self.fetch_last_trade_price(token_id, market_name, outcome).await;
+                let price = TokenPrice {
+                    token_id: token_id.to_string(),
+                    bid: None,
+                    ask: Some(mid),
+                    smoothed_bid: None,
+                    smoothed_ask: None,
+                    is_midpoint_derived: true,
+                    last,
+                };
+                self.finalize_token_price(price, market_name, outcome)
+            }
+            Err(e) => {
+                warn!("Failed to fetch {} {} midpoint fallback: {}", market_name, outcome, e);
+                None
             }
-            
-            sleep(self.check_interval).await;
         }
     }
-}
 
+    /// A single orderbook fetch, using its top bid/ask. See
+    /// `PriceSourcePreference::OrderbookTop`.
+    async fn fetch_token_price_from_orderbook_top(
+        &self,
+        token_id: &str,
+        market_name: &str,
+        outcome: &str,
+    ) -> Option<TokenPrice> {
+        match self.api.get_best_price(token_id).await {
+            Ok(Some(mut price)) => {
+                price.last = self.fetch_last_trade_price(token_id, market_name, outcome).await;
+                self.finalize_token_price(price, market_name, outcome)
+            }
+            Ok(None) => {
+                warn!("{} {} orderbook has no ask; discarding", market_name, outcome);
+                None
+            }
+            Err(e) => {
+                warn!("Failed to fetch {} {} orderbook top: {}", market_name, outcome, e);
+                None
+            }
+        }
+    }
+
+    /// A single call to `/midpoint`. See `PriceSourcePreference::Midpoint`.
+    async fn fetch_token_price_from_midpoint(
+        &self,
+        token_id: &str,
+        market_name: &str,
+        outcome: &str,
+    ) -> Option<TokenPrice> {
+        match self.api.get_midpoint(token_id).await {
+            Ok(mid) => {
+                let last = self.fetch_last_trade_price(token_id, market_name, outcome).await;
+                let price = TokenPrice {
+                    token_id: token_id.to_string(),
+                    bid: None,
+                    ask: Some(mid),
+                    smoothed_bid: None,
+                    smoothed_ask: None,
+                    is_midpoint_derived: true,
+                    last,
+                };
+                self.finalize_token_price(price, market_name, outcome)
+            }
+            Err(e) => {
+                warn!("Failed to fetch {} {} midpoint: {}", market_name, outcome, e);
+                None
+            }
+        }
+    }
+
+    /// Shared tail end of every `fetch_token_price_from_*` variant: rejects a
+    /// price outside `[min_sane_price, max_sane_price]` rather than feeding
+    /// it into arbitrage detection.
+    fn finalize_token_price(&self, price: TokenPrice, market_name: &str, outcome: &str) -> Option<TokenPrice> {
+        if !price.is_within_sane_bounds(self.min_sane_price, self.max_sane_price) {
+            warn!(
+                "{} {} price out of sane bounds [{}, {}] (bid={:?}, ask={:?}); discarding",
+                market_name, outcome, self.min_sane_price, self.max_sane_price, price.bid, price.ask
+            );
+            return None;
+        }
+        Some(price)
+    }
+
+    /// Fetches the last traded price for `token_id` when
+    /// `last_trade_price_band_check` is enabled, otherwise returns `None`
+    /// without making a request. Also skipped, and logged at debug level,
+    /// once the API call budget is exhausted for the current period - this
+    /// fetch only narrows detection (see below), so it's non-essential and
+    /// the first thing to shed when calls are scarce. A fetch failure is
+    /// logged and also treated as `None` - `TokenPrice::is_within_last_trade_band`
+    /// is permissive on missing data, so this only ever narrows detection,
+    /// never blocks it outright on a transient error.
+    async fn fetch_last_trade_price(&self, token_id: &str, market_name: &str, outcome: &str) -> Option<Decimal> {
+        if !self.last_trade_price_band_check {
+            return None;
+        }
+        if self.api.is_call_budget_exhausted() {
+            debug!(
+                "Skipping {} {} last trade price fetch: API call budget exhausted for this period",
+                market_name, outcome
+            );
+            return None;
+        }
+        match self.api.get_last_trade_price(token_id).await {
+            Ok(last) => Some(last),
+            Err(e) => {
+                warn!("Failed to fetch {} {} last trade price: {}", market_name, outcome, e);
+                None
+            }
+        }
+    }
+
+    /// Fold a freshly-fetched price into this token's running EMA and
+    /// return it with `smoothed_bid`/`smoothed_ask` populated.
+    async fn smooth_token_price(&self, price: Option<TokenPrice>) -> Option<TokenPrice> {
+        let mut price = price?;
+        let mut ema = self.price_ema.lock().await;
+        let (prev_bid, prev_ask) = ema.get(&price.token_id).copied().unwrap_or((None, None));
+
+        let smoothed_bid = price.bid.map(|raw| ema_update(prev_bid, raw, self.price_ema_alpha));
+        let smoothed_ask = price.ask.map(|raw| ema_update(prev_ask, raw, self.price_ema_alpha));
+
+        ema.insert(price.token_id.clone(), (smoothed_bid.or(prev_bid), smoothed_ask.or(prev_ask)));
+        drop(ema);
+
+        self.record_price_sample(&price.token_id, price.bid, price.ask).await;
+
+        price.smoothed_bid = smoothed_bid;
+        price.smoothed_ask = smoothed_ask;
+        Some(price)
+    }
+
+    /// Start monitoring markets continuously
+    /// Returns a callback function that can be used to update markets when new period starts
+    pub async fn start_monitoring<F, Fut>(&self, callback: F)
+    where
+        F: Fn(MarketSnapshot) -> Fut + Send + Sync + 'static,
+        Fut: std::future::Future<Output = ()> + Send + 'static,
+    {
+        info!("Starting market monitoring...");
+
+        loop {
+            *self.last_loop_tick.lock().await = Some(unix_now_secs());
+
+            match self.fetch_market_data().await {
+                Ok(snapshot) => {
+                    debug!("Market snapshot updated");
+                    callback(snapshot).await;
+                }
+                Err(e) => {
+                    warn!("Error fetching market data: {}", e);
+                }
+            }
+            
+            sleep(self.check_interval).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::PolymarketApi;
+    use crate::models::Market;
+    use rust_decimal_macros::dec;
+
+    fn dummy_market(condition_id: &str, slug: &str) -> Market {
+        Market {
+            condition_id: condition_id.to_string(),
+            market_id: None,
+            question: "test".to_string(),
+            slug: slug.to_string(),
+            resolution_source: None,
+            end_date_iso: None,
+            end_date_iso_alt: None,
+            active: true,
+            closed: false,
+            tokens: None,
+            clob_token_ids: None,
+            outcomes: None,
+        }
+    }
+
+    fn dummy_monitor() -> MarketMonitor {
+        let api = Arc::new(PolymarketApi::new(
+            "https://gamma.example".to_string(),
+            "https://clob.example".to_string(),
+            None,
+        ));
+        MarketMonitor::new(
+            api,
+            dummy_market("sol-cond", "sol-updown-15m-0"),
+            dummy_market("btc-cond", "btc-updown-15m-0"),
+            1000,
+            900,
+            None,
+            default_up_keywords(),
+            default_down_keywords(),
+            0.3,
+            200,
+            30,
+            0.001,
+            0.999,
+            false,
+            PriceInversionPolicy::default(),
+            false,
+            PriceSourcePreference::default(),
+            None,
+            None,
+            0.10,
+            false,
+        )
+    }
+
+    fn dummy_monitor_with_cache(cache_path: std::path::PathBuf) -> MarketMonitor {
+        let api = Arc::new(PolymarketApi::new(
+            "https://gamma.example".to_string(),
+            "https://clob.example".to_string(),
+            None,
+        ));
+        MarketMonitor::new(
+            api,
+            dummy_market("sol-cond", "sol-updown-15m-0"),
+            dummy_market("btc-cond", "btc-updown-15m-0"),
+            1000,
+            900,
+            Some(cache_path),
+            default_up_keywords(),
+            default_down_keywords(),
+            0.3,
+            200,
+            30,
+            0.001,
+            0.999,
+            false,
+            PriceInversionPolicy::default(),
+            false,
+            PriceSourcePreference::default(),
+            None,
+            None,
+            0.10,
+            false,
+        )
+    }
+
+    fn dummy_monitor_with_max_consecutive_failures(max_consecutive_price_failures: Option<u32>) -> MarketMonitor {
+        let api = Arc::new(PolymarketApi::new(
+            "https://gamma.example".to_string(),
+            "https://clob.example".to_string(),
+            None,
+        ));
+        MarketMonitor::new(
+            api,
+            dummy_market("sol-cond", "sol-updown-15m-0"),
+            dummy_market("btc-cond", "btc-updown-15m-0"),
+            1000,
+            900,
+            None,
+            default_up_keywords(),
+            default_down_keywords(),
+            0.3,
+            200,
+            30,
+            0.001,
+            0.999,
+            false,
+            PriceInversionPolicy::default(),
+            false,
+            PriceSourcePreference::default(),
+            max_consecutive_price_failures,
+            None,
+            0.10,
+            false,
+        )
+    }
+
+    fn default_up_keywords() -> Vec<String> {
+        vec!["UP".to_string(), "1".to_string()]
+    }
+
+    fn default_down_keywords() -> Vec<String> {
+        vec!["DOWN".to_string(), "0".to_string()]
+    }
+
+    #[test]
+    fn period_of_rounds_down_to_the_period_boundary() {
+        assert_eq!(period_of(0, 900), 0);
+        assert_eq!(period_of(899, 900), 0);
+        assert_eq!(period_of(900, 900), 900);
+        assert_eq!(period_of(901, 900), 900);
+        assert_eq!(period_of(1799, 900), 900);
+        assert_eq!(period_of(1800, 900), 1800);
+    }
+
+    #[test]
+    fn period_of_supports_non_15_minute_durations() {
+        // Hourly markets (3600s periods).
+        assert_eq!(period_of(3599, 3600), 0);
+        assert_eq!(period_of(3600, 3600), 3600);
+    }
+
+    #[test]
+    fn is_new_period_detects_boundary_crossing() {
+        // Exactly on the boundary of a new period.
+        assert!(is_new_period(900, 0, 900));
+        // Just before the boundary: still the old period.
+        assert!(!is_new_period(899, 0, 900));
+        // Just after the boundary: new period.
+        assert!(is_new_period(901, 0, 900));
+        // Same period: no change.
+        assert!(!is_new_period(450, 0, 900));
+    }
+
+    #[test]
+    fn parse_iso8601_to_unix_secs_parses_a_valid_timestamp() {
+        // 2024-01-01T00:00:00Z is a known epoch offset.
+        assert_eq!(parse_iso8601_to_unix_secs("2024-01-01T00:00:00Z"), Some(1_704_067_200));
+    }
+
+    #[test]
+    fn parse_iso8601_to_unix_secs_ignores_fractional_seconds() {
+        assert_eq!(
+            parse_iso8601_to_unix_secs("2024-01-01T00:00:00.123Z"),
+            Some(1_704_067_200)
+        );
+    }
+
+    #[test]
+    fn parse_iso8601_to_unix_secs_rejects_malformed_input() {
+        assert_eq!(parse_iso8601_to_unix_secs("not a timestamp"), None);
+        assert_eq!(parse_iso8601_to_unix_secs("2024-01-01"), None);
+        assert_eq!(parse_iso8601_to_unix_secs("2024-13-01T00:00:00Z"), None);
+        assert_eq!(parse_iso8601_to_unix_secs("2024-01-01T25:00:00Z"), None);
+    }
+
+    #[test]
+    fn period_boundary_drift_secs_is_zero_for_matching_end_times() {
+        let mut sol = dummy_market("sol-cond", "sol-updown-15m-0");
+        let mut btc = dummy_market("btc-cond", "btc-updown-15m-0");
+        sol.end_date_iso = Some("2024-01-01T00:15:00Z".to_string());
+        btc.end_date_iso = Some("2024-01-01T00:15:00Z".to_string());
+        assert_eq!(period_boundary_drift_secs(&sol, &btc), Some(0));
+    }
+
+    #[test]
+    fn period_boundary_drift_secs_reports_the_gap_when_boundaries_differ() {
+        let mut sol = dummy_market("sol-cond", "sol-updown-15m-0");
+        let mut btc = dummy_market("btc-cond", "btc-updown-15m-0");
+        sol.end_date_iso = Some("2024-01-01T00:15:45Z".to_string());
+        btc.end_date_iso = Some("2024-01-01T00:15:00Z".to_string());
+        assert_eq!(period_boundary_drift_secs(&sol, &btc), Some(45));
+    }
+
+    #[test]
+    fn period_boundary_drift_secs_is_none_when_an_end_time_is_missing() {
+        let sol = dummy_market("sol-cond", "sol-updown-15m-0");
+        let mut btc = dummy_market("btc-cond", "btc-updown-15m-0");
+        btc.end_date_iso = Some("2024-01-01T00:15:00Z".to_string());
+        assert_eq!(period_boundary_drift_secs(&sol, &btc), None);
+    }
+
+    #[test]
+    fn ema_update_seeds_from_the_first_raw_sample() {
+        assert_eq!(ema_update(None, dec!(0.5), dec!(0.3)), dec!(0.5));
+    }
+
+    #[test]
+    fn ema_update_blends_toward_the_new_sample() {
+        let smoothed = ema_update(Some(dec!(0.5)), dec!(0.8), dec!(0.3));
+        assert_eq!(smoothed, dec!(0.59)); // 0.3*0.8 + 0.7*0.5
+    }
+
+    #[tokio::test]
+    async fn smooth_token_price_filters_a_single_tick_spike() {
+        let monitor = dummy_monitor();
+        let mut steady = token_price("tok");
+        steady.ask = Some(dec!(0.5));
+
+        let first = monitor.smooth_token_price(Some(steady.clone())).await.unwrap();
+        assert_eq!(first.smoothed_ask, Some(dec!(0.5)));
+
+        let mut spike = steady.clone();
+        spike.ask = Some(dec!(1.0)); // a single anomalous tick
+        let second = monitor.smooth_token_price(Some(spike)).await.unwrap();
+
+        // The smoothed ask moves toward the spike but doesn't jump all the
+        // way there, unlike the raw ask.
+        assert!(second.smoothed_ask.unwrap() < dec!(1.0));
+        assert!(second.smoothed_ask.unwrap() > dec!(0.5));
+    }
+
+    #[tokio::test]
+    async fn price_history_is_empty_for_an_unseen_token() {
+        let monitor = dummy_monitor();
+        assert!(monitor.price_history("tok").await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn price_history_records_samples_in_order() {
+        let monitor = dummy_monitor();
+        let mut sample = token_price("tok");
+
+        sample.ask = Some(dec!(0.5));
+        monitor.smooth_token_price(Some(sample.clone())).await;
+        sample.ask = Some(dec!(0.6));
+        monitor.smooth_token_price(Some(sample)).await;
+
+        let history = monitor.price_history("tok").await;
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].ask, Some(dec!(0.5)));
+        assert_eq!(history[1].ask, Some(dec!(0.6)));
+    }
+
+    #[tokio::test]
+    async fn price_history_evicts_the_oldest_sample_once_at_capacity() {
+        let api = Arc::new(PolymarketApi::new(
+            "https://gamma.example".to_string(),
+            "https://clob.example".to_string(),
+            None,
+        ));
+        let monitor = MarketMonitor::new(
+            api,
+            dummy_market("sol-cond", "sol-updown-15m-0"),
+            dummy_market("btc-cond", "btc-updown-15m-0"),
+            1000,
+            900,
+            None,
+            default_up_keywords(),
+            default_down_keywords(),
+            0.3,
+            2,
+            30,
+            0.001,
+            0.999,
+            false,
+            PriceInversionPolicy::default(),
+            false,
+            PriceSourcePreference::default(),
+            None,
+            None,
+            0.10,
+            false,
+        );
+
+        for ask in [dec!(0.1), dec!(0.2), dec!(0.3)] {
+            let mut sample = token_price("tok");
+            sample.ask = Some(ask);
+            monitor.smooth_token_price(Some(sample)).await;
+        }
+
+        let history = monitor.price_history("tok").await;
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].ask, Some(dec!(0.2)));
+        assert_eq!(history[1].ask, Some(dec!(0.3)));
+    }
+
+    fn sample_at(timestamp_unix: u64, price: Decimal) -> PriceSample {
+        PriceSample { timestamp_unix, bid: Some(price), ask: Some(price) }
+    }
+
+    #[test]
+    fn price_correlation_is_none_with_too_little_overlap() {
+        let a = vec![sample_at(0, dec!(0.5)), sample_at(1, dec!(0.6))];
+        let b = vec![sample_at(0, dec!(0.5)), sample_at(1, dec!(0.6))];
+        assert_eq!(price_correlation(&a, &b), None);
+    }
+
+    #[test]
+    fn price_correlation_is_none_when_one_series_is_constant() {
+        let a: Vec<PriceSample> = (0..5).map(|i| sample_at(i, dec!(0.5) + Decimal::from(i))).collect();
+        let b: Vec<PriceSample> = (0..5).map(|_| sample_at(0, dec!(0.5))).collect();
+        assert_eq!(price_correlation(&a, &b), None);
+    }
+
+    // Uneven, non-arithmetic steps (unlike a plain 0.1*i ramp) so the
+    // tick-to-tick deltas actually vary - a perfectly constant delta series
+    // has ~zero variance and makes the correlation ratio numerically
+    // unstable once run through decimal-to-f64 conversion.
+    const UNEVEN_STEPS: [Decimal; 5] = [dec!(0.10), dec!(0.15), dec!(0.35), dec!(0.40), dec!(0.65)];
+
+    #[test]
+    fn price_correlation_is_strongly_positive_when_prices_move_together() {
+        let a: Vec<PriceSample> = UNEVEN_STEPS.iter().enumerate().map(|(i, p)| sample_at(i as u64, *p)).collect();
+        let b: Vec<PriceSample> =
+            UNEVEN_STEPS.iter().enumerate().map(|(i, p)| sample_at(i as u64, *p * dec!(2))).collect();
+        let correlation = price_correlation(&a, &b).unwrap();
+        assert!(correlation > 0.99, "expected near +1.0, got {correlation}");
+    }
+
+    #[test]
+    fn price_correlation_is_strongly_negative_when_prices_move_oppositely() {
+        let a: Vec<PriceSample> = UNEVEN_STEPS.iter().enumerate().map(|(i, p)| sample_at(i as u64, *p)).collect();
+        let b: Vec<PriceSample> =
+            UNEVEN_STEPS.iter().enumerate().map(|(i, p)| sample_at(i as u64, dec!(1.0) - *p)).collect();
+        let correlation = price_correlation(&a, &b).unwrap();
+        assert!(correlation < -0.99, "expected near -1.0, got {correlation}");
+    }
+
+    #[tokio::test]
+    async fn sol_btc_price_correlation_is_none_before_token_ids_are_known() {
+        let monitor = dummy_monitor();
+        assert_eq!(monitor.sol_btc_price_correlation().await, None);
+    }
+
+    #[tokio::test]
+    async fn sol_btc_price_correlation_reflects_recorded_history() {
+        let monitor = dummy_monitor();
+        *monitor.sol_up_token_id.lock().await = Some("sol-up".to_string());
+        *monitor.btc_up_token_id.lock().await = Some("btc-up".to_string());
+
+        for price in UNEVEN_STEPS {
+            let mut sol_sample = token_price("sol-up");
+            sol_sample.ask = Some(price);
+            monitor.smooth_token_price(Some(sol_sample)).await;
+
+            let mut btc_sample = token_price("btc-up");
+            btc_sample.ask = Some(price * dec!(2));
+            monitor.smooth_token_price(Some(btc_sample)).await;
+        }
+
+        let correlation = monitor.sol_btc_price_correlation().await.unwrap();
+        assert!(correlation > 0.99, "expected near +1.0, got {correlation}");
+    }
+
+    #[tokio::test]
+    async fn update_markets_resets_cached_token_ids_and_refresh_timestamp() {
+        let monitor = dummy_monitor();
+
+        *monitor.sol_up_token_id.lock().await = Some("sol-up".to_string());
+        *monitor.sol_down_token_id.lock().await = Some("sol-down".to_string());
+        *monitor.btc_up_token_id.lock().await = Some("btc-up".to_string());
+        *monitor.btc_down_token_id.lock().await = Some("btc-down".to_string());
+        *monitor.last_market_refresh.lock().await = Some(unix_now_secs());
+
+        monitor
+            .update_sol_market(dummy_market("sol-cond-2", "sol-updown-15m-900"))
+            .await
+            .unwrap();
+        monitor
+            .update_btc_market(dummy_market("btc-cond-2", "btc-updown-15m-900"))
+            .await
+            .unwrap();
+
+        assert!(monitor.sol_up_token_id.lock().await.is_none());
+        assert!(monitor.sol_down_token_id.lock().await.is_none());
+        assert!(monitor.btc_up_token_id.lock().await.is_none());
+        assert!(monitor.btc_down_token_id.lock().await.is_none());
+        assert!(monitor.last_market_refresh.lock().await.is_none());
+
+        let (sol_id, btc_id) = monitor.get_current_condition_ids().await;
+        assert_eq!(sol_id, "sol-cond-2");
+        assert_eq!(btc_id, "btc-cond-2");
+    }
+
+    #[tokio::test]
+    async fn override_markets_applies_both_sides_when_the_market_validates() {
+        let mut details = dummy_market_details(true, Vec::new());
+        details.condition_id = "sol-cond-2".to_string();
+        details.market_slug = "sol-updown-15m-900".to_string();
+        let source = Arc::new(ScriptedPriceSource {
+            market: Some(details),
+            ..Default::default()
+        });
+        let monitor = dummy_monitor_with_source(source);
+
+        monitor.override_markets("sol-cond-2", "btc-cond-2").await.unwrap();
+
+        // ScriptedPriceSource::get_market ignores its condition_id argument
+        // and always returns the same scripted MarketDetails, so both sides
+        // resolve to it here - the point of the assertion is that both
+        // update_sol_market and update_btc_market actually ran.
+        let (sol_id, btc_id) = monitor.get_current_condition_ids().await;
+        assert_eq!(sol_id, "sol-cond-2");
+        assert_eq!(btc_id, "sol-cond-2");
+    }
+
+    #[tokio::test]
+    async fn override_markets_rejects_a_market_that_is_not_accepting_orders() {
+        let mut details = dummy_market_details(true, Vec::new());
+        details.accepting_orders = false;
+        let source = Arc::new(ScriptedPriceSource {
+            market: Some(details),
+            ..Default::default()
+        });
+        let monitor = dummy_monitor_with_source(source);
+
+        let result = monitor.override_markets("sol-cond-2", "btc-cond-2").await;
+
+        assert!(result.is_err());
+        let (sol_id, btc_id) = monitor.get_current_condition_ids().await;
+        assert_eq!(sol_id, "sol-cond");
+        assert_eq!(btc_id, "btc-cond");
+    }
+
+    #[tokio::test]
+    async fn seconds_since_last_tick_is_none_before_the_loop_has_run() {
+        let monitor = dummy_monitor();
+        assert_eq!(monitor.seconds_since_last_tick().await, None);
+    }
+
+    #[tokio::test]
+    async fn seconds_since_last_tick_reflects_the_most_recent_recorded_tick() {
+        let monitor = dummy_monitor();
+        *monitor.last_loop_tick.lock().await = Some(unix_now_secs() - 30);
+        assert_eq!(monitor.seconds_since_last_tick().await, Some(30));
+    }
+
+    #[tokio::test]
+    async fn ms_since_last_rollover_is_none_until_a_rollover_is_observed() {
+        let monitor = dummy_monitor();
+        assert_eq!(monitor.ms_since_last_rollover().await, None);
+    }
+
+    #[tokio::test]
+    async fn ms_since_last_rollover_is_set_once_a_new_period_begins() {
+        let monitor = dummy_monitor();
+
+        // Simulate a period rollover, same as `a_new_period_marks_both_markets_pending_until_each_is_updated`.
+        *monitor.current_period_timestamp.lock().await -= 900;
+        monitor
+            .update_sol_market(dummy_market("sol-cond-2", "sol-updown-15m-900"))
+            .await
+            .unwrap();
+
+        let elapsed = monitor.ms_since_last_rollover().await;
+        assert!(elapsed.is_some());
+        assert!(elapsed.unwrap() < 5_000);
+    }
+
+    #[tokio::test]
+    async fn markets_pending_discovery_is_false_for_both_right_after_construction() {
+        let monitor = dummy_monitor();
+        assert_eq!(monitor.markets_pending_discovery().await, (false, false));
+        assert!(!monitor.should_discover_new_markets().await);
+    }
+
+    #[tokio::test]
+    async fn a_new_period_marks_both_markets_pending_until_each_is_updated() {
+        let monitor = dummy_monitor();
+
+        // Simulate a period rollover without going through update_sol_market/update_btc_market.
+        *monitor.current_period_timestamp.lock().await -= 900;
+        assert_eq!(monitor.markets_pending_discovery().await, (true, true));
+        assert!(monitor.should_discover_new_markets().await);
+
+        monitor
+            .update_sol_market(dummy_market("sol-cond-2", "sol-updown-15m-900"))
+            .await
+            .unwrap();
+
+        // SOL succeeded, BTC is still outstanding and should be retried
+        // without re-discovering SOL.
+        assert_eq!(monitor.markets_pending_discovery().await, (false, true));
+        assert!(monitor.should_discover_new_markets().await);
+
+        monitor
+            .update_btc_market(dummy_market("btc-cond-2", "btc-updown-15m-900"))
+            .await
+            .unwrap();
+
+        assert_eq!(monitor.markets_pending_discovery().await, (false, false));
+        assert!(!monitor.should_discover_new_markets().await);
+    }
+
+    #[tokio::test]
+    async fn update_sol_market_leaves_btc_state_untouched() {
+        let monitor = dummy_monitor();
+        *monitor.btc_up_token_id.lock().await = Some("btc-up".to_string());
+
+        monitor
+            .update_sol_market(dummy_market("sol-cond-2", "sol-updown-15m-900"))
+            .await
+            .unwrap();
+
+        assert!(monitor.sol_up_token_id.lock().await.is_none());
+        assert_eq!(monitor.btc_up_token_id.lock().await.as_deref(), Some("btc-up"));
+
+        let (_, btc_id) = monitor.get_current_condition_ids().await;
+        assert_eq!(btc_id, "btc-cond");
+    }
+
+    fn temp_cache_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("monitor_test_{}_{}.json", name, std::process::id()))
+    }
+
+    #[tokio::test]
+    async fn warm_start_applies_a_cache_from_the_current_period() {
+        let cache_path = temp_cache_path("fresh");
+        let monitor = dummy_monitor_with_cache(cache_path.clone());
+        let current_period = *monitor.current_period_timestamp.lock().await;
+
+        let cache = PersistedTokenCache {
+            period: current_period,
+            sol_condition_id: "sol-cond".to_string(),
+            btc_condition_id: "btc-cond".to_string(),
+            sol_up_token_id: Some("sol-up".to_string()),
+            sol_down_token_id: Some("sol-down".to_string()),
+            btc_up_token_id: Some("btc-up".to_string()),
+            btc_down_token_id: Some("btc-down".to_string()),
+        };
+        std::fs::write(&cache_path, serde_json::to_string(&cache).unwrap()).unwrap();
+
+        let applied = monitor.try_warm_start().await.unwrap();
+        std::fs::remove_file(&cache_path).ok();
+
+        assert!(applied);
+        assert_eq!(monitor.sol_up_token_id.lock().await.as_deref(), Some("sol-up"));
+        assert_eq!(monitor.btc_down_token_id.lock().await.as_deref(), Some("btc-down"));
+    }
+
+    #[tokio::test]
+    async fn warm_start_ignores_a_cache_from_a_stale_period() {
+        let cache_path = temp_cache_path("stale");
+        let monitor = dummy_monitor_with_cache(cache_path.clone());
+        let current_period = *monitor.current_period_timestamp.lock().await;
+
+        let cache = PersistedTokenCache {
+            period: current_period.saturating_sub(900),
+            sol_condition_id: "sol-cond".to_string(),
+            btc_condition_id: "btc-cond".to_string(),
+            sol_up_token_id: Some("sol-up".to_string()),
+            sol_down_token_id: Some("sol-down".to_string()),
+            btc_up_token_id: Some("btc-up".to_string()),
+            btc_down_token_id: Some("btc-down".to_string()),
+        };
+        std::fs::write(&cache_path, serde_json::to_string(&cache).unwrap()).unwrap();
+
+        let applied = monitor.try_warm_start().await.unwrap();
+        std::fs::remove_file(&cache_path).ok();
+
+        assert!(!applied);
+        assert!(monitor.sol_up_token_id.lock().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn warm_start_is_a_no_op_without_a_configured_cache_path() {
+        let monitor = dummy_monitor();
+        assert!(!monitor.try_warm_start().await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn latest_snapshot_is_none_before_any_fetch() {
+        let monitor = dummy_monitor();
+        assert!(monitor.latest_snapshot().await.is_none());
+    }
+
+    #[test]
+    fn classify_outcome_matches_default_keywords_case_insensitively() {
+        let up = default_up_keywords();
+        let down = default_down_keywords();
+
+        assert_eq!(classify_outcome("Up", &up, &down), Some(true));
+        assert_eq!(classify_outcome(" down ", &up, &down), Some(false));
+        assert_eq!(classify_outcome("1", &up, &down), Some(true));
+        assert_eq!(classify_outcome("0", &up, &down), Some(false));
+        assert_eq!(classify_outcome("Sideways", &up, &down), None);
+    }
+
+    #[test]
+    fn classify_outcome_supports_custom_keyword_lists() {
+        let up = vec!["Yes".to_string()];
+        let down = vec!["No".to_string()];
+
+        assert_eq!(classify_outcome("YES", &up, &down), Some(true));
+        assert_eq!(classify_outcome("no", &up, &down), Some(false));
+        assert_eq!(classify_outcome("Up", &up, &down), None);
+    }
+
+    fn token_price(token_id: &str) -> TokenPrice {
+        TokenPrice {
+            token_id: token_id.to_string(),
+            bid: None,
+            ask: None,
+            smoothed_bid: None,
+            smoothed_ask: None,
+            is_midpoint_derived: false,
+            last: None,
+        }
+    }
+
+    #[test]
+    fn find_duplicate_token_id_detects_a_shared_token_across_markets() {
+        let sol_market = MarketData {
+            condition_id: "sol-cond".to_string(),
+            market_name: "SOL".to_string(),
+            up_token: Some(token_price("shared-token")),
+            down_token: Some(token_price("sol-down")),
+        };
+        let btc_market = MarketData {
+            condition_id: "btc-cond".to_string(),
+            market_name: "BTC".to_string(),
+            up_token: Some(token_price("btc-up")),
+            down_token: Some(token_price("shared-token")),
+        };
+
+        assert_eq!(
+            find_duplicate_token_id(&sol_market, &btc_market),
+            Some("shared-token".to_string())
+        );
+    }
+
+    #[test]
+    fn find_duplicate_token_id_is_none_for_disjoint_markets() {
+        let sol_market = MarketData {
+            condition_id: "sol-cond".to_string(),
+            market_name: "SOL".to_string(),
+            up_token: Some(token_price("sol-up")),
+            down_token: Some(token_price("sol-down")),
+        };
+        let btc_market = MarketData {
+            condition_id: "btc-cond".to_string(),
+            market_name: "BTC".to_string(),
+            up_token: Some(token_price("btc-up")),
+            down_token: Some(token_price("btc-down")),
+        };
+
+        assert_eq!(find_duplicate_token_id(&sol_market, &btc_market), None);
+    }
+
+    #[test]
+    fn is_within_sane_bounds_accepts_a_normal_bid_and_ask() {
+        let mut price = token_price("tok");
+        price.bid = Some(dec!(0.45));
+        price.ask = Some(dec!(0.55));
+
+        assert!(price.is_within_sane_bounds(dec!(0.001), dec!(0.999)));
+    }
+
+    #[test]
+    fn is_within_sane_bounds_rejects_a_price_above_one() {
+        let mut price = token_price("tok");
+        price.ask = Some(dec!(1.2));
+
+        assert!(!price.is_within_sane_bounds(dec!(0.001), dec!(0.999)));
+    }
+
+    #[test]
+    fn is_within_sane_bounds_rejects_a_negative_price() {
+        let mut price = token_price("tok");
+        price.bid = Some(dec!(-0.1));
+
+        assert!(!price.is_within_sane_bounds(dec!(0.001), dec!(0.999)));
+    }
+
+    #[test]
+    fn is_within_sane_bounds_ignores_absent_sides() {
+        let price = token_price("tok");
+
+        assert!(price.is_within_sane_bounds(dec!(0.001), dec!(0.999)));
+    }
+
+    /// A `PriceSource` that returns pre-scripted BUY/SELL prices instead of
+    /// making real HTTP calls, so `fetch_token_price`'s bid/ask/midpoint
+    /// fallback logic can be exercised deterministically.
+    #[derive(Default)]
+    struct ScriptedPriceSource {
+        buy: Option<rust_decimal::Decimal>,
+        sell: Option<rust_decimal::Decimal>,
+        midpoint: Option<rust_decimal::Decimal>,
+        last_trade: Option<rust_decimal::Decimal>,
+        market: Option<crate::models::MarketDetails>,
+        best_price: Option<TokenPrice>,
+    }
+
+    fn dummy_market_details(is_50_50_outcome: bool, tokens: Vec<crate::models::MarketToken>) -> crate::models::MarketDetails {
+        crate::models::MarketDetails {
+            accepting_order_timestamp: None,
+            accepting_orders: true,
+            active: true,
+            archived: false,
+            closed: false,
+            condition_id: "cond".to_string(),
+            description: String::new(),
+            enable_order_book: true,
+            end_date_iso: String::new(),
+            fpmm: String::new(),
+            game_start_time: None,
+            icon: String::new(),
+            image: String::new(),
+            is_50_50_outcome,
+            maker_base_fee: rust_decimal::Decimal::ZERO,
+            market_slug: "market".to_string(),
+            minimum_order_size: rust_decimal::Decimal::ONE,
+            minimum_tick_size: dec!(0.01),
+            neg_risk: false,
+            neg_risk_market_id: String::new(),
+            neg_risk_request_id: String::new(),
+            notifications_enabled: true,
+            question: String::new(),
+            question_id: String::new(),
+            rewards: crate::models::Rewards {
+                max_spread: rust_decimal::Decimal::ZERO,
+                min_size: rust_decimal::Decimal::ZERO,
+                rates: None,
+            },
+            seconds_delay: 0,
+            tags: Vec::new(),
+            taker_base_fee: rust_decimal::Decimal::ZERO,
+            tokens,
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl crate::api::PriceSource for ScriptedPriceSource {
+        async fn get_market(&self, _condition_id: &str) -> Result<crate::models::MarketDetails, crate::error::ApiError> {
+            self.market.clone().ok_or_else(|| crate::error::ApiError::InvalidResponse("not scripted".to_string()))
+        }
+
+        async fn get_price(&self, _token_id: &str, side: &str) -> Result<rust_decimal::Decimal, crate::error::ApiError> {
+            let scripted = if side == "BUY" { self.buy } else { self.sell };
+            scripted.ok_or_else(|| crate::error::ApiError::InvalidResponse("not scripted".to_string()))
+        }
+
+        async fn get_midpoint(&self, _token_id: &str) -> Result<rust_decimal::Decimal, crate::error::ApiError> {
+            self.midpoint.ok_or_else(|| crate::error::ApiError::InvalidResponse("not scripted".to_string()))
+        }
+
+        async fn get_last_trade_price(&self, _token_id: &str) -> Result<rust_decimal::Decimal, crate::error::ApiError> {
+            self.last_trade.ok_or_else(|| crate::error::ApiError::InvalidResponse("not scripted".to_string()))
+        }
+
+        async fn get_best_price(&self, _token_id: &str) -> Result<Option<TokenPrice>, crate::error::ApiError> {
+            match &self.best_price {
+                Some(price) => Ok(Some(price.clone())),
+                None => Err(crate::error::ApiError::InvalidResponse("not scripted".to_string())),
+            }
+        }
+
+        async fn place_order(&self, _order: &crate::models::OrderRequest) -> Result<crate::models::OrderResponse, crate::error::ApiError> {
+            Err(crate::error::ApiError::InvalidResponse("not scripted".to_string()))
+        }
+
+        async fn validate_order(&self, _order: &crate::models::OrderRequest) -> Result<crate::models::OrderValidation, crate::error::ApiError> {
+            Err(crate::error::ApiError::InvalidResponse("not scripted".to_string()))
+        }
+
+        async fn get_positions(&self) -> Result<Vec<crate::models::Position>, crate::error::ApiError> {
+            Err(crate::error::ApiError::InvalidResponse("not scripted".to_string()))
+        }
+
+        async fn get_trade_history(&self) -> Result<Vec<crate::models::TradeHistoryEntry>, crate::error::ApiError> {
+            Err(crate::error::ApiError::InvalidResponse("not scripted".to_string()))
+        }
+
+        async fn get_order_status(&self, _order_id: &str) -> Result<crate::models::OrderResponse, crate::error::ApiError> {
+            Err(crate::error::ApiError::InvalidResponse("not scripted".to_string()))
+        }
+    }
+
+    fn dummy_monitor_with_source(source: Arc<dyn crate::api::PriceSource>) -> MarketMonitor {
+        dummy_monitor_with_source_and_50_50_policy(source, false)
+    }
+
+    fn dummy_monitor_with_source_and_50_50_policy(source: Arc<dyn crate::api::PriceSource>, allow_non_50_50_markets: bool) -> MarketMonitor {
+        MarketMonitor::new(
+            source,
+            dummy_market("sol-cond", "sol-updown-15m-0"),
+            dummy_market("btc-cond", "btc-updown-15m-0"),
+            1000,
+            900,
+            None,
+            default_up_keywords(),
+            default_down_keywords(),
+            0.3,
+            200,
+            30,
+            0.001,
+            0.999,
+            allow_non_50_50_markets,
+            PriceInversionPolicy::default(),
+            false,
+            PriceSourcePreference::default(),
+            None,
+            None,
+            0.10,
+            false,
+        )
+    }
+
+    fn dummy_monitor_with_source_and_inversion_policy(
+        source: Arc<dyn crate::api::PriceSource>,
+        price_inversion_policy: PriceInversionPolicy,
+    ) -> MarketMonitor {
+        MarketMonitor::new(
+            source,
+            dummy_market("sol-cond", "sol-updown-15m-0"),
+            dummy_market("btc-cond", "btc-updown-15m-0"),
+            1000,
+            900,
+            None,
+            default_up_keywords(),
+            default_down_keywords(),
+            0.3,
+            200,
+            30,
+            0.001,
+            0.999,
+            false,
+            price_inversion_policy,
+            false,
+            PriceSourcePreference::default(),
+            None,
+            None,
+            0.10,
+            false,
+        )
+    }
+
+    #[tokio::test]
+    async fn fetch_token_price_uses_scripted_buy_and_sell_prices() {
+        let source = Arc::new(ScriptedPriceSource {
+            buy: Some(dec!(0.55)),
+            sell: Some(dec!(0.45)),
+            ..Default::default()
+        });
+        let monitor = dummy_monitor_with_source(source);
+
+        let price = monitor
+            .fetch_token_price(&Some("tok".to_string()), "SOL", "Up")
+            .await
+            .expect("scripted BUY/SELL prices should produce a price");
+
+        assert_eq!(price.ask, Some(dec!(0.55)));
+        assert_eq!(price.bid, Some(dec!(0.45)));
+        assert!(!price.is_midpoint_derived);
+    }
+
+    #[tokio::test]
+    async fn fetch_token_price_falls_back_to_scripted_midpoint_when_buy_and_sell_fail() {
+        let source = Arc::new(ScriptedPriceSource {
+            midpoint: Some(dec!(0.5)),
+            ..Default::default()
+        });
+        let monitor = dummy_monitor_with_source(source);
+
+        let price = monitor
+            .fetch_token_price(&Some("tok".to_string()), "SOL", "Up")
+            .await
+            .expect("scripted midpoint should produce a fallback price");
+
+        assert_eq!(price.ask, Some(dec!(0.5)));
+        assert_eq!(price.bid, None);
+        assert!(price.is_midpoint_derived);
+    }
+
+    #[test]
+    fn resolve_price_inversion_leaves_a_normal_book_unchanged() {
+        assert_eq!(resolve_price_inversion(dec!(0.45), dec!(0.55), PriceInversionPolicy::Drop), Some((dec!(0.45), dec!(0.55))));
+    }
+
+    #[test]
+    fn resolve_price_inversion_drop_discards_both_sides() {
+        assert_eq!(resolve_price_inversion(dec!(0.6), dec!(0.5), PriceInversionPolicy::Drop), None);
+    }
+
+    #[test]
+    fn resolve_price_inversion_swap_flips_bid_and_ask() {
+        assert_eq!(resolve_price_inversion(dec!(0.6), dec!(0.5), PriceInversionPolicy::Swap), Some((dec!(0.5), dec!(0.6))));
+    }
+
+    #[test]
+    fn resolve_price_inversion_clamp_pulls_bid_down_to_ask() {
+        assert_eq!(resolve_price_inversion(dec!(0.6), dec!(0.5), PriceInversionPolicy::Clamp), Some((dec!(0.5), dec!(0.5))));
+    }
+
+    #[tokio::test]
+    async fn fetch_token_price_drops_an_inverted_book_and_falls_back_to_midpoint() {
+        let source = Arc::new(ScriptedPriceSource {
+            buy: Some(dec!(0.5)),
+            sell: Some(dec!(0.6)),
+            midpoint: Some(dec!(0.55)),
+            ..Default::default()
+        });
+        let monitor = dummy_monitor_with_source_and_inversion_policy(source, PriceInversionPolicy::Drop);
+
+        let price = monitor
+            .fetch_token_price(&Some("tok".to_string()), "SOL", "Up")
+            .await
+            .expect("dropped inversion should fall back to the scripted midpoint");
+
+        assert_eq!(price.ask, Some(dec!(0.55)));
+        assert!(price.is_midpoint_derived);
+    }
+
+    #[tokio::test]
+    async fn fetch_token_price_swaps_an_inverted_book_when_configured() {
+        let source = Arc::new(ScriptedPriceSource {
+            buy: Some(dec!(0.5)),
+            sell: Some(dec!(0.6)),
+            ..Default::default()
+        });
+        let monitor = dummy_monitor_with_source_and_inversion_policy(source, PriceInversionPolicy::Swap);
+
+        let price = monitor
+            .fetch_token_price(&Some("tok".to_string()), "SOL", "Up")
+            .await
+            .expect("swap policy should still produce a price");
+
+        assert_eq!(price.ask, Some(dec!(0.6)));
+        assert_eq!(price.bid, Some(dec!(0.5)));
+        assert!(!price.is_crossed());
+    }
+
+    #[tokio::test]
+    async fn fetch_token_price_clamps_an_inverted_book_when_configured() {
+        let source = Arc::new(ScriptedPriceSource {
+            buy: Some(dec!(0.5)),
+            sell: Some(dec!(0.6)),
+            ..Default::default()
+        });
+        let monitor = dummy_monitor_with_source_and_inversion_policy(source, PriceInversionPolicy::Clamp);
+
+        let price = monitor
+            .fetch_token_price(&Some("tok".to_string()), "SOL", "Up")
+            .await
+            .expect("clamp policy should still produce a price");
+
+        assert_eq!(price.ask, Some(dec!(0.5)));
+        assert_eq!(price.bid, Some(dec!(0.5)));
+    }
+
+    fn dummy_monitor_with_source_and_last_trade_band(source: Arc<dyn crate::api::PriceSource>) -> MarketMonitor {
+        MarketMonitor::new(
+            source,
+            dummy_market("sol-cond", "sol-updown-15m-0"),
+            dummy_market("btc-cond", "btc-updown-15m-0"),
+            1000,
+            900,
+            None,
+            default_up_keywords(),
+            default_down_keywords(),
+            0.3,
+            200,
+            30,
+            0.001,
+            0.999,
+            false,
+            PriceInversionPolicy::default(),
+            true,
+            PriceSourcePreference::default(),
+            None,
+            None,
+            0.10,
+            false,
+        )
+    }
+
+    fn dummy_monitor_with_source_and_price_source_preference(
+        source: Arc<dyn crate::api::PriceSource>,
+        price_source_preference: PriceSourcePreference,
+    ) -> MarketMonitor {
+        MarketMonitor::new(
+            source,
+            dummy_market("sol-cond", "sol-updown-15m-0"),
+            dummy_market("btc-cond", "btc-updown-15m-0"),
+            1000,
+            900,
+            None,
+            default_up_keywords(),
+            default_down_keywords(),
+            0.3,
+            200,
+            30,
+            0.001,
+            0.999,
+            false,
+            PriceInversionPolicy::default(),
+            false,
+            price_source_preference,
+            None,
+            None,
+            0.10,
+            false,
+        )
+    }
+
+    fn dummy_monitor_with_cross_check(
+        source: Arc<dyn crate::api::PriceSource>,
+        cross_check_source: Option<PriceSourcePreference>,
+        cross_check_tolerance_pct: f64,
+        skip_trading_on_cross_check_mismatch: bool,
+    ) -> MarketMonitor {
+        MarketMonitor::new(
+            source,
+            dummy_market("sol-cond", "sol-updown-15m-0"),
+            dummy_market("btc-cond", "btc-updown-15m-0"),
+            1000,
+            900,
+            None,
+            default_up_keywords(),
+            default_down_keywords(),
+            0.3,
+            200,
+            30,
+            0.001,
+            0.999,
+            false,
+            PriceInversionPolicy::default(),
+            false,
+            PriceSourcePreference::default(),
+            None,
+            cross_check_source,
+            cross_check_tolerance_pct,
+            skip_trading_on_cross_check_mismatch,
+        )
+    }
+
+    #[tokio::test]
+    async fn fetch_token_price_keeps_the_primary_price_when_cross_check_sources_agree() {
+        let mut secondary = token_price("tok");
+        secondary.bid = Some(dec!(0.46));
+        secondary.ask = Some(dec!(0.54));
+        let source = Arc::new(ScriptedPriceSource {
+            buy: Some(dec!(0.55)),
+            sell: Some(dec!(0.45)),
+            best_price: Some(secondary),
+            ..Default::default()
+        });
+        let monitor = dummy_monitor_with_cross_check(source, Some(PriceSourcePreference::OrderbookTop), 0.10, true);
+
+        let price = monitor
+            .fetch_token_price(&Some("tok".to_string()), "SOL", "Up")
+            .await
+            .expect("agreeing cross-check should not drop the leg");
+
+        assert_eq!(price.ask, Some(dec!(0.55)));
+        assert_eq!(price.bid, Some(dec!(0.45)));
+    }
+
+    #[tokio::test]
+    async fn fetch_token_price_logs_but_keeps_the_leg_on_mismatch_when_skip_is_disabled() {
+        let mut secondary = token_price("tok");
+        secondary.bid = Some(dec!(0.90));
+        secondary.ask = Some(dec!(0.95));
+        let source = Arc::new(ScriptedPriceSource {
+            buy: Some(dec!(0.55)),
+            sell: Some(dec!(0.45)),
+            best_price: Some(secondary),
+            ..Default::default()
+        });
+        let monitor = dummy_monitor_with_cross_check(source, Some(PriceSourcePreference::OrderbookTop), 0.10, false);
+
+        let price = monitor
+            .fetch_token_price(&Some("tok".to_string()), "SOL", "Up")
+            .await
+            .expect("mismatch should only be logged when skip_trading_on_cross_check_mismatch is false");
+
+        assert_eq!(price.ask, Some(dec!(0.55)));
+    }
+
+    #[tokio::test]
+    async fn fetch_token_price_drops_the_leg_on_mismatch_when_skip_is_enabled() {
+        let mut secondary = token_price("tok");
+        secondary.bid = Some(dec!(0.90));
+        secondary.ask = Some(dec!(0.95));
+        let source = Arc::new(ScriptedPriceSource {
+            buy: Some(dec!(0.55)),
+            sell: Some(dec!(0.45)),
+            best_price: Some(secondary),
+            ..Default::default()
+        });
+        let monitor = dummy_monitor_with_cross_check(source, Some(PriceSourcePreference::OrderbookTop), 0.10, true);
+
+        let price = monitor.fetch_token_price(&Some("tok".to_string()), "SOL", "Up").await;
+
+        assert!(price.is_none());
+    }
+
+    #[tokio::test]
+    async fn fetch_token_price_populates_last_when_the_band_check_is_enabled() {
+        let source = Arc::new(ScriptedPriceSource {
+            buy: Some(dec!(0.55)),
+            sell: Some(dec!(0.45)),
+            last_trade: Some(dec!(0.5)),
+            ..Default::default()
+        });
+        let monitor = dummy_monitor_with_source_and_last_trade_band(source);
+
+        let price = monitor
+            .fetch_token_price(&Some("tok".to_string()), "SOL", "Up")
+            .await
+            .expect("scripted BUY/SELL prices should produce a price");
+
+        assert_eq!(price.last, Some(dec!(0.5)));
+    }
+
+    #[tokio::test]
+    async fn fetch_token_price_leaves_last_unset_when_the_band_check_is_disabled() {
+        let source = Arc::new(ScriptedPriceSource {
+            buy: Some(dec!(0.55)),
+            sell: Some(dec!(0.45)),
+            last_trade: Some(dec!(0.5)),
+            ..Default::default()
+        });
+        let monitor = dummy_monitor_with_source(source);
+
+        let price = monitor
+            .fetch_token_price(&Some("tok".to_string()), "SOL", "Up")
+            .await
+            .expect("scripted BUY/SELL prices should produce a price");
+
+        assert_eq!(price.last, None);
+    }
+
+    fn up_down_tokens() -> Vec<crate::models::MarketToken> {
+        vec![
+            crate::models::MarketToken {
+                outcome: "Up".to_string(),
+                price: dec!(0.5),
+                token_id: "up-tok".to_string(),
+                winner: false,
+            },
+            crate::models::MarketToken {
+                outcome: "Down".to_string(),
+                price: dec!(0.5),
+                token_id: "down-tok".to_string(),
+                winner: false,
+            },
+        ]
+    }
+
+    #[tokio::test]
+    async fn refresh_market_tokens_skips_a_non_50_50_market_by_default() {
+        let source = Arc::new(ScriptedPriceSource {
+            market: Some(dummy_market_details(false, up_down_tokens())),
+            ..Default::default()
+        });
+        let monitor = dummy_monitor_with_source(source);
+
+        monitor.refresh_market_tokens().await.unwrap();
+
+        assert_eq!(*monitor.sol_up_token_id.lock().await, None);
+        assert_eq!(*monitor.sol_down_token_id.lock().await, None);
+    }
+
+    #[tokio::test]
+    async fn refresh_market_tokens_maps_a_non_50_50_market_when_forced() {
+        let source = Arc::new(ScriptedPriceSource {
+            market: Some(dummy_market_details(false, up_down_tokens())),
+            ..Default::default()
+        });
+        let monitor = dummy_monitor_with_source_and_50_50_policy(source, true);
+
+        monitor.refresh_market_tokens().await.unwrap();
+
+        assert_eq!(*monitor.sol_up_token_id.lock().await, Some("up-tok".to_string()));
+        assert_eq!(*monitor.sol_down_token_id.lock().await, Some("down-tok".to_string()));
+    }
+
+    #[tokio::test]
+    async fn force_refresh_if_leg_unhealthy_is_a_noop_when_unconfigured() {
+        let monitor = dummy_monitor_with_max_consecutive_failures(None);
+        *monitor.last_market_refresh.lock().await = Some(unix_now_secs());
+
+        for _ in 0..10 {
+            monitor
+                .force_refresh_if_leg_unhealthy(&monitor.sol_up_consecutive_failures, false, "SOL", "Up")
+                .await;
+        }
+
+        assert!(monitor.last_market_refresh.lock().await.is_some());
+    }
+
+    #[tokio::test]
+    async fn consecutive_failures_below_the_threshold_do_not_force_a_refresh() {
+        let monitor = dummy_monitor_with_max_consecutive_failures(Some(3));
+        *monitor.last_market_refresh.lock().await = Some(unix_now_secs());
+
+        monitor
+            .force_refresh_if_leg_unhealthy(&monitor.sol_up_consecutive_failures, false, "SOL", "Up")
+            .await;
+        monitor
+            .force_refresh_if_leg_unhealthy(&monitor.sol_up_consecutive_failures, false, "SOL", "Up")
+            .await;
+
+        assert!(monitor.last_market_refresh.lock().await.is_some());
+    }
+
+    #[tokio::test]
+    async fn reaching_the_failure_threshold_forces_an_early_refresh() {
+        let monitor = dummy_monitor_with_max_consecutive_failures(Some(3));
+        *monitor.last_market_refresh.lock().await = Some(unix_now_secs());
+
+        for _ in 0..3 {
+            monitor
+                .force_refresh_if_leg_unhealthy(&monitor.btc_down_consecutive_failures, false, "BTC", "Down")
+                .await;
+        }
+
+        assert_eq!(*monitor.last_market_refresh.lock().await, None);
+        assert_eq!(*monitor.btc_down_consecutive_failures.lock().await, 0);
+    }
+
+    #[tokio::test]
+    async fn a_success_resets_the_consecutive_failure_counter() {
+        let monitor = dummy_monitor_with_max_consecutive_failures(Some(3));
+        *monitor.last_market_refresh.lock().await = Some(unix_now_secs());
+
+        monitor
+            .force_refresh_if_leg_unhealthy(&monitor.sol_down_consecutive_failures, false, "SOL", "Down")
+            .await;
+        monitor
+            .force_refresh_if_leg_unhealthy(&monitor.sol_down_consecutive_failures, false, "SOL", "Down")
+            .await;
+        monitor
+            .force_refresh_if_leg_unhealthy(&monitor.sol_down_consecutive_failures, true, "SOL", "Down")
+            .await;
+        monitor
+            .force_refresh_if_leg_unhealthy(&monitor.sol_down_consecutive_failures, false, "SOL", "Down")
+            .await;
+
+        assert!(monitor.last_market_refresh.lock().await.is_some());
+        assert_eq!(*monitor.sol_down_consecutive_failures.lock().await, 1);
+    }
+
+    #[tokio::test]
+    async fn seconds_until_accepting_orders_is_none_before_any_refresh() {
+        let monitor = dummy_monitor();
+        assert_eq!(monitor.seconds_until_accepting_orders().await, None);
+    }
+
+    #[tokio::test]
+    async fn seconds_until_accepting_orders_reflects_a_future_market_open() {
+        let mut sol_details = dummy_market_details(true, up_down_tokens());
+        sol_details.accepting_order_timestamp = Some("2999-01-01T00:00:00Z".to_string());
+        let source = Arc::new(ScriptedPriceSource { market: Some(sol_details), ..Default::default() });
+        let monitor = dummy_monitor_with_source(source);
+
+        monitor.refresh_market_tokens().await.unwrap();
+
+        let wait = monitor.seconds_until_accepting_orders().await;
+        assert!(wait.is_some_and(|secs| secs > 0));
+    }
+
+    #[tokio::test]
+    async fn seconds_until_accepting_orders_is_none_once_the_timestamp_has_passed() {
+        let mut sol_details = dummy_market_details(true, up_down_tokens());
+        sol_details.accepting_order_timestamp = Some("2000-01-01T00:00:00Z".to_string());
+        let source = Arc::new(ScriptedPriceSource { market: Some(sol_details), ..Default::default() });
+        let monitor = dummy_monitor_with_source(source);
+
+        monitor.refresh_market_tokens().await.unwrap();
+
+        assert_eq!(monitor.seconds_until_accepting_orders().await, None);
+    }
+
+    #[tokio::test]
+    async fn seconds_until_accepting_orders_is_reset_by_update_sol_market() {
+        let monitor = dummy_monitor();
+        *monitor.sol_accepting_order_timestamp.lock().await = Some(unix_now_secs() + 3600);
+
+        monitor.update_sol_market(dummy_market("sol-cond-2", "sol-updown-15m-900")).await.unwrap();
+
+        assert!(monitor.sol_accepting_order_timestamp.lock().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn fetch_token_price_uses_the_orderbook_top_when_configured() {
+        let source = Arc::new(ScriptedPriceSource {
+            // BUY/SELL and midpoint are also scripted, but orderbook-top
+            // preference should ignore them entirely and use `best_price`.
+            buy: Some(dec!(0.9)),
+            sell: Some(dec!(0.1)),
+            midpoint: Some(dec!(0.5)),
+            best_price: Some(TokenPrice {
+                token_id: "tok".to_string(),
+                bid: Some(dec!(0.42)),
+                ask: Some(dec!(0.44)),
+                smoothed_bid: None,
+                smoothed_ask: None,
+                is_midpoint_derived: false,
+                last: None,
+            }),
+            ..Default::default()
+        });
+        let monitor = dummy_monitor_with_source_and_price_source_preference(source, PriceSourcePreference::OrderbookTop);
+
+        let price = monitor
+            .fetch_token_price(&Some("tok".to_string()), "SOL", "Up")
+            .await
+            .expect("scripted orderbook top should produce a price");
+
+        assert_eq!(price.bid, Some(dec!(0.42)));
+        assert_eq!(price.ask, Some(dec!(0.44)));
+        assert!(!price.is_midpoint_derived);
+    }
+
+    #[tokio::test]
+    async fn fetch_token_price_uses_the_midpoint_when_configured() {
+        let source = Arc::new(ScriptedPriceSource {
+            buy: Some(dec!(0.9)),
+            sell: Some(dec!(0.1)),
+            midpoint: Some(dec!(0.5)),
+            ..Default::default()
+        });
+        let monitor = dummy_monitor_with_source_and_price_source_preference(source, PriceSourcePreference::Midpoint);
+
+        let price = monitor
+            .fetch_token_price(&Some("tok".to_string()), "SOL", "Up")
+            .await
+            .expect("scripted midpoint should produce a price");
+
+        assert_eq!(price.bid, None);
+        assert_eq!(price.ask, Some(dec!(0.5)));
+        assert!(price.is_midpoint_derived);
+    }
+}
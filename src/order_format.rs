@@ -0,0 +1,142 @@
+//! Centralized, side-aware conversion from `Decimal` order prices/sizes to
+//! the CLOB's string wire format. Formatting a price or size independent of
+//! which side of the book it's for (as plain `Decimal::to_string()` or
+//! `{:.6}` does) risks rounding in whichever direction happens to fall out
+//! of float/decimal formatting - which can quietly manufacture edge that
+//! doesn't actually exist once the order hits the book. `round_price`/
+//! `round_size` make that direction an explicit, tested choice instead.
+
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+/// Which side of the book an order rests on. Rounding a BUY price up (or a
+/// SELL price down) pays/receives worse than the number an opportunity was
+/// evaluated at; `round_price` uses this to pick the direction that's worse
+/// for us when a value doesn't already land on a tick boundary.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrderSide {
+    Buy,
+    Sell,
+}
+
+/// How `round_price`/`round_size` resolve a value that doesn't land exactly
+/// on a tick/lot boundary.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum RoundingMode {
+    /// Snap to the tick/lot that's worse for us whenever the exact value
+    /// isn't already on one: a BUY price rounds up, a SELL price rounds
+    /// down, and a size always rounds down. Never lets rounding
+    /// manufacture edge that wouldn't survive against the real order book.
+    #[default]
+    Conservative,
+    /// Standard round-half-up to the nearest tick/lot, regardless of side.
+    /// Mainly useful for comparing against what naive formatting would have
+    /// done.
+    Nearest,
+}
+
+/// Snaps `price` to the nearest multiple of `tick_size`, per `mode`. A
+/// non-positive `tick_size` disables quantization and returns `price`
+/// unchanged, since there's no tick to snap to.
+pub fn round_price(price: Decimal, tick_size: Decimal, side: OrderSide, mode: RoundingMode) -> Decimal {
+    if tick_size <= Decimal::ZERO {
+        return price;
+    }
+    let ticks = price / tick_size;
+    let rounded_ticks = match mode {
+        RoundingMode::Nearest => ticks.round(),
+        RoundingMode::Conservative => match side {
+            OrderSide::Buy => ticks.ceil(),
+            OrderSide::Sell => ticks.floor(),
+        },
+    };
+    rounded_ticks * tick_size
+}
+
+/// Snaps `size` to the nearest multiple of `lot_size`, per `mode`.
+/// `Conservative` always floors regardless of side - a size that rounds up
+/// could request more units than the trade was actually sized for. A
+/// non-positive `lot_size` disables quantization and returns `size`
+/// unchanged.
+pub fn round_size(size: Decimal, lot_size: Decimal, mode: RoundingMode) -> Decimal {
+    if lot_size <= Decimal::ZERO {
+        return size;
+    }
+    let lots = size / lot_size;
+    let rounded_lots = match mode {
+        RoundingMode::Nearest => lots.round(),
+        RoundingMode::Conservative => lots.floor(),
+    };
+    rounded_lots * lot_size
+}
+
+/// `round_price` followed by the string format the CLOB expects.
+pub fn format_price(price: Decimal, tick_size: Decimal, side: OrderSide, mode: RoundingMode) -> String {
+    round_price(price, tick_size, side, mode).to_string()
+}
+
+/// `round_size` followed by the string format the CLOB expects.
+pub fn format_size(size: Decimal, lot_size: Decimal, mode: RoundingMode) -> String {
+    round_size(size, lot_size, mode).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn round_price_leaves_a_price_already_on_tick_unchanged() {
+        assert_eq!(round_price(dec!(0.45), dec!(0.01), OrderSide::Buy, RoundingMode::Conservative), dec!(0.45));
+        assert_eq!(round_price(dec!(0.45), dec!(0.01), OrderSide::Sell, RoundingMode::Conservative), dec!(0.45));
+    }
+
+    #[test]
+    fn round_price_conservative_rounds_a_buy_up_to_the_worse_tick() {
+        assert_eq!(round_price(dec!(0.451), dec!(0.01), OrderSide::Buy, RoundingMode::Conservative), dec!(0.46));
+    }
+
+    #[test]
+    fn round_price_conservative_rounds_a_sell_down_to_the_worse_tick() {
+        assert_eq!(round_price(dec!(0.459), dec!(0.01), OrderSide::Sell, RoundingMode::Conservative), dec!(0.45));
+    }
+
+    #[test]
+    fn round_price_nearest_ignores_side() {
+        assert_eq!(round_price(dec!(0.454), dec!(0.01), OrderSide::Buy, RoundingMode::Nearest), dec!(0.45));
+        assert_eq!(round_price(dec!(0.454), dec!(0.01), OrderSide::Sell, RoundingMode::Nearest), dec!(0.45));
+        assert_eq!(round_price(dec!(0.456), dec!(0.01), OrderSide::Buy, RoundingMode::Nearest), dec!(0.46));
+    }
+
+    #[test]
+    fn round_price_treats_a_non_positive_tick_size_as_disabled() {
+        assert_eq!(round_price(dec!(0.4567), dec!(0.0), OrderSide::Buy, RoundingMode::Conservative), dec!(0.4567));
+    }
+
+    #[test]
+    fn round_size_conservative_always_rounds_down_regardless_of_side() {
+        assert_eq!(round_size(dec!(133.3337), dec!(0.000001), RoundingMode::Conservative), dec!(133.333700));
+        assert_eq!(round_size(dec!(133.3339999), dec!(0.000001), RoundingMode::Conservative), dec!(133.333999));
+    }
+
+    #[test]
+    fn round_size_nearest_rounds_to_the_closer_lot() {
+        assert_eq!(round_size(dec!(133.5), dec!(1), RoundingMode::Nearest), dec!(134));
+        assert_eq!(round_size(dec!(133.4), dec!(1), RoundingMode::Nearest), dec!(133));
+    }
+
+    #[test]
+    fn round_size_treats_a_non_positive_lot_size_as_disabled() {
+        assert_eq!(round_size(dec!(133.3337), dec!(0.0), RoundingMode::Conservative), dec!(133.3337));
+    }
+
+    #[test]
+    fn format_price_produces_the_expected_wire_string() {
+        assert_eq!(format_price(dec!(0.451), dec!(0.01), OrderSide::Buy, RoundingMode::Conservative), "0.46");
+    }
+
+    #[test]
+    fn format_size_produces_the_expected_wire_string() {
+        assert_eq!(format_size(dec!(133.3339999), dec!(0.000001), RoundingMode::Conservative), "133.333999");
+    }
+}
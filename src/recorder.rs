@@ -0,0 +1,106 @@
+use crate::models::{MarketData, TokenPrice};
+use crate::monitor::MarketSnapshot;
+use anyhow::{Context, Result};
+use rust_decimal::Decimal;
+use serde::Serialize;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use tokio::sync::Mutex;
+
+/// A serializable view of a `TokenPrice` for the JSONL backtest corpus.
+/// `MarketSnapshot` itself doesn't derive Serde and keeps only a whole-second
+/// timestamp, so recording uses its own mirror types with a millisecond
+/// wall-clock timestamp taken at record time.
+#[derive(Serialize)]
+struct RecordedTokenPrice {
+    token_id: String,
+    bid: Option<Decimal>,
+    ask: Option<Decimal>,
+}
+
+impl From<&TokenPrice> for RecordedTokenPrice {
+    fn from(price: &TokenPrice) -> Self {
+        Self {
+            token_id: price.token_id.clone(),
+            bid: price.bid,
+            ask: price.ask,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct RecordedMarketData {
+    condition_id: String,
+    market_name: String,
+    up_token: Option<RecordedTokenPrice>,
+    down_token: Option<RecordedTokenPrice>,
+}
+
+impl From<&MarketData> for RecordedMarketData {
+    fn from(data: &MarketData) -> Self {
+        Self {
+            condition_id: data.condition_id.clone(),
+            market_name: data.market_name.clone(),
+            up_token: data.up_token.as_ref().map(RecordedTokenPrice::from),
+            down_token: data.down_token.as_ref().map(RecordedTokenPrice::from),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct RecordedSnapshot {
+    timestamp_ms: u128,
+    sol_market: RecordedMarketData,
+    btc_market: RecordedMarketData,
+}
+
+/// Appends `MarketSnapshot`s to a JSONL file for later backtesting.
+/// Writes are buffered in memory and only flushed periodically (via
+/// `flush`) so recording doesn't slow down the monitoring loop.
+pub struct SnapshotRecorder {
+    writer: Mutex<BufWriter<std::fs::File>>,
+}
+
+impl SnapshotRecorder {
+    pub fn new(path: &Path) -> Result<Self> {
+        let file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("Failed to open snapshot record file: {}", path.display()))?;
+
+        Ok(Self {
+            writer: Mutex::new(BufWriter::new(file)),
+        })
+    }
+
+    pub async fn record(&self, snapshot: &MarketSnapshot) -> Result<()> {
+        let timestamp_ms = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_millis();
+
+        let record = RecordedSnapshot {
+            timestamp_ms,
+            sol_market: RecordedMarketData::from(&snapshot.sol_market),
+            btc_market: RecordedMarketData::from(&snapshot.btc_market),
+        };
+
+        let line = serde_json::to_string(&record).context("Failed to serialize snapshot")?;
+
+        let mut writer = self.writer.lock().await;
+        writer.write_all(line.as_bytes())?;
+        writer.write_all(b"\n")?;
+
+        Ok(())
+    }
+
+    /// Flushes buffered writes to the OS and `fsync`s the underlying file,
+    /// so a periodic call guarantees recorded snapshots survive a crash
+    /// between flushes, not just a clean process exit.
+    pub async fn flush(&self) -> Result<()> {
+        let mut writer = self.writer.lock().await;
+        writer.flush().context("Failed to flush snapshot recorder")?;
+        writer.get_ref().sync_all().context("Failed to fsync snapshot record file")
+    }
+}
@@ -0,0 +1,188 @@
+use crate::api::PolymarketApi;
+use crate::trade_log::TradeLogEntry;
+use crate::trader::{invert_leg_result, leg_result_for_token, settlement_profit};
+use anyhow::{Context, Result};
+use log::{info, warn};
+use std::io::BufRead;
+use std::path::Path;
+
+/// Parses trade-log JSONL lines into entries, tolerating a corrupt or
+/// truncated last line - the shape a crash mid-append leaves behind, since
+/// the log is append-only - by skipping it with a warning instead of
+/// failing the whole load. A corrupt line earlier in the file indicates
+/// real corruption rather than an interrupted write, so that still errors.
+fn parse_trade_log_lines(lines: &[String]) -> Result<Vec<TradeLogEntry>> {
+    let last_index = lines.len().saturating_sub(1);
+    let mut entries = Vec::new();
+
+    for (index, line) in lines.iter().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        match serde_json::from_str::<TradeLogEntry>(line) {
+            Ok(entry) => entries.push(entry),
+            Err(e) if index == last_index => {
+                warn!("Ignoring unparseable last line in trade history (likely a crash mid-write): {}", e);
+            }
+            Err(e) => {
+                return Err(e).with_context(|| format!("Failed to parse trade history line: {}", line));
+            }
+        }
+    }
+
+    Ok(entries)
+}
+
+/// Re-settle a trade-history file (written via `--trade-log`) against each
+/// trade's actual on-chain resolution, diffing the recomputed profit
+/// against what was recorded at settlement time. Read-only: only ever calls
+/// `get_market`, never places orders or mutates any local state. Exits
+/// after reporting, regardless of whether mismatches were found.
+pub async fn replay_trade_history(api: &PolymarketApi, path: &Path) -> Result<()> {
+    let file = std::fs::File::open(path)
+        .with_context(|| format!("Failed to open trade history file: {}", path.display()))?;
+    let reader = std::io::BufReader::new(file);
+
+    let lines: Vec<String> = reader.lines().collect::<std::io::Result<_>>()?;
+    let entries = parse_trade_log_lines(&lines)?;
+
+    let mut checked = 0usize;
+    let mut mismatches = 0usize;
+
+    for entry in &entries {
+        let sol_market = api.get_market(&entry.sol_condition_id).await.with_context(|| {
+            format!("Failed to fetch SOL market {} for trade {}", entry.sol_condition_id, entry.trade_id)
+        })?;
+        let btc_market = api.get_market(&entry.btc_condition_id).await.with_context(|| {
+            format!("Failed to fetch BTC market {} for trade {}", entry.btc_condition_id, entry.trade_id)
+        })?;
+
+        let true_sol_result = leg_result_for_token(&sol_market, &entry.sol_token_id);
+        let true_btc_result = leg_result_for_token(&btc_market, &entry.btc_token_id);
+
+        let (mut true_profit, _) = settlement_profit(
+            entry.investment_amount,
+            entry.units,
+            entry.redemption_cost_estimate,
+            f64::try_from(entry.entry_sol_price).unwrap_or(0.0),
+            f64::try_from(entry.entry_btc_price).unwrap_or(0.0),
+            true_sol_result,
+            true_btc_result,
+            entry.sol_sold,
+            entry.btc_sold,
+        );
+
+        if let Some(hedge) = &entry.hedge {
+            let (hedge_profit, _) = settlement_profit(
+                hedge.investment_amount,
+                hedge.units,
+                0.0,
+                f64::try_from(hedge.sol_price).unwrap_or(0.0),
+                f64::try_from(hedge.btc_price).unwrap_or(0.0),
+                invert_leg_result(true_sol_result),
+                invert_leg_result(true_btc_result),
+                hedge.sol_sold,
+                hedge.btc_sold,
+            );
+            true_profit += hedge_profit;
+        }
+
+        checked += 1;
+        let diff = (true_profit - entry.recorded_profit).abs();
+        if diff > 0.0001 {
+            mismatches += 1;
+            warn!(
+                "❌ MISMATCH trade_id={} strategy={} recorded=${:.4} recomputed=${:.4} diff=${:.4} \
+                 (sol_result recorded={:?} actual={:?}, btc_result recorded={:?} actual={:?})",
+                entry.trade_id,
+                entry.strategy,
+                entry.recorded_profit,
+                true_profit,
+                diff,
+                entry.sol_result,
+                true_sol_result,
+                entry.btc_result,
+                true_btc_result,
+            );
+        } else {
+            info!(
+                "✅ trade_id={} matches: recorded=${:.4} recomputed=${:.4}",
+                entry.trade_id, entry.recorded_profit, true_profit
+            );
+        }
+    }
+
+    info!("🔍 Replay complete: {} trade(s) checked, {} mismatch(es)", checked, mismatches);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_entry_json(trade_id: &str) -> String {
+        serde_json::json!({
+            "trade_id": trade_id,
+            "strategy": "sol_up_btc_down",
+            "sol_condition_id": "sol-cond",
+            "btc_condition_id": "btc-cond",
+            "sol_token_id": "sol-tok",
+            "btc_token_id": "btc-tok",
+            "investment_amount": 100.0,
+            "units": 100.0,
+            "entry_sol_price": "0.5",
+            "entry_btc_price": "0.4",
+            "redemption_cost_estimate": 0.0,
+            "sol_result": "won",
+            "btc_result": "lost",
+            "sol_sold": true,
+            "btc_sold": true,
+            "recorded_profit": 5.0,
+        })
+        .to_string()
+    }
+
+    #[test]
+    fn parse_trade_log_lines_skips_a_truncated_last_line() {
+        let lines = vec![
+            sample_entry_json("trade-1"),
+            "{\"trade_id\": \"trade-2\", \"strategy\": \"sol_up".to_string(),
+        ];
+
+        let entries = parse_trade_log_lines(&lines).unwrap();
+
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].trade_id, "trade-1");
+    }
+
+    #[test]
+    fn parse_trade_log_lines_errors_on_a_corrupt_middle_line() {
+        let lines = vec![
+            "{\"trade_id\": \"trade-1\", \"strategy\": \"sol_up".to_string(),
+            sample_entry_json("trade-2"),
+        ];
+
+        assert!(parse_trade_log_lines(&lines).is_err());
+    }
+
+    #[test]
+    fn parse_trade_log_lines_skips_blank_lines() {
+        let lines = vec![sample_entry_json("trade-1"), String::new(), "   ".to_string()];
+
+        let entries = parse_trade_log_lines(&lines).unwrap();
+
+        assert_eq!(entries.len(), 1);
+    }
+
+    #[test]
+    fn parse_trade_log_lines_returns_all_entries_when_every_line_is_valid() {
+        let lines = vec![sample_entry_json("trade-1"), sample_entry_json("trade-2")];
+
+        let entries = parse_trade_log_lines(&lines).unwrap();
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[1].trade_id, "trade-2");
+    }
+}
@@ -0,0 +1,259 @@
+//! Optional coordination layer for running multiple `Trader` instances (e.g.
+//! one per asset pair) against a shared bankroll. Standalone (no backend
+//! configured) is the default and preserves the original single-instance
+//! behavior; when a backend is configured, `Trader` reserves shared capital
+//! and dedupes trade keys through it before entering a trade.
+//!
+//! `FileSharedState` is the first backend: a JSON file guarded by a
+//! plain-old-file lock. The `SharedStateBackend` trait leaves room for a
+//! Redis-backed implementation later without touching `Trader`.
+
+use crate::error::SharedStateError;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+type Result<T> = std::result::Result<T, SharedStateError>;
+
+/// Coordinates a global deployed-capital limit and trade-key dedup across
+/// multiple `Trader` instances sharing a bankroll.
+#[async_trait::async_trait]
+pub trait SharedStateBackend: Send + Sync {
+    /// Attempts to claim `trade_key` and reserve `amount` of shared capital.
+    /// Returns `Ok(false)` if the key is already claimed by another instance
+    /// or the reservation would push total shared deployment past
+    /// `max_shared_deployed`, without granting anything.
+    async fn try_reserve(&self, trade_key: &str, amount: f64, max_shared_deployed: f64) -> Result<bool>;
+
+    /// Releases a previously claimed trade key so the same market can be
+    /// traded again. The reserved capital itself is never released - it
+    /// mirrors `Trader::total_deployed`, which tracks lifetime deployment,
+    /// not current exposure.
+    async fn release_claim(&self, trade_key: &str) -> Result<()>;
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+struct SharedStateData {
+    total_deployed: f64,
+    claimed_trade_keys: HashSet<String>,
+}
+
+/// A `SharedStateBackend` backed by a single JSON file, guarded by a
+/// sibling `.lock` file used as a mutual-exclusion primitive (atomic
+/// exclusive create, deleted on release). Good enough for instances that
+/// share a filesystem; a Redis-backed `SharedStateBackend` would drop in
+/// for instances that don't.
+#[derive(Clone)]
+pub struct FileSharedState {
+    path: PathBuf,
+    lock_timeout: Duration,
+}
+
+impl FileSharedState {
+    pub fn new(path: PathBuf) -> Self {
+        Self::with_lock_timeout(path, Duration::from_secs(5))
+    }
+
+    pub fn with_lock_timeout(path: PathBuf, lock_timeout: Duration) -> Self {
+        Self { path, lock_timeout }
+    }
+
+    fn lock_path(&self) -> PathBuf {
+        PathBuf::from(format!("{}.lock", self.path.display()))
+    }
+
+    fn acquire_lock(&self) -> Result<FileLockGuard> {
+        let lock_path = self.lock_path();
+        let deadline = Instant::now() + self.lock_timeout;
+        loop {
+            match std::fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&lock_path)
+            {
+                Ok(_) => return Ok(FileLockGuard { path: lock_path }),
+                Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                    if Instant::now() >= deadline {
+                        return Err(SharedStateError::LockTimeout {
+                            timeout_ms: self.lock_timeout.as_millis() as u64,
+                        });
+                    }
+                    std::thread::sleep(Duration::from_millis(20));
+                }
+                Err(e) => return Err(SharedStateError::Io(e.to_string())),
+            }
+        }
+    }
+
+    fn load(&self) -> Result<SharedStateData> {
+        match std::fs::read_to_string(&self.path) {
+            Ok(contents) => {
+                serde_json::from_str(&contents).map_err(|e| SharedStateError::Parse(e.to_string()))
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(SharedStateData::default()),
+            Err(e) => Err(SharedStateError::Io(e.to_string())),
+        }
+    }
+
+    fn write(&self, data: &SharedStateData) -> Result<()> {
+        let json =
+            serde_json::to_string_pretty(data).map_err(|e| SharedStateError::Parse(e.to_string()))?;
+        let tmp_path = PathBuf::from(format!("{}.tmp.{}", self.path.display(), std::process::id()));
+        std::fs::write(&tmp_path, json).map_err(|e| SharedStateError::Io(e.to_string()))?;
+        std::fs::rename(&tmp_path, &self.path).map_err(|e| SharedStateError::Io(e.to_string()))
+    }
+
+    fn try_reserve_blocking(&self, trade_key: &str, amount: f64, max_shared_deployed: f64) -> Result<bool> {
+        let _lock = self.acquire_lock()?;
+        let mut data = self.load()?;
+        if data.claimed_trade_keys.contains(trade_key) || data.total_deployed + amount > max_shared_deployed {
+            return Ok(false);
+        }
+        data.total_deployed += amount;
+        data.claimed_trade_keys.insert(trade_key.to_string());
+        self.write(&data)?;
+        Ok(true)
+    }
+
+    fn release_claim_blocking(&self, trade_key: &str) -> Result<()> {
+        let _lock = self.acquire_lock()?;
+        let mut data = self.load()?;
+        data.claimed_trade_keys.remove(trade_key);
+        self.write(&data)
+    }
+}
+
+/// Releases the on-disk lock file on drop so a panic or early return can't
+/// leave `FileSharedState` permanently locked out.
+struct FileLockGuard {
+    path: PathBuf,
+}
+
+impl Drop for FileLockGuard {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+#[async_trait::async_trait]
+impl SharedStateBackend for FileSharedState {
+    async fn try_reserve(&self, trade_key: &str, amount: f64, max_shared_deployed: f64) -> Result<bool> {
+        let this = self.clone();
+        let trade_key = trade_key.to_string();
+        tokio::task::spawn_blocking(move || this.try_reserve_blocking(&trade_key, amount, max_shared_deployed))
+            .await
+            .map_err(|e| SharedStateError::Io(format!("shared-state task panicked: {}", e)))?
+    }
+
+    async fn release_claim(&self, trade_key: &str) -> Result<()> {
+        let this = self.clone();
+        let trade_key = trade_key.to_string();
+        tokio::task::spawn_blocking(move || this.release_claim_blocking(&trade_key))
+            .await
+            .map_err(|e| SharedStateError::Io(format!("shared-state task panicked: {}", e)))?
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "shared_state_test_{}_{}_{}.json",
+            std::process::id(),
+            name,
+            std::thread::current().name().unwrap_or("t").replace("::", "_")
+        ))
+    }
+
+    fn cleanup(path: &PathBuf) {
+        let _ = std::fs::remove_file(path);
+        let _ = std::fs::remove_file(format!("{}.lock", path.display()));
+    }
+
+    #[tokio::test]
+    async fn try_reserve_grants_the_first_reservation_within_budget() {
+        let path = temp_path("grants_first");
+        cleanup(&path);
+        let backend = FileSharedState::new(path.clone());
+
+        let granted = backend.try_reserve("market-a", 100.0, 500.0).await.unwrap();
+
+        assert!(granted);
+        cleanup(&path);
+    }
+
+    #[tokio::test]
+    async fn try_reserve_denies_a_reservation_that_would_exceed_the_cap() {
+        let path = temp_path("denies_over_cap");
+        cleanup(&path);
+        let backend = FileSharedState::new(path.clone());
+
+        assert!(backend.try_reserve("market-a", 400.0, 500.0).await.unwrap());
+        let granted = backend.try_reserve("market-b", 200.0, 500.0).await.unwrap();
+
+        assert!(!granted);
+        cleanup(&path);
+    }
+
+    #[tokio::test]
+    async fn try_reserve_denies_a_trade_key_already_claimed_by_another_instance() {
+        let path = temp_path("denies_duplicate_key");
+        cleanup(&path);
+        let backend = FileSharedState::new(path.clone());
+
+        assert!(backend.try_reserve("market-a", 50.0, 500.0).await.unwrap());
+        let granted_again = backend.try_reserve("market-a", 50.0, 500.0).await.unwrap();
+
+        assert!(!granted_again);
+        cleanup(&path);
+    }
+
+    #[tokio::test]
+    async fn release_claim_lets_the_same_trade_key_be_reserved_again() {
+        let path = temp_path("release_reopens");
+        cleanup(&path);
+        let backend = FileSharedState::new(path.clone());
+
+        assert!(backend.try_reserve("market-a", 50.0, 500.0).await.unwrap());
+        backend.release_claim("market-a").await.unwrap();
+        let granted_again = backend.try_reserve("market-a", 50.0, 500.0).await.unwrap();
+
+        assert!(granted_again);
+        cleanup(&path);
+    }
+
+    #[tokio::test]
+    async fn try_reserve_persists_the_shared_total_across_instances() {
+        let path = temp_path("persists_total");
+        cleanup(&path);
+        let first = FileSharedState::new(path.clone());
+        let second = FileSharedState::new(path.clone());
+
+        assert!(first.try_reserve("market-a", 300.0, 500.0).await.unwrap());
+        let granted = second.try_reserve("market-b", 300.0, 500.0).await.unwrap();
+
+        assert!(!granted);
+        cleanup(&path);
+    }
+
+    #[test]
+    fn acquire_lock_times_out_when_already_held() {
+        let path = temp_path("lock_timeout");
+        cleanup(&path);
+        let backend = FileSharedState::with_lock_timeout(path.clone(), Duration::from_millis(50));
+        let held_lock = std::fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(backend.lock_path())
+            .unwrap();
+
+        let result = backend.acquire_lock();
+
+        assert!(matches!(result, Err(SharedStateError::LockTimeout { .. })));
+        drop(held_lock);
+        cleanup(&path);
+    }
+}
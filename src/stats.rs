@@ -0,0 +1,125 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tokio::sync::Mutex;
+
+/// Lifetime trading totals, persisted to disk on every settlement so an
+/// operator gets an at-a-glance view of cumulative performance across
+/// restarts without needing to replay the full trade log.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct LifetimeStats {
+    pub profit: f64,
+    pub trades: u64,
+    pub wins: u64,
+    pub losses: u64,
+    pub deployed: f64,
+    pub updated_at_secs: u64,
+}
+
+/// Rewrites `LifetimeStats` to a JSON file on each settlement. Each write
+/// goes to a sibling `.tmp` file which is then renamed into place, so a
+/// crash mid-write can never leave a half-written, unparseable stats file
+/// behind. The internal lock serializes concurrent settlements so two
+/// writers can't interleave and corrupt the file.
+pub struct StatsFile {
+    path: PathBuf,
+    write_lock: Mutex<()>,
+}
+
+impl StatsFile {
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            write_lock: Mutex::new(()),
+        }
+    }
+
+    /// Loads the persisted stats, or `LifetimeStats::default()` if the file
+    /// doesn't exist yet (first run).
+    pub fn load(path: &Path) -> Result<LifetimeStats> {
+        match std::fs::read_to_string(path) {
+            Ok(contents) => serde_json::from_str(&contents)
+                .with_context(|| format!("Failed to parse stats file: {}", path.display())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(LifetimeStats::default()),
+            Err(e) => {
+                Err(e).with_context(|| format!("Failed to read stats file: {}", path.display()))
+            }
+        }
+    }
+
+    /// Atomically rewrites the stats file with `stats`.
+    pub async fn write(&self, stats: &LifetimeStats) -> Result<()> {
+        let _guard = self.write_lock.lock().await;
+
+        let json =
+            serde_json::to_string_pretty(stats).context("Failed to serialize lifetime stats")?;
+        let tmp_path = PathBuf::from(format!("{}.tmp", self.path.display()));
+        std::fs::write(&tmp_path, json)
+            .with_context(|| format!("Failed to write stats temp file: {}", tmp_path.display()))?;
+        std::fs::rename(&tmp_path, &self.path).with_context(|| {
+            format!(
+                "Failed to rename stats temp file into place: {}",
+                self.path.display()
+            )
+        })?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn load_returns_default_when_the_file_does_not_exist() {
+        let stats = StatsFile::load(Path::new("/nonexistent/does-not-exist.json")).unwrap();
+        assert_eq!(stats.trades, 0);
+        assert_eq!(stats.profit, 0.0);
+    }
+
+    #[tokio::test]
+    async fn write_then_load_round_trips_the_stats() {
+        let path = std::env::temp_dir().join(format!(
+            "stats_round_trip_test_{}.json",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+
+        let file = StatsFile::new(path.clone());
+        let stats = LifetimeStats {
+            profit: 12.5,
+            trades: 4,
+            wins: 3,
+            losses: 1,
+            deployed: 100.0,
+            updated_at_secs: 42,
+        };
+        file.write(&stats).await.unwrap();
+
+        let loaded = StatsFile::load(&path).unwrap();
+        assert_eq!(loaded.trades, 4);
+        assert_eq!(loaded.wins, 3);
+        assert_eq!(loaded.losses, 1);
+        assert_eq!(loaded.deployed, 100.0);
+        assert_eq!(loaded.updated_at_secs, 42);
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[tokio::test]
+    async fn write_leaves_no_tmp_file_behind_after_a_successful_rename() {
+        let path = std::env::temp_dir().join(format!("stats_tmp_test_{}.json", std::process::id()));
+        let tmp_path = PathBuf::from(format!("{}.tmp", path.display()));
+        let _ = std::fs::remove_file(&path);
+        let _ = std::fs::remove_file(&tmp_path);
+
+        let file = StatsFile::new(path.clone());
+        file.write(&LifetimeStats::default()).await.unwrap();
+
+        assert!(path.exists());
+        assert!(!tmp_path.exists());
+
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,155 @@
+//! Optional OpenTelemetry trace export covering one trade's lifecycle from
+//! detection/execution (`Trader::execute_arbitrage`) through settlement
+//! (`Trader::settle_one_trade`). Everything here is a no-op unless this
+//! binary is built with the `otel` feature *and* `config.tracing.enabled`
+//! is `true`, so call sites in `trader.rs` never need `#[cfg]` of their own.
+//!
+//! `TradeSpan` correlates the two stages by the same composite key
+//! (`sol_condition_id`_`btc_condition_id`_`strategy`) already used as the
+//! `pending_trades` map key, so a span opened in `execute_arbitrage` and one
+//! opened later in `settle_one_trade` share a `trade_key` attribute a
+//! collector can join on across the intervening async boundary.
+
+use crate::config::TracingConfig;
+
+#[cfg(feature = "otel")]
+mod imp {
+    use super::TracingConfig;
+    use opentelemetry::trace::TracerProvider as _;
+    use opentelemetry_otlp::WithExportConfig;
+    use opentelemetry_sdk::trace::SdkTracerProvider;
+    use std::future::Future;
+    use std::sync::OnceLock;
+    use tracing::Instrument;
+    use tracing_subscriber::layer::SubscriberExt;
+    use tracing_subscriber::util::SubscriberInitExt;
+
+    static PROVIDER: OnceLock<SdkTracerProvider> = OnceLock::new();
+
+    /// Builds the OTLP/gRPC exporter and installs a `tracing` subscriber
+    /// that bridges spans into it. A no-op if `config.enabled` is `false`.
+    pub fn init(config: &TracingConfig) -> anyhow::Result<()> {
+        if !config.enabled {
+            return Ok(());
+        }
+        let endpoint = config
+            .otlp_endpoint
+            .as_deref()
+            .ok_or_else(|| anyhow::anyhow!("tracing.otlp_endpoint must be set when tracing.enabled is true"))?;
+
+        let exporter = opentelemetry_otlp::SpanExporter::builder()
+            .with_tonic()
+            .with_endpoint(endpoint)
+            .build()?;
+
+        let resource = opentelemetry_sdk::Resource::builder().with_service_name(config.service_name.clone()).build();
+
+        let provider = SdkTracerProvider::builder().with_batch_exporter(exporter).with_resource(resource).build();
+
+        let tracer = provider.tracer("polymarket-arbitrage-bot");
+        let otel_layer = tracing_opentelemetry::layer().with_tracer(tracer);
+        tracing_subscriber::registry()
+            .with(otel_layer)
+            .try_init()
+            .map_err(|e| anyhow::anyhow!("failed to install tracing subscriber: {}", e))?;
+
+        // Only set if this is the first (and only) call - `init` is only
+        // ever invoked once at startup, but guards against a double-init in
+        // tests from silently leaking a second provider.
+        let _ = PROVIDER.set(provider);
+
+        log::info!("📡 OpenTelemetry trace export enabled, exporting spans to {}", endpoint);
+        Ok(())
+    }
+
+    /// Flushes and shuts down the exporter, giving buffered spans a chance
+    /// to reach the collector before the process exits. A no-op if `init`
+    /// was never called or tracing wasn't enabled.
+    pub fn shutdown() {
+        if let Some(provider) = PROVIDER.get() {
+            if let Err(e) = provider.shutdown() {
+                log::warn!("Failed to shut down OpenTelemetry tracer provider: {}", e);
+            }
+        }
+    }
+
+    /// One span covering a trade's execution or settlement stage. See the
+    /// module docs for how `trade_key` correlates the two.
+    pub struct TradeSpan(tracing::Span);
+
+    impl TradeSpan {
+        pub fn new(trade_key: &str, strategy: &str, sol_condition_id: &str, btc_condition_id: &str) -> Self {
+            Self(tracing::info_span!(
+                "trade",
+                trade_key = %trade_key,
+                strategy = %strategy,
+                sol_condition_id = %sol_condition_id,
+                btc_condition_id = %btc_condition_id,
+                total_cost = tracing::field::Empty,
+                expected_profit = tracing::field::Empty,
+                realized_profit = tracing::field::Empty,
+                fully_realized = tracing::field::Empty,
+            ))
+        }
+
+        /// Runs `fut` with this span entered, so any nested spans/events
+        /// (and any `record_execution`/`record_settlement` call made from
+        /// within `fut`) attach to it - including across the `.await`
+        /// points inside `fut`, which a synchronous `span.enter()` guard
+        /// can't safely span.
+        pub async fn instrument<F: Future>(&self, fut: F) -> F::Output {
+            fut.instrument(self.0.clone()).await
+        }
+    }
+
+    /// Records detection's output onto the current span. Called from
+    /// within a `TradeSpan::instrument`ed future so `tracing::Span::current()`
+    /// resolves to that span.
+    pub fn record_execution(total_cost: f64, expected_profit: f64) {
+        tracing::Span::current().record("total_cost", total_cost).record("expected_profit", expected_profit);
+    }
+
+    /// Records settlement's outcome onto the current span, same caveat as
+    /// `record_execution`.
+    pub fn record_settlement(realized_profit: f64, fully_realized: bool) {
+        tracing::Span::current()
+            .record("realized_profit", realized_profit)
+            .record("fully_realized", fully_realized);
+    }
+}
+
+#[cfg(not(feature = "otel"))]
+mod imp {
+    use super::TracingConfig;
+    use std::future::Future;
+
+    pub fn init(config: &TracingConfig) -> anyhow::Result<()> {
+        if config.enabled {
+            log::warn!(
+                "tracing.enabled is true but this binary wasn't built with the `otel` feature \
+                 (rebuild with `--features otel`); continuing without OpenTelemetry export"
+            );
+        }
+        Ok(())
+    }
+
+    pub fn shutdown() {}
+
+    pub struct TradeSpan;
+
+    impl TradeSpan {
+        pub fn new(_trade_key: &str, _strategy: &str, _sol_condition_id: &str, _btc_condition_id: &str) -> Self {
+            Self
+        }
+
+        pub async fn instrument<F: Future>(&self, fut: F) -> F::Output {
+            fut.await
+        }
+    }
+
+    pub fn record_execution(_total_cost: f64, _expected_profit: f64) {}
+
+    pub fn record_settlement(_realized_profit: f64, _fully_realized: bool) {}
+}
+
+pub use imp::*;
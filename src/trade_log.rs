@@ -0,0 +1,114 @@
+use crate::trader::LegResult;
+use anyhow::{Context, Result};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use tokio::sync::Mutex;
+
+/// A settled trade, persisted for later audit via the `replay` CLI mode.
+/// Carries everything `settlement_profit` needs to recompute the trade's
+/// profit independently of the live run, plus what was actually recorded at
+/// settlement time so the two can be diffed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TradeLogEntry {
+    pub trade_id: String,
+    pub strategy: String,
+    pub sol_condition_id: String,
+    pub btc_condition_id: String,
+    pub sol_token_id: String,
+    pub btc_token_id: String,
+    pub investment_amount: f64,
+    pub units: f64,
+    pub entry_sol_price: Decimal,
+    pub entry_btc_price: Decimal,
+    /// Realized average fill price for each leg, when known - `None` for
+    /// simulated trades or if the CLOB's order response didn't report one.
+    /// Diffed against `entry_sol_price`/`entry_btc_price` for post-hoc
+    /// slippage analysis. `#[serde(default)]` so older log entries written
+    /// before these fields existed still deserialize.
+    #[serde(default)]
+    pub sol_fill_price: Option<Decimal>,
+    #[serde(default)]
+    pub btc_fill_price: Option<Decimal>,
+    pub redemption_cost_estimate: f64,
+    pub sol_result: LegResult,
+    pub btc_result: LegResult,
+    pub sol_sold: bool,
+    pub btc_sold: bool,
+    pub recorded_profit: f64,
+    /// `PendingTrade::expected_profit` at entry, so `recorded_profit -
+    /// expected_profit` (the realized-vs-expected divergence) can be
+    /// recomputed offline from the log alone. `#[serde(default)]` so older
+    /// log entries written before this field existed still deserialize.
+    #[serde(default)]
+    pub expected_profit: f64,
+    /// Tail hedge settlement inputs/outputs, when the trade had one (see
+    /// `PendingTrade::hedge`) - carries everything `settlement_profit` needs
+    /// to recompute the hedge's own P&L, mirroring the main trade's fields
+    /// above, so `replay_trade_history` can net it into the recomputed total
+    /// the same way `finalize_settlement` did live. `#[serde(default)]` so
+    /// older log entries written before hedging existed still deserialize.
+    #[serde(default)]
+    pub hedge: Option<HedgeLogEntry>,
+}
+
+/// A settled tail hedge's own settlement record, nested inside
+/// `TradeLogEntry`. `sol_result`/`btc_result` are the hedge leg's own
+/// results (the inverse of the main trade's, via
+/// `crate::trader::invert_leg_result`), not the main trade's.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HedgeLogEntry {
+    pub sol_token_id: String,
+    pub btc_token_id: String,
+    pub sol_price: Decimal,
+    pub btc_price: Decimal,
+    pub units: f64,
+    pub investment_amount: f64,
+    pub sol_result: LegResult,
+    pub btc_result: LegResult,
+    pub sol_sold: bool,
+    pub btc_sold: bool,
+    pub profit: f64,
+}
+
+/// Appends settled trades to a JSONL file for later replay auditing.
+/// Writes are buffered in memory and only flushed periodically (via
+/// `flush`), mirroring `SnapshotRecorder`.
+pub struct TradeLogger {
+    writer: Mutex<BufWriter<std::fs::File>>,
+}
+
+impl TradeLogger {
+    pub fn new(path: &Path) -> Result<Self> {
+        let file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("Failed to open trade log file: {}", path.display()))?;
+
+        Ok(Self {
+            writer: Mutex::new(BufWriter::new(file)),
+        })
+    }
+
+    pub async fn log(&self, entry: &TradeLogEntry) -> Result<()> {
+        let line = serde_json::to_string(entry).context("Failed to serialize trade log entry")?;
+
+        let mut writer = self.writer.lock().await;
+        writer.write_all(line.as_bytes())?;
+        writer.write_all(b"\n")?;
+
+        Ok(())
+    }
+
+    /// Flushes buffered writes to the OS and `fsync`s the underlying file,
+    /// so a periodic call (rather than one per `log`) still guarantees
+    /// settled trades survive a crash between flushes, not just a clean
+    /// process exit.
+    pub async fn flush(&self) -> Result<()> {
+        let mut writer = self.writer.lock().await;
+        writer.flush().context("Failed to flush trade logger")?;
+        writer.get_ref().sync_all().context("Failed to fsync trade log file")
+    }
+}
@@ -1,257 +1,1555 @@
-use crate::api::PolymarketApi;
+use crate::api::PriceSource;
+use crate::error::{ApiError, TraderError};
 use crate::models::*;
-use crate::config::TradingConfig;
-use anyhow::Result;
+use crate::config::{TradingConfig, TradingWindow};
+use crate::order_format::{self, OrderSide};
+use crate::shared_state::SharedStateBackend;
+use crate::stats::{LifetimeStats, StatsFile};
+use crate::telemetry;
+use futures::stream::{self, StreamExt};
 use log::{info, warn, debug};
+use rand::{Rng, SeedableRng};
 use rust_decimal::Decimal;
 use std::sync::Arc;
 use tokio::sync::Mutex;
 use std::collections::HashMap;
-use std::time::{Instant, Duration};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::Duration;
+
+type Result<T> = std::result::Result<T, TraderError>;
+
+/// Below this, `total_cost` is too small (or corrupt) to size a position
+/// from - dividing `max_size` by it would blow units toward infinity, or
+/// toward NaN for an exact zero, and could put on a runaway-sized order from
+/// what should have been a rejected opportunity.
+const MIN_VIABLE_COST_PER_UNIT: f64 = 1e-6;
+
+/// Converts `total_cost` to `f64` and rejects it as unusable for sizing
+/// unless it's finite and at least `MIN_VIABLE_COST_PER_UNIT`. Shared by
+/// `calculate_position_size` and `calculate_units` so a bad price is caught
+/// before either divides by it.
+fn viable_cost_per_unit(total_cost: Decimal) -> Option<f64> {
+    let cost_per_unit = f64::try_from(total_cost).unwrap_or(1.0);
+    if cost_per_unit.is_finite() && cost_per_unit >= MIN_VIABLE_COST_PER_UNIT {
+        Some(cost_per_unit)
+    } else {
+        None
+    }
+}
+
+/// Current wall-clock time as unix epoch seconds. Used in place of
+/// `Instant::now()` for anything that must survive a system sleep/suspend:
+/// `Instant` is monotonic but frozen during suspend, so an `elapsed()` age
+/// computed from it under-counts the true wall-clock age after a resume.
+fn unix_now_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+/// Split a total notional into chunks of at most `max_slice`, preserving the
+/// total exactly. The last chunk carries the remainder.
+fn split_notional(total: f64, max_slice: f64) -> Vec<f64> {
+    if total <= max_slice || max_slice <= 0.0 {
+        return vec![total];
+    }
+
+    let num_slices = (total / max_slice).ceil() as usize;
+    let mut slices = Vec::with_capacity(num_slices);
+    let mut remaining = total;
+    for _ in 0..num_slices {
+        let slice = remaining.min(max_slice);
+        slices.push(slice);
+        remaining -= slice;
+    }
+    slices
+}
+
+/// Parse a "HH:MM" (24-hour) time-of-day into minutes since UTC midnight.
+fn parse_hhmm_to_minute_of_day(s: &str) -> Option<u32> {
+    let (h, m) = s.split_once(':')?;
+    let h: u32 = h.parse().ok()?;
+    let m: u32 = m.parse().ok()?;
+    if h < 24 && m < 60 {
+        Some(h * 60 + m)
+    } else {
+        None
+    }
+}
+
+/// Whether `minute_of_day` falls within `[start, end)`, treating `end <
+/// start` as a window that wraps past midnight (e.g. 22:00-02:00) and `start
+/// == end` as the entire day.
+fn minute_of_day_in_window(minute_of_day: u32, start: u32, end: u32) -> bool {
+    if start == end {
+        true
+    } else if start < end {
+        minute_of_day >= start && minute_of_day < end
+    } else {
+        minute_of_day >= start || minute_of_day < end
+    }
+}
+
+/// True if `unix_secs` falls within any of `windows`. An empty `windows`
+/// means no restriction - always true. A window whose start/end can't be
+/// parsed is logged and skipped rather than failing the whole check.
+fn is_within_trading_windows(unix_secs: u64, windows: &[TradingWindow]) -> bool {
+    if windows.is_empty() {
+        return true;
+    }
+    let minute_of_day = ((unix_secs % 86_400) / 60) as u32;
+    windows.iter().any(|w| {
+        match (parse_hhmm_to_minute_of_day(&w.start_utc), parse_hhmm_to_minute_of_day(&w.end_utc)) {
+            (Some(start), Some(end)) => minute_of_day_in_window(minute_of_day, start, end),
+            _ => {
+                warn!("Invalid trading window \"{}\"-\"{}\"; ignoring", w.start_utc, w.end_utc);
+                false
+            }
+        }
+    })
+}
+
+/// True if a rollover was observed less than `grace_ms` ago. `None` (no
+/// rollover seen this run, e.g. the process started mid-period) never
+/// blocks - there's no freshly-opened book to wait out. A `grace_ms` of
+/// zero always returns false, preserving the original no-grace-period
+/// behavior regardless of `ms_since_rollover`.
+fn is_in_post_rollover_grace(ms_since_rollover: Option<u64>, grace_ms: u64) -> bool {
+    match ms_since_rollover {
+        Some(elapsed_ms) => elapsed_ms < grace_ms,
+        None => false,
+    }
+}
+
+/// Outcome of a single leg's market once it's closed. Most closed markets
+/// resolve to exactly one winning token, but a market can also resolve as
+/// invalid/void (e.g. the underlying question was ambiguous or the event
+/// never occurred) with no winner at all - in that case funds are returned
+/// rather than won or lost.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LegResult {
+    Won,
+    Lost,
+    Invalid,
+}
+
+/// True when `market.tokens` contains an entry for `token_id`. A closed
+/// market is expected to enumerate every token it was created with, so this
+/// being false means either the CLOB response's `tokens` field hasn't
+/// finished populating yet (retry later) or our own `token_id` genuinely
+/// isn't one of this market's tokens (a real problem worth surfacing loudly)
+/// - either way it's not safe to treat as a definite loss.
+fn market_has_token(market: &crate::models::MarketDetails, token_id: &str) -> bool {
+    market.tokens.iter().any(|t| t.token_id == token_id)
+}
+
+/// Determine a leg's result from a closed market's tokens. A closed market
+/// ordinarily has exactly one token with `winner == true`; if none do, the
+/// market resolved invalid/void (e.g. an ambiguous or cancelled event)
+/// rather than picking a winner.
+pub fn leg_result_for_token(market: &crate::models::MarketDetails, token_id: &str) -> LegResult {
+    if !market.tokens.iter().any(|t| t.winner) {
+        return LegResult::Invalid;
+    }
+
+    let is_winner = market.tokens.iter().find(|t| t.token_id == token_id).map(|t| t.winner).unwrap_or(false);
+    if is_winner {
+        LegResult::Won
+    } else {
+        LegResult::Lost
+    }
+}
+
+/// The opposing outcome's result for the same binary market - a tail
+/// hedge's leg always resolves as the exact inverse of the main trade's
+/// corresponding leg, since it's a bet on the same market's other side. A
+/// market that resolves invalid/void has no winner on either side, so it
+/// stays invalid rather than flipping to a win.
+pub(crate) fn invert_leg_result(result: LegResult) -> LegResult {
+    match result {
+        LegResult::Won => LegResult::Lost,
+        LegResult::Lost => LegResult::Won,
+        LegResult::Invalid => LegResult::Invalid,
+    }
+}
+
+/// A settled tail hedge's leg results/sold flags and net P&L, returned by
+/// `Trader::settle_hedge` for `finalize_settlement` to fold into the main
+/// trade's own settlement and record on the trade log.
+struct HedgeSettlement {
+    sol_result: LegResult,
+    btc_result: LegResult,
+    sol_sold: bool,
+    btc_sold: bool,
+    profit: f64,
+    fully_realized: bool,
+}
+
+/// Compute realized profit for a settled trade from its outcome, and
+/// whether it is fully realized. Pulled out as a pure function (rather than
+/// a `Trader` method) so the `replay` audit tool can recompute the "true"
+/// profit from a trade-history record's actual winner flags using the exact
+/// same settlement math as the live run, instead of a separately maintained
+/// reimplementation that could silently drift out of sync.
+///
+/// We bought SOL Up + BTC Down (or the mirrored Down/Up legs for the other
+/// strategy). When markets close:
+/// - If the SOL leg wins and is sold: we get $1 per unit
+/// - If the BTC leg wins and is sold: we get $1 per unit
+/// - If both win and are sold: we get $2 per unit
+/// - If both lose: we get $0 per unit
+/// - If a leg resolves invalid/void: that leg's stake is returned (its
+///   entry price per unit), rather than winning $1 or losing everything
+///
+/// A winning leg whose sell order could not be confirmed filled is not
+/// credited, since we didn't actually capture that dollar.
+#[allow(clippy::too_many_arguments)]
+pub fn settlement_profit(
+    investment_amount: f64,
+    units: f64,
+    redemption_cost_estimate: f64,
+    entry_sol_price: f64,
+    entry_btc_price: f64,
+    sol_result: LegResult,
+    btc_result: LegResult,
+    sol_sold: bool,
+    btc_sold: bool,
+) -> (f64, bool) {
+    let leg_payout = |result: LegResult, sold: bool, entry_price: f64| match result {
+        LegResult::Won if sold => 1.0,
+        LegResult::Won => 0.0,
+        LegResult::Lost => 0.0,
+        LegResult::Invalid => entry_price,
+    };
+
+    let sol_payout = leg_payout(sol_result, sol_sold, entry_sol_price);
+    let btc_payout = leg_payout(btc_result, btc_sold, entry_btc_price);
+    let payout_per_unit = sol_payout + btc_payout;
+
+    let total_payout = payout_per_unit * units;
+    // Redemption gas/fee cost is charged once per settled trade, not per leg.
+    let actual_profit = total_payout - investment_amount - redemption_cost_estimate;
+
+    let fully_realized = (sol_result != LegResult::Won || sol_sold) && (btc_result != LegResult::Won || btc_sold);
+
+    (actual_profit, fully_realized)
+}
+
+/// The `pending_trades` accumulation key for `opportunity`'s condition IDs
+/// and strategy - multiple trades entered in the same period on the same
+/// strategy accumulate under this one key. Also used as the OpenTelemetry
+/// `trade_key` attribute (see `crate::telemetry`) so a span opened here in
+/// `execute_arbitrage` and one opened later in `settle_one_trade` for the
+/// same trade share the same correlating value.
+fn trade_key_for(opportunity: &ArbitrageOpportunity) -> String {
+    format!("{}_{}_{}", opportunity.sol_condition_id, opportunity.btc_condition_id, opportunity.strategy)
+}
+
+/// Total resting size across `book`'s asks priced at or below `limit_price` -
+/// the depth a BUY order at `limit_price` could actually match against,
+/// used by `Trader::depth_fraction_unit_cap` to size a position against
+/// available liquidity rather than just `max_position_size`.
+fn available_ask_depth(book: &OrderBook, limit_price: Decimal) -> f64 {
+    book.asks
+        .iter()
+        .filter(|entry| entry.price <= limit_price)
+        .map(|entry| f64::try_from(entry.size).unwrap_or(0.0))
+        .sum()
+}
+
+/// Taker fee charged at entry for a position of `investment_amount` dollars,
+/// at `fee_bps` basis points (e.g. `10.0` = 0.10%). Applied to the combined
+/// notional of both legs rather than per leg, since `investment_amount`
+/// already covers both.
+fn entry_fee_for_position(investment_amount: f64, fee_bps: f64) -> f64 {
+    investment_amount * (fee_bps / 10_000.0)
+}
+
+/// Folds a newly-observed fill price into a running units-weighted average,
+/// used by `record_pending_with_fills` when multiple trades accumulate under
+/// the same key. `None` on either side means "no fill price known for that
+/// contribution" rather than "zero", so it's preserved rather than dragging
+/// the average toward zero.
+fn weighted_average_fill_price(existing: Option<Decimal>, existing_units: f64, new: Option<Decimal>, new_units: f64) -> Option<Decimal> {
+    match (existing, new) {
+        (Some(existing_price), Some(new_price)) => {
+            let total_units = existing_units + new_units;
+            if total_units <= 0.0 {
+                return Some(new_price);
+            }
+            let existing_weight = Decimal::try_from(existing_units / total_units).unwrap_or_default();
+            let new_weight = Decimal::try_from(new_units / total_units).unwrap_or_default();
+            Some(existing_price * existing_weight + new_price * new_weight)
+        }
+        (Some(existing_price), None) => Some(existing_price),
+        (None, Some(new_price)) => Some(new_price),
+        (None, None) => None,
+    }
+}
+
+/// Folds a newly-computed tail hedge into a trade's existing one when
+/// accumulating more units into the same `trade_key`, weighting each leg's
+/// price by the units it hedges - mirrors `weighted_average_fill_price` for
+/// the hedge's own entry prices. Either side missing (hedging disabled, or no
+/// viable opposing price at that particular entry) just passes the other
+/// through unchanged rather than dropping hedge coverage already bought.
+fn accumulate_hedge(existing: Option<HedgeLeg>, new: Option<HedgeLeg>) -> Option<HedgeLeg> {
+    match (existing, new) {
+        (Some(mut existing), Some(new)) => {
+            let total_units = existing.units + new.units;
+            if total_units > 0.0 {
+                let existing_weight = Decimal::try_from(existing.units / total_units).unwrap_or_default();
+                let new_weight = Decimal::try_from(new.units / total_units).unwrap_or_default();
+                existing.sol_price = existing.sol_price * existing_weight + new.sol_price * new_weight;
+                existing.btc_price = existing.btc_price * existing_weight + new.btc_price * new_weight;
+            }
+            existing.units = total_units;
+            existing.investment_amount += new.investment_amount;
+            Some(existing)
+        }
+        (Some(existing), None) => Some(existing),
+        (None, Some(new)) => Some(new),
+        (None, None) => None,
+    }
+}
+
+/// Runs `settlement_profit` against a table of known scenarios and returns
+/// an error naming the first mismatch. Intended as a startup smoke test
+/// (see `--skip-self-test`): a regression here means the core money math is
+/// broken, which is worth aborting startup over rather than discovering in
+/// production.
+pub fn run_settlement_self_test() -> std::result::Result<(), String> {
+    struct Scenario {
+        name: &'static str,
+        investment_amount: f64,
+        units: f64,
+        redemption_cost_estimate: f64,
+        entry_sol_price: f64,
+        entry_btc_price: f64,
+        sol_result: LegResult,
+        btc_result: LegResult,
+        sol_sold: bool,
+        btc_sold: bool,
+        expected_profit: f64,
+        expected_fully_realized: bool,
+    }
+
+    let scenarios = [
+        Scenario {
+            name: "both legs lose: total loss of the investment",
+            investment_amount: 100.0,
+            units: 100.0,
+            redemption_cost_estimate: 0.0,
+            entry_sol_price: 0.5,
+            entry_btc_price: 0.5,
+            sol_result: LegResult::Lost,
+            btc_result: LegResult::Lost,
+            sol_sold: false,
+            btc_sold: false,
+            expected_profit: -100.0,
+            expected_fully_realized: true,
+        },
+        Scenario {
+            name: "exactly one leg wins and redeems: payout minus investment",
+            investment_amount: 90.0,
+            units: 100.0,
+            redemption_cost_estimate: 0.0,
+            entry_sol_price: 0.5,
+            entry_btc_price: 0.4,
+            sol_result: LegResult::Won,
+            btc_result: LegResult::Lost,
+            sol_sold: true,
+            btc_sold: false,
+            expected_profit: 10.0,
+            expected_fully_realized: true,
+        },
+        Scenario {
+            name: "redemption cost is charged once per trade, not per leg",
+            investment_amount: 90.0,
+            units: 100.0,
+            redemption_cost_estimate: 1.5,
+            entry_sol_price: 0.5,
+            entry_btc_price: 0.4,
+            sol_result: LegResult::Won,
+            btc_result: LegResult::Lost,
+            sol_sold: true,
+            btc_sold: false,
+            expected_profit: 8.5,
+            expected_fully_realized: true,
+        },
+        Scenario {
+            name: "a winning leg that failed to sell earns no payout and is not fully realized",
+            investment_amount: 90.0,
+            units: 100.0,
+            redemption_cost_estimate: 0.0,
+            entry_sol_price: 0.5,
+            entry_btc_price: 0.4,
+            sol_result: LegResult::Won,
+            btc_result: LegResult::Lost,
+            sol_sold: false,
+            btc_sold: false,
+            expected_profit: -90.0,
+            expected_fully_realized: false,
+        },
+        Scenario {
+            name: "a losing leg that never sold doesn't block full realization",
+            investment_amount: 90.0,
+            units: 100.0,
+            redemption_cost_estimate: 0.0,
+            entry_sol_price: 0.5,
+            entry_btc_price: 0.4,
+            sol_result: LegResult::Won,
+            btc_result: LegResult::Lost,
+            sol_sold: true,
+            btc_sold: false,
+            expected_profit: 10.0,
+            expected_fully_realized: true,
+        },
+        Scenario {
+            name: "an invalid/void leg returns its own stake instead of winning or losing",
+            investment_amount: 90.0,
+            units: 100.0,
+            redemption_cost_estimate: 0.0,
+            entry_sol_price: 0.5,
+            entry_btc_price: 0.4,
+            sol_result: LegResult::Invalid,
+            btc_result: LegResult::Lost,
+            sol_sold: false,
+            btc_sold: false,
+            expected_profit: -40.0,
+            expected_fully_realized: true,
+        },
+        Scenario {
+            name: "both legs invalid: roughly breaks even minus redemption cost",
+            investment_amount: 90.0,
+            units: 100.0,
+            redemption_cost_estimate: 2.0,
+            entry_sol_price: 0.5,
+            entry_btc_price: 0.4,
+            sol_result: LegResult::Invalid,
+            btc_result: LegResult::Invalid,
+            sol_sold: false,
+            btc_sold: false,
+            expected_profit: -2.0,
+            expected_fully_realized: true,
+        },
+    ];
+
+    for scenario in &scenarios {
+        let (profit, fully_realized) = settlement_profit(
+            scenario.investment_amount,
+            scenario.units,
+            scenario.redemption_cost_estimate,
+            scenario.entry_sol_price,
+            scenario.entry_btc_price,
+            scenario.sol_result,
+            scenario.btc_result,
+            scenario.sol_sold,
+            scenario.btc_sold,
+        );
+
+        if (profit - scenario.expected_profit).abs() > 0.0001 {
+            return Err(format!(
+                "settlement self-test failed for scenario \"{}\": expected profit {:.4}, got {:.4}",
+                scenario.name, scenario.expected_profit, profit
+            ));
+        }
+        if fully_realized != scenario.expected_fully_realized {
+            return Err(format!(
+                "settlement self-test failed for scenario \"{}\": expected fully_realized {}, got {}",
+                scenario.name, scenario.expected_fully_realized, fully_realized
+            ));
+        }
+    }
+
+    Ok(())
+}
 
 #[derive(Clone)]
 struct CachedMarketData {
     market: MarketDetails,
-    cached_at: Instant,
+    // Unix epoch seconds, not `Instant` - see `unix_now_secs`.
+    cached_at: u64,
+}
+
+/// Removes entries older than `max_age_secs` from a market cache, so a
+/// long-running process doesn't accumulate one entry per period forever.
+fn evict_stale_market_cache_entries(
+    cache: &mut HashMap<String, CachedMarketData>,
+    now: u64,
+    max_age_secs: u64,
+) {
+    cache.retain(|_, cached| now.saturating_sub(cached.cached_at) < max_age_secs);
+}
+
+/// Per-strategy performance counters, exposed via `get_detailed_stats` so an
+/// operator can see which strategy is actually profitable and disable a
+/// losing one via `ArbitrageDetector::set_strategy_enabled`.
+#[derive(Debug, Clone, Default)]
+pub struct StrategyStats {
+    pub profit: f64,
+    pub trades_executed: u64,
+    pub wins: u64,
+    pub losses: u64,
+    /// Sum of `PendingTrade::expected_profit` across this strategy's settled
+    /// trades. Compared against `profit` to see how optimistic the
+    /// detector's model has been in practice - see `avg_profit_divergence`.
+    pub expected_profit: f64,
+    /// Sum of `(actual settled profit - expected_profit)` across this
+    /// strategy's settled trades. A consistently negative running total
+    /// means fees/slippage/losses are eating more than the detector
+    /// accounts for, and the strategy's profit threshold should be raised.
+    pub profit_divergence: f64,
+}
+
+impl StrategyStats {
+    /// Fraction of settled trades that were profitable, or 0.0 if none have
+    /// settled yet.
+    pub fn win_rate(&self) -> f64 {
+        let settled = self.wins + self.losses;
+        if settled == 0 {
+            0.0
+        } else {
+            self.wins as f64 / settled as f64
+        }
+    }
+
+    /// Average per-trade gap between actual and expected profit across
+    /// settled trades, or 0.0 if none have settled yet. Negative means the
+    /// strategy is systematically underperforming its own model.
+    pub fn avg_profit_divergence(&self) -> f64 {
+        let settled = self.wins + self.losses;
+        if settled == 0 {
+            0.0
+        } else {
+            self.profit_divergence / settled as f64
+        }
+    }
 }
 
 pub struct Trader {
-    api: Arc<PolymarketApi>,
+    api: Arc<dyn PriceSource>,
     config: TradingConfig,
     simulation_mode: bool,
     total_profit: Arc<Mutex<f64>>,
     trades_executed: Arc<Mutex<u64>>,
-    pending_trades: Arc<Mutex<HashMap<String, PendingTrade>>>, // Key: sol_condition_id + btc_condition_id
+    // Lifetime win/loss counts across all strategies, mirrored into
+    // `strategy_stats` per-strategy and persisted via `stats_file`.
+    wins: Arc<Mutex<u64>>,
+    losses: Arc<Mutex<u64>>,
+    // Cumulative capital ever deployed across all trades, checked against
+    // `config.max_lifetime_deployed` before entering a new one. Never
+    // decremented - this tracks lifetime deployment, not current exposure.
+    total_deployed: Arc<Mutex<f64>>,
+    pending_trades: Arc<Mutex<HashMap<String, PendingTrade>>>, // Key: sol_condition_id + btc_condition_id + strategy
     market_cache: Arc<Mutex<HashMap<String, CachedMarketData>>>, // Key: condition_id, cache for 60 seconds
+    strategy_stats: Arc<Mutex<HashMap<String, StrategyStats>>>, // Key: strategy name
+    next_trade_id: AtomicU64,
+    // When set, every settled trade is appended here for later `replay`
+    // auditing against the market's actual resolution.
+    trade_logger: Option<Arc<crate::trade_log::TradeLogger>>,
+    // RNG backing `trade_sample_rate`'s per-opportunity coin flip. Seedable
+    // (via `config.trade_sample_seed`) so a backtest/replay run can
+    // reproduce exactly which opportunities were sampled.
+    sample_rng: Mutex<rand::rngs::StdRng>,
+    // Count of real-trade opportunities skipped by `trade_sample_rate`,
+    // tracked separately from `trades_executed` so sampled-out trades don't
+    // silently bias execution-rate stats.
+    trades_skipped_by_sampling: AtomicU64,
+    // When set, lifetime totals are rewritten here on every settlement so
+    // they survive a restart; see `LifetimeStats`.
+    stats_file: Option<Arc<StatsFile>>,
+    // Set by `check_fill_slippage` once a fill's realized slippage exceeds
+    // `config.max_fill_slippage_pct` and `config.halt_trading_on_slippage_breach`
+    // is enabled. Checked at the top of `execute_arbitrage`. Deliberately a
+    // plain runtime flag rather than a config value - unlike
+    // `halt_on_unauthorized`'s hard `process::exit` (a bad API key can never
+    // recover on its own), degrading microstructure is plausibly transient,
+    // so this only stops new entries and lets a restart clear it.
+    trading_halted: AtomicBool,
+    // Optional coordination backend (see `crate::shared_state`) letting
+    // multiple `Trader` instances share a bankroll and dedupe trades on the
+    // same market. `None` (the default) is fully standalone.
+    shared_state: Option<Arc<dyn SharedStateBackend>>,
 }
 
 impl Trader {
-    pub fn new(api: Arc<PolymarketApi>, config: TradingConfig, simulation_mode: bool) -> Self {
+    /// `trade_logger`, if set, receives every settled trade for later
+    /// `replay` auditing. `stats_path`, if set, is loaded here to seed
+    /// lifetime totals and then rewritten on every settlement.
+    pub fn new(
+        api: Arc<dyn PriceSource>,
+        config: TradingConfig,
+        simulation_mode: bool,
+        trade_logger: Option<Arc<crate::trade_log::TradeLogger>>,
+        stats_path: Option<PathBuf>,
+        shared_state: Option<Arc<dyn SharedStateBackend>>,
+    ) -> Self {
+        let sample_rng = match config.trade_sample_seed {
+            Some(seed) => rand::rngs::StdRng::seed_from_u64(seed),
+            None => rand::rngs::StdRng::from_entropy(),
+        };
+        let (stats_file, loaded) = match stats_path {
+            Some(path) => {
+                let loaded = StatsFile::load(&path).unwrap_or_else(|e| {
+                    warn!("Failed to load lifetime stats file, starting from zero: {}", e);
+                    LifetimeStats::default()
+                });
+                (Some(Arc::new(StatsFile::new(path))), loaded)
+            }
+            None => (None, LifetimeStats::default()),
+        };
         Self {
             api,
             config,
             simulation_mode,
-            total_profit: Arc::new(Mutex::new(0.0)),
-            trades_executed: Arc::new(Mutex::new(0)),
+            total_profit: Arc::new(Mutex::new(loaded.profit)),
+            trades_executed: Arc::new(Mutex::new(loaded.trades)),
+            wins: Arc::new(Mutex::new(loaded.wins)),
+            losses: Arc::new(Mutex::new(loaded.losses)),
+            sample_rng: Mutex::new(sample_rng),
+            trades_skipped_by_sampling: AtomicU64::new(0),
+            total_deployed: Arc::new(Mutex::new(loaded.deployed)),
             pending_trades: Arc::new(Mutex::new(HashMap::new())),
             market_cache: Arc::new(Mutex::new(HashMap::new())),
+            strategy_stats: Arc::new(Mutex::new(HashMap::new())),
+            next_trade_id: AtomicU64::new(1),
+            trade_logger,
+            stats_file,
+            trading_halted: AtomicBool::new(false),
+            shared_state,
         }
     }
 
-    /// Check and settle pending trades when markets close
-    pub async fn check_pending_trades(&self) -> Result<()> {
+    /// Generate a unique, monotonically increasing trade ID for correlating
+    /// an entry with its eventual settlement in logs/audit trails.
+    fn new_trade_id(&self) -> String {
+        let n = self.next_trade_id.fetch_add(1, Ordering::Relaxed);
+        format!("trade-{}", n)
+    }
+
+    /// Reconcile in-memory `pending_trades` against the exchange's reported
+    /// positions, intended to be run once at startup to catch drift from a
+    /// crash or a missed fill notification. On-exchange positions with no
+    /// matching tracked token are logged as orphans; tracked trades with no
+    /// matching on-exchange position are flagged as unbacked. This never
+    /// mutates `pending_trades` — it's a diagnostic pass, not an auto-heal.
+    pub async fn reconcile_positions(&self) -> Result<()> {
+        let positions = self.api.get_positions().await?;
+        let trade_history = self.api.get_trade_history().await?;
+        info!("Reconciliation: {} on-exchange positions, {} recent trades on record",
+              positions.len(), trade_history.len());
+
+        let tracked_token_ids: std::collections::HashSet<String> = {
+            let pending = self.pending_trades.lock().await;
+            pending
+                .values()
+                .flat_map(|t| [t.sol_token_id.clone(), t.btc_token_id.clone()])
+                .collect()
+        };
+
+        let position_token_ids: std::collections::HashSet<String> = positions
+            .iter()
+            .filter(|p| p.size > Decimal::ZERO)
+            .map(|p| p.token_id.clone())
+            .collect();
+
+        for orphan in position_token_ids.difference(&tracked_token_ids) {
+            warn!("⚠️  Orphan on-exchange position not tracked in pending_trades: token_id={}", orphan);
+        }
+
+        for unbacked in tracked_token_ids.difference(&position_token_ids) {
+            warn!("⚠️  Tracked pending trade has no matching on-exchange position: token_id={}", unbacked);
+        }
+
+        Ok(())
+    }
+
+    /// Atomically claim every pending trade old enough to check for market
+    /// closure and not already claimed by another in-flight settlement,
+    /// marking each `settling` before releasing the lock. Two concurrent
+    /// callers racing on the same trade will never both get it back: only
+    /// one observes `settling == false` and flips it, since both the check
+    /// and the flip happen under a single lock acquisition.
+    async fn claim_settleable_trades(&self, min_age: Duration) -> Vec<(String, PendingTrade)> {
         let mut pending = self.pending_trades.lock().await;
-        let mut to_remove = Vec::new();
-        
-        // Only check trades that are at least 14 minutes old (markets close after 15 minutes)
-        let min_age = Duration::from_secs(14 * 60);
-        
+
         let pending_count = pending.len();
         if pending_count > 0 {
             debug!("Checking {} pending trades for market closure...", pending_count);
         }
-        
-        for (key, trade) in pending.iter() {
-            let age = trade.timestamp.elapsed();
-            
-            // Skip checking if trade is too recent (markets won't be closed yet)
-            if age < min_age {
-                debug!("Trade {} is too recent (age: {:.1}s, need: {:.1}s), skipping", 
-                       key, age.as_secs_f64(), min_age.as_secs_f64());
+
+        let mut claimed = Vec::new();
+        for (key, trade) in pending.iter_mut() {
+            if trade.settling {
+                debug!("Trade {} is already being settled elsewhere, skipping", key);
+                continue;
+            }
+
+            let age_secs = unix_now_secs().saturating_sub(trade.timestamp);
+            if age_secs < min_age.as_secs() {
+                debug!("Trade {} is too recent (age: {}s, need: {}s), skipping",
+                       key, age_secs, min_age.as_secs());
                 continue;
             }
-            
-            info!("🔍 Checking market closure for trade {} (age: {:.1} minutes)", 
-                  key, age.as_secs_f64() / 60.0);
-            
+
+            trade.settling = true;
+            claimed.push((key.clone(), trade.clone()));
+        }
+        claimed
+    }
+
+    /// Check and settle pending trades when markets close. Idempotent with
+    /// respect to a trade that's already being settled: each eligible trade
+    /// is claimed (marked `settling`) under the `pending_trades` lock before
+    /// any network calls are made, and released back for an unclosed market
+    /// or removed once settled. If two calls race (a concurrent poller tick,
+    /// or a future websocket-driven resolution event alongside the poller),
+    /// only the call that wins the claim settles the trade and books profit.
+    pub async fn check_pending_trades(&self) -> Result<()> {
+        // Only check trades once they're close to the period boundary (a 60s
+        // safety margin before the configured period duration elapses).
+        let min_age = Duration::from_secs(self.config.period_duration_secs.saturating_sub(60));
+        self.settle_claimed_trades(min_age).await
+    }
+
+    /// How long the settlement poller should sleep before its next
+    /// `check_pending_trades` call. With no pending trades there's nothing
+    /// to react to, so it idles at `settlement_idle_check_interval_ms`
+    /// instead of waking on the busy cadence for no reason. With pending
+    /// trades, it sleeps until the soonest one's settlement window opens -
+    /// the same `min_age` gate `check_pending_trades` itself enforces - so a
+    /// trade far from closing doesn't cause dozens of wasted wakeups; once
+    /// that window is open it falls back to `settlement_check_interval_ms`
+    /// so a market that's slow to confirm closed still gets checked
+    /// regularly.
+    pub async fn next_settlement_check_delay(&self) -> Duration {
+        let pending = self.pending_trades.lock().await;
+        if pending.is_empty() {
+            return Duration::from_millis(self.config.settlement_idle_check_interval_ms);
+        }
+
+        let min_age_secs = self.config.period_duration_secs.saturating_sub(60);
+        let now = unix_now_secs();
+        let soonest_remaining_secs = pending
+            .values()
+            .map(|trade| min_age_secs.saturating_sub(now.saturating_sub(trade.timestamp)))
+            .min()
+            .unwrap_or(0);
+
+        if soonest_remaining_secs == 0 {
+            Duration::from_millis(self.config.settlement_check_interval_ms)
+        } else {
+            Duration::from_secs(soonest_remaining_secs)
+        }
+    }
+
+    /// One-time startup reconciliation: settle any loaded pending trade
+    /// whose market has already closed, regardless of age. A restart that
+    /// crosses a period boundary while down would otherwise leave an
+    /// already-resolved trade sitting unsettled until it passes
+    /// `check_pending_trades`' normal min-age gate and the next poll tick -
+    /// this claims and settles it immediately instead.
+    pub async fn recover_resolved_trades_on_startup(&self) -> Result<()> {
+        self.settle_claimed_trades(Duration::ZERO).await
+    }
+
+    /// Polls `check_pending_trades` at `poll_interval` until every pending
+    /// trade has settled or `timeout` elapses, whichever comes first.
+    /// Intended for a graceful shutdown that wants to realize as many
+    /// near-to-settle trades as possible before exiting; a trade that's not
+    /// yet close to its period boundary won't settle no matter how long
+    /// this waits, so a bounded timeout rather than an unbounded wait is
+    /// the right default. Returns the number of trades still pending when
+    /// the wait ends, so the caller can log what's being left for the
+    /// startup recovery path.
+    pub async fn wait_for_pending_settlement(&self, timeout: Duration, poll_interval: Duration) -> usize {
+        let deadline = tokio::time::Instant::now() + timeout;
+        loop {
+            if let Err(e) = self.check_pending_trades().await {
+                warn!("Error checking pending trades during shutdown settlement wait: {}", e);
+            }
+
+            let remaining = self.pending_trade_count().await;
+            if remaining == 0 || tokio::time::Instant::now() >= deadline {
+                return remaining;
+            }
+
+            tokio::time::sleep(poll_interval).await;
+        }
+    }
+
+    /// Shared settlement loop behind `check_pending_trades` and
+    /// `recover_resolved_trades_on_startup`, differing only in how old a
+    /// trade must be before it's eligible for a closure check. Claimed
+    /// trades are checked and settled concurrently, bounded by
+    /// `config.settlement_concurrency`, so a backlog of many pending trades
+    /// doesn't burst every `get_market` call in the tick at once -
+    /// `PolymarketApi`'s own `concurrency_limit` semaphore still caps total
+    /// in-flight HTTP requests underneath this.
+    async fn settle_claimed_trades(&self, min_age: Duration) -> Result<()> {
+        let claimed = self.claim_settleable_trades(min_age).await;
+        let results: Vec<Result<()>> = stream::iter(claimed)
+            .map(|(key, trade)| self.settle_one_trade(key, trade))
+            .buffer_unordered(self.config.settlement_concurrency.max(1))
+            .collect()
+            .await;
+
+        for result in results {
+            result?;
+        }
+
+        Ok(())
+    }
+
+    /// Checks a single claimed trade for market closure and, if both legs
+    /// have closed, settles it - selling/redeeming winning tokens,
+    /// recording actual profit, and updating lifetime stats. Extracted from
+    /// `settle_claimed_trades` so it can be driven concurrently across
+    /// multiple claimed trades via `buffer_unordered`.
+    async fn settle_one_trade(&self, key: String, trade: PendingTrade) -> Result<()> {
+        let span =
+            telemetry::TradeSpan::new(&key, &trade.strategy, &trade.sol_condition_id, &trade.btc_condition_id);
+        span.instrument(self.settle_one_trade_traced(key, trade)).await
+    }
+
+    /// Body of `settle_one_trade`, run inside its OpenTelemetry span - see
+    /// `crate::telemetry`.
+    async fn settle_one_trade_traced(&self, key: String, trade: PendingTrade) -> Result<()> {
+        {
+            let age_secs = unix_now_secs().saturating_sub(trade.timestamp);
+            info!("🔍 Checking market closure for trade {} (age: {:.1} minutes)",
+                  key, age_secs as f64 / 60.0);
+
             // Check if markets are closed (using cached data when possible)
-            let (sol_closed, sol_winner) = self.check_market_result_cached(&trade.sol_condition_id, &trade.sol_token_id).await?;
-            let (btc_closed, btc_winner) = self.check_market_result_cached(&trade.btc_condition_id, &trade.btc_token_id).await?;
-            
-            info!("   SOL Market ({}): closed={}, winner={}", 
-                  &trade.sol_condition_id[..16], sol_closed, sol_winner);
-            info!("   BTC Market ({}): closed={}, winner={}", 
-                  &trade.btc_condition_id[..16], btc_closed, btc_winner);
-            
+            let (sol_closed, sol_result) = self.check_market_result_cached(&trade.sol_condition_id, &trade.sol_token_id).await?;
+            let (btc_closed, btc_result) = self.check_market_result_cached(&trade.btc_condition_id, &trade.btc_token_id).await?;
+
+            info!("   SOL Market ({}): closed={}, result={:?}",
+                  &trade.sol_condition_id[..16], sol_closed, sol_result);
+            info!("   BTC Market ({}): closed={}, result={:?}",
+                  &trade.btc_condition_id[..16], btc_closed, btc_result);
+
             if sol_closed && btc_closed {
-                // Both markets closed, sell/redeem winning tokens and calculate actual profit
-                if !self.simulation_mode {
-                    // In production mode, try to sell winning tokens (they're worth $1 each)
-                    self.sell_winning_tokens(&trade, sol_winner, btc_winner).await;
-                }
-                
-                let actual_profit = self.calculate_actual_profit(&trade, sol_winner, btc_winner);
-                
-                let mut total = self.total_profit.lock().await;
-                *total += actual_profit;
-                let total_profit = *total;
-                drop(total);
-                
-                info!(
-                    "💰 Market Closed - SOL Winner: {}, BTC Winner: {} | Actual Profit: ${:.4} | Total Profit: ${:.2}",
-                    if sol_winner { "WON" } else { "LOST" },
-                    if btc_winner { "WON" } else { "LOST" },
-                    actual_profit,
-                    total_profit
-                );
-                
-                to_remove.push(key.clone());
+                self.finalize_settlement(&key, &trade, sol_result, btc_result).await?;
             } else {
-                info!("   ⏳ Markets not both closed yet (SOL: {}, BTC: {}), will check again...", 
+                info!("   ⏳ Markets not both closed yet (SOL: {}, BTC: {}), will check again...",
                       sol_closed, btc_closed);
+                // Not settled yet — release the claim so the next tick can retry.
+                if let Some(trade) = self.pending_trades.lock().await.get_mut(&key) {
+                    trade.settling = false;
+                }
+            }
+        }
+
+
+        Ok(())
+    }
+
+    /// Sells/redeems both winning legs, updates every profit/stat tracker,
+    /// writes the audit log line, and drops the trade from `pending_trades`.
+    /// Shared by the normal `settle_one_trade_traced` poll path (once both
+    /// markets are confirmed closed) and `force_settle_paper_trade` (an
+    /// on-demand override for exercising this same code in simulation mode),
+    /// so both paths settle a trade identically instead of drifting apart.
+    async fn finalize_settlement(&self, key: &str, trade: &PendingTrade, sol_result: LegResult, btc_result: LegResult) -> Result<()> {
+        // Both markets closed, sell/redeem winning tokens and calculate actual profit
+        let (sol_sold, btc_sold) = if !self.simulation_mode {
+            // In production mode, try to sell winning tokens (they're worth $1 each)
+            self.sell_winning_tokens(trade, sol_result, btc_result).await
+        } else {
+            (true, true)
+        };
+
+        let (actual_profit, fully_realized) =
+            self.calculate_actual_profit(trade, sol_result, btc_result, sol_sold, btc_sold);
+
+        let hedge_settlement = match &trade.hedge {
+            Some(hedge) => Some(self.settle_hedge(hedge, sol_result, btc_result).await),
+            None => None,
+        };
+        let actual_profit = actual_profit + hedge_settlement.as_ref().map(|s| s.profit).unwrap_or(0.0);
+        let fully_realized = fully_realized && hedge_settlement.as_ref().map(|s| s.fully_realized).unwrap_or(true);
+
+        telemetry::record_settlement(actual_profit, fully_realized);
+
+        let mut total = self.total_profit.lock().await;
+        *total += actual_profit;
+        let total_profit = *total;
+        drop(total);
+
+        {
+            let mut strategy_stats = self.strategy_stats.lock().await;
+            let entry = strategy_stats.entry(trade.strategy.clone()).or_default();
+            entry.profit += actual_profit;
+            entry.expected_profit += trade.expected_profit;
+            entry.profit_divergence += actual_profit - trade.expected_profit;
+            if actual_profit > 0.0 {
+                entry.wins += 1;
+            } else {
+                entry.losses += 1;
+            }
+        }
+
+        if actual_profit > 0.0 {
+            *self.wins.lock().await += 1;
+        } else {
+            *self.losses.lock().await += 1;
+        }
+
+        if let Some(stats_file) = &self.stats_file {
+            let stats = LifetimeStats {
+                profit: total_profit,
+                trades: *self.trades_executed.lock().await,
+                wins: *self.wins.lock().await,
+                losses: *self.losses.lock().await,
+                deployed: *self.total_deployed.lock().await,
+                updated_at_secs: unix_now_secs(),
+            };
+            if let Err(e) = stats_file.write(&stats).await {
+                warn!("Failed to persist lifetime stats: {}", e);
+            }
+        }
+
+        info!(
+            "💰 Market Closed - SOL: {:?}, BTC: {:?} | Actual Profit: ${:.4} | Total Profit: ${:.2}",
+            sol_result,
+            btc_result,
+            actual_profit,
+            total_profit
+        );
+        info!(
+            "   🧾 Audit: trade_id={} entry_sol=${} entry_btc=${} settled_profit=${:.4} status={}",
+            trade.trade_id,
+            trade.entry_sol_price,
+            trade.entry_btc_price,
+            actual_profit,
+            if fully_realized { "realized" } else { "unrealized" }
+        );
+
+        if let Some(logger) = &self.trade_logger {
+            let log_entry = crate::trade_log::TradeLogEntry {
+                trade_id: trade.trade_id.clone(),
+                strategy: trade.strategy.clone(),
+                sol_condition_id: trade.sol_condition_id.clone(),
+                btc_condition_id: trade.btc_condition_id.clone(),
+                sol_token_id: trade.sol_token_id.clone(),
+                btc_token_id: trade.btc_token_id.clone(),
+                investment_amount: trade.investment_amount,
+                units: trade.units,
+                entry_sol_price: trade.entry_sol_price,
+                entry_btc_price: trade.entry_btc_price,
+                sol_fill_price: trade.sol_fill_price,
+                btc_fill_price: trade.btc_fill_price,
+                redemption_cost_estimate: self.config.redemption_cost_estimate,
+                sol_result,
+                btc_result,
+                sol_sold,
+                btc_sold,
+                recorded_profit: actual_profit,
+                expected_profit: trade.expected_profit,
+                hedge: trade.hedge.as_ref().zip(hedge_settlement.as_ref()).map(|(hedge, settlement)| {
+                    crate::trade_log::HedgeLogEntry {
+                        sol_token_id: hedge.sol_token_id.clone(),
+                        btc_token_id: hedge.btc_token_id.clone(),
+                        sol_price: hedge.sol_price,
+                        btc_price: hedge.btc_price,
+                        units: hedge.units,
+                        investment_amount: hedge.investment_amount,
+                        sol_result: settlement.sol_result,
+                        btc_result: settlement.btc_result,
+                        sol_sold: settlement.sol_sold,
+                        btc_sold: settlement.btc_sold,
+                        profit: settlement.profit,
+                    }
+                }),
+            };
+            if let Err(e) = logger.log(&log_entry).await {
+                warn!("Failed to append trade log entry: {}", e);
             }
         }
-        
-        for key in to_remove {
-            pending.remove(&key);
+
+        self.pending_trades.lock().await.remove(key);
+
+        if let Some(backend) = &self.shared_state {
+            if let Err(e) = backend.release_claim(key).await {
+                warn!("Failed to release shared-state claim for {}: {}", key, e);
+            }
         }
-        
+
         Ok(())
     }
 
-    async fn check_market_result_cached(&self, condition_id: &str, token_id: &str) -> Result<(bool, bool)> {
+    /// Manually settles a simulated pending trade with operator-supplied leg
+    /// outcomes, without waiting for its real markets to close. There's no
+    /// HTTP status/control endpoint in this codebase yet to hang this off of,
+    /// so it's exposed directly as a `Trader` method for now; it routes
+    /// through the same `finalize_settlement` the normal poll loop uses, so
+    /// calling it exercises the real settlement/accounting code rather than a
+    /// separate test-only path. Rejected outside simulation mode, since
+    /// production trades must settle against actual market results.
+    pub async fn force_settle_paper_trade(&self, trade_key: &str, sol_result: LegResult, btc_result: LegResult) -> Result<()> {
+        if !self.simulation_mode {
+            return Err(TraderError::NotSimulationMode {
+                operation: "force_settle_paper_trade".to_string(),
+            });
+        }
+
+        let trade = self
+            .pending_trades
+            .lock()
+            .await
+            .remove(trade_key)
+            .ok_or_else(|| TraderError::TradeNotFound {
+                key: trade_key.to_string(),
+            })?;
+
+        warn!(
+            "Manually force-settling paper trade {} (key {}) with SOL={:?}, BTC={:?}",
+            trade.trade_id, trade_key, sol_result, btc_result
+        );
+
+        self.finalize_settlement(trade_key, &trade, sol_result, btc_result).await
+    }
+
+    async fn check_market_result_cached(&self, condition_id: &str, token_id: &str) -> Result<(bool, LegResult)> {
         // Check cache first (cache for 60 seconds)
         let cache_ttl = Duration::from_secs(60);
-        let mut cache = self.market_cache.lock().await;
-        
+        let cache = self.market_cache.lock().await;
+
         // Check if we have cached data that's still valid
         if let Some(cached) = cache.get(condition_id) {
-            if cached.cached_at.elapsed() < cache_ttl {
+            let age_secs = unix_now_secs().saturating_sub(cached.cached_at);
+            if age_secs < cache_ttl.as_secs() {
                 // Use cached data
                 let market = &cached.market;
                 if market.closed {
-                    let winner = market.tokens.iter()
-                        .find(|t| t.token_id == token_id)
-                        .map(|t| t.winner)
-                        .unwrap_or(false);
                     debug!("Using cached market data for condition_id: {}", condition_id);
-                    return Ok((true, winner));
+                    return Ok(match self.leg_result_for_closed_market(market, token_id, condition_id) {
+                        Some(result) => (true, result),
+                        None => (false, LegResult::Lost),
+                    });
                 } else {
                     debug!("Using cached market data (not closed yet) for condition_id: {}", condition_id);
-                    return Ok((false, false));
+                    return Ok((false, LegResult::Lost));
                 }
             }
         }
-        
+
         // Cache miss or expired - fetch from API
         drop(cache);
         match self.api.get_market(condition_id).await {
             Ok(market) => {
-                // Update cache
+                // Update cache, evicting anything long-settled first so the
+                // map doesn't grow by two entries every period forever.
                 let mut cache = self.market_cache.lock().await;
+                evict_stale_market_cache_entries(&mut cache, unix_now_secs(), self.config.market_cache_max_age_secs);
                 cache.insert(condition_id.to_string(), CachedMarketData {
                     market: market.clone(),
-                    cached_at: Instant::now(),
+                    cached_at: unix_now_secs(),
                 });
                 drop(cache);
-                
+
                 if market.closed {
-                    // Find our token and check if it's the winner
-                    let winner = market.tokens.iter()
-                        .find(|t| t.token_id == token_id)
-                        .map(|t| t.winner)
-                        .unwrap_or(false);
-                    Ok((true, winner))
+                    Ok(match self.leg_result_for_closed_market(&market, token_id, condition_id) {
+                        Some(result) => (true, result),
+                        None => (false, LegResult::Lost),
+                    })
                 } else {
-                    Ok((false, false))
+                    Ok((false, LegResult::Lost))
                 }
             }
             Err(e) => {
                 warn!("Failed to fetch market {}: {}", condition_id, e);
-                Ok((false, false))
+                Ok((false, LegResult::Lost))
             }
         }
     }
 
-    /// Sell winning tokens when markets close (production mode only)
-    async fn sell_winning_tokens(&self, trade: &PendingTrade, sol_winner: bool, btc_winner: bool) {
-        // When markets close, winning tokens are worth $1 each
-        // We should sell them to realize the profit
-        let sell_price = "1.0"; // Winning tokens are worth $1 when market closes
-        
-        if sol_winner {
-            // Sell SOL Up token (it won, worth $1)
-            let sell_order = OrderRequest {
-                token_id: trade.sol_token_id.clone(),
-                side: "SELL".to_string(),
-                size: format!("{:.6}", trade.units),
-                price: sell_price.to_string(),
-                order_type: "LIMIT".to_string(),
-            };
-            
-            match self.api.place_order(&sell_order).await {
-                Ok(_) => {
-                    info!("✅ Sold {} units of SOL Up token (winner) at $1.00", trade.units);
-                }
-                Err(e) => {
-                    warn!("⚠️  Failed to sell SOL Up token: {}", e);
-                }
+    /// Resolves a closed market's leg result, or `None` if `token_id` isn't
+    /// among `market.tokens` at all. An empty `tokens` list reads as the
+    /// CLOB response not having finished populating it yet; a non-empty list
+    /// missing our specific token reads as a real mismatch worth a louder
+    /// warning - but both cases return `None` so the caller retries on the
+    /// next tick instead of booking a false loss for a token we can't find.
+    fn leg_result_for_closed_market(&self, market: &crate::models::MarketDetails, token_id: &str, condition_id: &str) -> Option<LegResult> {
+        if !market_has_token(market, token_id) {
+            if market.tokens.is_empty() {
+                debug!("Market {} is closed but its tokens aren't populated yet; will retry", condition_id);
+            } else {
+                warn!("Market {} is closed with {} token(s), but none match our token_id {}; will retry rather than book a false loss",
+                      condition_id, market.tokens.len(), token_id);
+            }
+            return None;
+        }
+
+        Some(self.apply_adversarial_override(leg_result_for_token(market, token_id)))
+    }
+
+    /// In simulation's adversarial stress-test mode, force a losing result
+    /// with probability `adversarial_loss_probability` regardless of the
+    /// real outcome, so loss-limiting risk controls can be exercised under an
+    /// artificially bad streak. A no-op unless that config field is set, and
+    /// never turns an invalid/void market into a fabricated loss - there's no
+    /// realistic failure mode that behaves like a loss there.
+    fn apply_adversarial_override(&self, actual_result: LegResult) -> LegResult {
+        if actual_result == LegResult::Invalid {
+            return actual_result;
+        }
+        match self.config.adversarial_loss_probability {
+            Some(probability) if rand::random::<f64>() < probability => {
+                warn!("🧪 Adversarial simulation: forcing a loss for a leg that actually {}",
+                      if actual_result == LegResult::Won { "won" } else { "lost" });
+                LegResult::Lost
             }
+            _ => actual_result,
+        }
+    }
+
+    const SELL_MAX_ATTEMPTS: u32 = 3;
+    const SELL_RETRY_DELAY: Duration = Duration::from_secs(2);
+
+    /// Sell winning tokens when markets close (production mode only).
+    /// Returns whether the SOL leg and BTC leg (respectively) were confirmed
+    /// sold; a non-winning leg is trivially reported as sold since there is
+    /// nothing to redeem for it. An invalid/void leg is also nothing to
+    /// sell - its stake is returned automatically rather than through a
+    /// sell order.
+    async fn sell_winning_tokens(&self, trade: &PendingTrade, sol_result: LegResult, btc_result: LegResult) -> (bool, bool) {
+        let sol_sold = if sol_result == LegResult::Won {
+            self.sell_with_confirmation(&trade.sol_token_id, trade.units, "SOL Up").await
+        } else {
+            true
+        };
+
+        let btc_sold = if btc_result == LegResult::Won {
+            self.sell_with_confirmation(&trade.btc_token_id, trade.units, "BTC Down").await
+        } else {
+            true
+        };
+
+        if sol_result != LegResult::Won && btc_result != LegResult::Won {
+            warn!("⚠️  No winning leg to sell (won/lost/invalid: {:?}/{:?})", sol_result, btc_result);
         }
-        
-        if btc_winner {
-            // Sell BTC Down token (it won, worth $1)
-            let sell_order = OrderRequest {
-                token_id: trade.btc_token_id.clone(),
-                side: "SELL".to_string(),
-                size: format!("{:.6}", trade.units),
-                price: sell_price.to_string(),
-                order_type: "LIMIT".to_string(),
+
+        (sol_sold, btc_sold)
+    }
+
+    /// Settles a tail hedge against the main trade's own leg results: the
+    /// hedge's legs are the same binary markets' opposing outcome, so their
+    /// results are the exact inverse (`invert_leg_result`) of `sol_result`/
+    /// `btc_result` rather than requiring a second pair of market-close
+    /// lookups. Sells any winning hedge leg the same way `sell_winning_tokens`
+    /// does for the main trade, then reuses `settlement_profit` for the
+    /// hedge's own P&L, for `finalize_settlement` to net into the main
+    /// trade's own numbers and record on the trade log for
+    /// `replay_trade_history` to recompute later.
+    async fn settle_hedge(&self, hedge: &HedgeLeg, sol_result: LegResult, btc_result: LegResult) -> HedgeSettlement {
+        let sol_result = invert_leg_result(sol_result);
+        let btc_result = invert_leg_result(btc_result);
+
+        let (sol_sold, btc_sold) = if !self.simulation_mode {
+            let sol_sold = if sol_result == LegResult::Won {
+                self.sell_with_confirmation(&hedge.sol_token_id, hedge.units, "hedge SOL").await
+            } else {
+                true
+            };
+            let btc_sold = if btc_result == LegResult::Won {
+                self.sell_with_confirmation(&hedge.btc_token_id, hedge.units, "hedge BTC").await
+            } else {
+                true
             };
-            
-            match self.api.place_order(&sell_order).await {
-                Ok(_) => {
-                    info!("✅ Sold {} units of BTC Down token (winner) at $1.00", trade.units);
+            (sol_sold, btc_sold)
+        } else {
+            (true, true)
+        };
+
+        let (profit, fully_realized) = settlement_profit(
+            hedge.investment_amount,
+            hedge.units,
+            // Redemption cost is already charged once for the main trade in
+            // `calculate_actual_profit` - not charged twice for the hedge.
+            0.0,
+            f64::try_from(hedge.sol_price).unwrap_or(0.0),
+            f64::try_from(hedge.btc_price).unwrap_or(0.0),
+            sol_result,
+            btc_result,
+            sol_sold,
+            btc_sold,
+        );
+
+        HedgeSettlement { sol_result, btc_result, sol_sold, btc_sold, profit, fully_realized }
+    }
+
+    /// Place a SELL order for a winning token and retry until the fill is
+    /// confirmed via order status, up to `SELL_MAX_ATTEMPTS` attempts.
+    async fn sell_with_confirmation(&self, token_id: &str, units: f64, label: &str) -> bool {
+        // Winning tokens are worth $1 each when the market closes.
+        let size_decimal = Decimal::from_f64_retain(units).unwrap_or_default();
+        let sell_order = OrderRequest {
+            token_id: token_id.to_string(),
+            side: "SELL".to_string(),
+            size: order_format::format_size(size_decimal, self.config.size_lot_size, self.config.price_rounding_mode),
+            price: "1.0".to_string(),
+            order_type: "LIMIT".to_string(),
+            // Redeeming a winning token isn't part of the arbitrage hedge -
+            // there's no second leg to strand - so this keeps GTC regardless
+            // of `order_time_in_force`, and instead relies on
+            // `sell_with_confirmation`'s own retry loop.
+            time_in_force: "GTC".to_string(),
+            funder: None,
+        };
+
+        for attempt in 1..=Self::SELL_MAX_ATTEMPTS {
+            let order_response = match self.api.place_order(&sell_order).await {
+                Ok(response) => response,
+                Err(ApiError::RateLimited) => {
+                    warn!("⚠️  Attempt {}/{}: rate limited placing sell order for {} token, backing off", attempt, Self::SELL_MAX_ATTEMPTS, label);
+                    tokio::time::sleep(Self::SELL_RETRY_DELAY * 5).await;
+                    continue;
                 }
                 Err(e) => {
-                    warn!("⚠️  Failed to sell BTC Down token: {}", e);
+                    warn!("⚠️  Attempt {}/{}: failed to place sell order for {} token: {}", attempt, Self::SELL_MAX_ATTEMPTS, label, e);
+                    tokio::time::sleep(Self::SELL_RETRY_DELAY).await;
+                    continue;
                 }
+            };
+
+            let confirmed = match &order_response.order_id {
+                Some(order_id) => match self.api.get_order_status(order_id).await {
+                    Ok(status) => Self::is_fill_confirmed(&status.status),
+                    Err(e) => {
+                        warn!("⚠️  Attempt {}/{}: could not confirm fill for {} token order {}: {}", attempt, Self::SELL_MAX_ATTEMPTS, label, order_id, e);
+                        false
+                    }
+                },
+                None => Self::is_fill_confirmed(&order_response.status),
+            };
+
+            if confirmed {
+                info!("✅ Sold {} units of {} token (winner) at $1.00", units, label);
+                return true;
             }
+
+            warn!("⚠️  Attempt {}/{}: sell order for {} token not yet confirmed filled (status: {})", attempt, Self::SELL_MAX_ATTEMPTS, label, order_response.status);
+            tokio::time::sleep(Self::SELL_RETRY_DELAY).await;
         }
-        
-        if !sol_winner && !btc_winner {
-            warn!("⚠️  Both tokens lost - nothing to sell (both worth $0)");
-        }
+
+        warn!("⚠️  Giving up selling {} token after {} attempts - profit for this leg is unrealized", label, Self::SELL_MAX_ATTEMPTS);
+        false
     }
 
-    fn calculate_actual_profit(&self, trade: &PendingTrade, sol_winner: bool, btc_winner: bool) -> f64 {
-        // We bought SOL Up + BTC Down
-        // When markets close:
-        // - If SOL Up wins: we get $1 per unit
-        // - If BTC Down wins: we get $1 per unit
-        // - If both win: we get $2 per unit
-        // - If both lose: we get $0 per unit
-        
-        let payout_per_unit = if sol_winner && btc_winner {
-            2.0 // Both won! (SOL went UP, BTC went DOWN)
-        } else if sol_winner || btc_winner {
-            1.0 // One won (break even or small profit)
-        } else {
-            0.0 // Both lost! (SOL went DOWN, BTC went UP) - TOTAL LOSS
-        };
-        
-        let total_payout = payout_per_unit * trade.units;
-        let actual_profit = total_payout - trade.investment_amount;
-        
+    fn is_fill_confirmed(status: &str) -> bool {
+        matches!(status.to_uppercase().as_str(), "FILLED" | "MATCHED" | "CONFIRMED")
+    }
+
+    /// Compute realized profit for a settled trade, and whether it is fully
+    /// realized. A winning leg whose sell order could not be confirmed filled
+    /// is not credited, since we didn't actually capture that dollar.
+    fn calculate_actual_profit(
+        &self,
+        trade: &PendingTrade,
+        sol_result: LegResult,
+        btc_result: LegResult,
+        sol_sold: bool,
+        btc_sold: bool,
+    ) -> (f64, bool) {
+        let (actual_profit, fully_realized) = settlement_profit(
+            trade.investment_amount,
+            trade.units,
+            self.config.redemption_cost_estimate,
+            f64::try_from(trade.entry_sol_price).unwrap_or(0.0),
+            f64::try_from(trade.entry_btc_price).unwrap_or(0.0),
+            sol_result,
+            btc_result,
+            sol_sold,
+            btc_sold,
+        );
+
         if actual_profit < 0.0 {
-            warn!("⚠️  LOSS: Both tokens lost! Lost ${:.4} on this trade", -actual_profit);
+            warn!("⚠️  LOSS: Lost ${:.4} on this trade (after redemption cost)", -actual_profit);
         }
-        
-        actual_profit
-    }
 
-    /// Execute arbitrage trade
-    pub async fn execute_arbitrage(&self, opportunity: &ArbitrageOpportunity) -> Result<()> {
-        if self.simulation_mode {
-            self.simulate_trade(opportunity).await
-        } else {
-            self.execute_real_trade(opportunity).await
+        if !fully_realized {
+            warn!("⚠️  Trade {} left unrealized: a winning leg could not be confirmed sold", trade.trade_id);
         }
+
+        (actual_profit, fully_realized)
     }
 
-    async fn simulate_trade(&self, opportunity: &ArbitrageOpportunity) -> Result<()> {
-        info!(
-            "🔍 SIMULATION: Arbitrage opportunity detected!"
+    /// Execute arbitrage trade. `ms_since_rollover` is the monitor's
+    /// `MarketMonitor::ms_since_last_rollover`, and `seconds_until_accepting_orders`
+    /// is `MarketMonitor::seconds_until_accepting_orders`, both threaded
+    /// through here rather than fetched directly so the trade gate stays a
+    /// pure function of its arguments; `None` never blocks either one.
+    pub async fn execute_arbitrage(
+        &self,
+        opportunity: &ArbitrageOpportunity,
+        ms_since_rollover: Option<u64>,
+        seconds_until_accepting_orders: Option<u64>,
+    ) -> Result<()> {
+        let trade_key = trade_key_for(opportunity);
+        let span = telemetry::TradeSpan::new(
+            &trade_key,
+            &opportunity.strategy,
+            &opportunity.sol_condition_id,
+            &opportunity.btc_condition_id,
+        );
+        span.instrument(self.execute_arbitrage_traced(opportunity, ms_since_rollover, seconds_until_accepting_orders))
+            .await
+    }
+
+    /// Body of `execute_arbitrage`, run inside its OpenTelemetry span so
+    /// every log/return path below is covered by the trace - see
+    /// `crate::telemetry`.
+    async fn execute_arbitrage_traced(
+        &self,
+        opportunity: &ArbitrageOpportunity,
+        ms_since_rollover: Option<u64>,
+        seconds_until_accepting_orders: Option<u64>,
+    ) -> Result<()> {
+        telemetry::record_execution(
+            f64::try_from(opportunity.total_cost).unwrap_or(0.0),
+            f64::try_from(opportunity.expected_profit).unwrap_or(0.0),
+        );
+
+        info!(
+            "📐 Evaluating opportunity: strategy={} expected_profit=${:.4} breakeven_cushion={:.2}%",
+            opportunity.strategy,
+            opportunity.expected_profit,
+            opportunity.breakeven_price_move_pct * rust_decimal_macros::dec!(100)
+        );
+
+        if self.trading_halted.load(Ordering::Relaxed) {
+            warn!("🛑 Trading halted after a fill slippage breach - no new trades will be entered (existing trades still settle)");
+            return Ok(());
+        }
+
+        if let Some(max_deployed) = self.config.max_lifetime_deployed {
+            let deployed = *self.total_deployed.lock().await;
+            if deployed >= max_deployed {
+                warn!(
+                    "🛑 Lifetime capital deployment ceiling reached: ${:.2} deployed >= ${:.2} limit - no new trades will be entered (existing trades still settle)",
+                    deployed, max_deployed
+                );
+                return Ok(());
+            }
+        }
+
+        if !self.within_trading_window() {
+            info!("⏭️  Skipping opportunity: outside configured trading window(s)");
+            return Ok(());
+        }
+
+        if is_in_post_rollover_grace(ms_since_rollover, self.config.post_rollover_grace_ms) {
+            info!(
+                "⏭️  Skipping opportunity: still within the post-rollover grace period ({:?}ms elapsed < {}ms grace)",
+                ms_since_rollover, self.config.post_rollover_grace_ms
+            );
+            return Ok(());
+        }
+
+        if let Some(wait_secs) = seconds_until_accepting_orders {
+            info!(
+                "⏭️  Skipping opportunity: market not accepting orders yet ({}s until it opens)",
+                wait_secs
+            );
+            return Ok(());
+        }
+
+        if opportunity.is_midpoint_derived && !self.config.trust_midpoint_for_execution {
+            info!("⏭️  Skipping opportunity: at least one leg's price is midpoint-derived and midpoint trust is disabled");
+            return Ok(());
+        }
+
+        if !self.meets_min_book_depth(opportunity).await {
+            return Ok(());
+        }
+
+        if !self.last_look_edge_still_valid(opportunity).await {
+            info!("⏭️  Skipping opportunity: edge did not survive the last-look re-check");
+            return Ok(());
+        }
+
+        if !self.reserve_shared_state_budget(opportunity).await {
+            return Ok(());
+        }
+
+        if self.simulation_mode {
+            self.simulate_trade(opportunity).await
+        } else if self.sample_trade().await {
+            self.execute_real_trade(opportunity).await
+        } else {
+            info!(
+                "🎲 Skipping opportunity: not sampled (trade_sample_rate = {})",
+                self.config.trade_sample_rate
+            );
+            self.trades_skipped_by_sampling.fetch_add(1, Ordering::Relaxed);
+            Ok(())
+        }
+    }
+
+    /// Rolls the per-opportunity coin flip for `trade_sample_rate`, letting
+    /// an operator ramp a new strategy up gradually in production by
+    /// executing only a fraction of detected opportunities. Only consulted
+    /// for real trades - simulation always runs every opportunity, since
+    /// there's no capital at risk to ramp cautiously.
+    async fn sample_trade(&self) -> bool {
+        if self.config.trade_sample_rate >= 1.0 {
+            return true;
+        }
+        if self.config.trade_sample_rate <= 0.0 {
+            return false;
+        }
+        let mut rng = self.sample_rng.lock().await;
+        rng.gen::<f64>() < self.config.trade_sample_rate
+    }
+
+    /// Number of real-trade opportunities skipped by `trade_sample_rate`
+    /// so far, exposed for status reporting alongside `get_stats`.
+    pub fn trades_skipped_by_sampling(&self) -> u64 {
+        self.trades_skipped_by_sampling.load(Ordering::Relaxed)
+    }
+
+    /// Whether the current UTC time falls within `config.trading_windows`.
+    /// Always true when no windows are configured.
+    fn within_trading_window(&self) -> bool {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        is_within_trading_windows(now, &self.config.trading_windows)
+    }
+
+    /// Re-fetch current best prices for both legs and recompute the edge
+    /// right before order placement, aborting if it has shrunk below
+    /// `expected_profit - last_look_tolerance`. Detection and execution are
+    /// separated by network latency, and a snapshot-derived opportunity can
+    /// go stale in that window.
+    async fn last_look_edge_still_valid(&self, opportunity: &ArbitrageOpportunity) -> bool {
+        let (sol_price, btc_price) = tokio::join!(
+            self.api.get_best_price(&opportunity.sol_up_token_id),
+            self.api.get_best_price(&opportunity.btc_down_token_id)
+        );
+
+        let (sol_price, btc_price) = match (sol_price, btc_price) {
+            (Ok(Some(sol)), Ok(Some(btc))) => (sol, btc),
+            _ => {
+                warn!("Last-look re-fetch failed to get fresh prices for both legs; skipping trade");
+                return false;
+            }
+        };
+
+        if sol_price.is_crossed() || btc_price.is_crossed() {
+            warn!("Last-look re-fetch found a crossed/locked book; skipping trade");
+            return false;
+        }
+
+        let current_total_cost = sol_price.ask_price() + btc_price.ask_price();
+        let current_profit = Decimal::ONE - current_total_cost;
+        let tolerance = Decimal::from_f64_retain(self.config.last_look_tolerance).unwrap_or(Decimal::ZERO);
+        let min_required_profit = opportunity.expected_profit - tolerance;
+
+        if current_profit < min_required_profit {
+            warn!(
+                "⏭️  Stale opportunity: edge shrank from ${:.4} to ${:.4} (tolerance ${:.4})",
+                opportunity.expected_profit, current_profit, tolerance
+            );
+            return false;
+        }
+
+        true
+    }
+
+    /// Skip opportunities whose either leg's resting orderbook depth is
+    /// below `min_book_depth` - fresh 15m markets often quote a tight
+    /// top-of-book spread with almost nothing behind it for the first few
+    /// seconds, thin enough that even a modest order would walk the book far
+    /// past the quoted price. Disabled (returns `true` unconditionally,
+    /// without fetching either orderbook) at the default `min_book_depth` of
+    /// `0.0`. Fails closed: a fetch error means depth can't be confirmed, so
+    /// the opportunity is skipped rather than assumed liquid.
+    async fn meets_min_book_depth(&self, opportunity: &ArbitrageOpportunity) -> bool {
+        if self.config.min_book_depth <= 0.0 {
+            return true;
+        }
+
+        let (sol_book, btc_book) = tokio::join!(
+            self.api.get_orderbook(&opportunity.sol_up_token_id),
+            self.api.get_orderbook(&opportunity.btc_down_token_id)
+        );
+
+        let (sol_book, btc_book) = match (sol_book, btc_book) {
+            (Ok(sol), Ok(btc)) => (sol, btc),
+            _ => {
+                warn!("⏭️  Skipping opportunity: failed to fetch orderbook depth for one or both legs");
+                return false;
+            }
+        };
+
+        let sol_depth = available_ask_depth(&sol_book, opportunity.sol_up_price);
+        let btc_depth = available_ask_depth(&btc_book, opportunity.btc_down_price);
+
+        if sol_depth < self.config.min_book_depth || btc_depth < self.config.min_book_depth {
+            info!(
+                "⏭️  Skipping opportunity: thin book (SOL depth {:.2}, BTC depth {:.2}, minimum {:.2})",
+                sol_depth, btc_depth, self.config.min_book_depth
+            );
+            return false;
+        }
+
+        true
+    }
+
+    /// When a shared-state backend is configured, claims `opportunity`'s
+    /// trade key and reserves `config.max_position_size` of shared capital
+    /// against `config.max_shared_deployed` before entry. Returns `false`
+    /// (already claimed elsewhere, shared budget exhausted, or the backend
+    /// errored) to skip the trade; `true` (including when no backend is
+    /// configured at all) to proceed.
+    async fn reserve_shared_state_budget(&self, opportunity: &ArbitrageOpportunity) -> bool {
+        let Some(backend) = &self.shared_state else {
+            return true;
+        };
+        let trade_key = trade_key_for(opportunity);
+        let max_shared_deployed = self.config.max_shared_deployed.unwrap_or(f64::INFINITY);
+        match backend
+            .try_reserve(&trade_key, self.config.max_position_size, max_shared_deployed)
+            .await
+        {
+            Ok(true) => true,
+            Ok(false) => {
+                info!("⏭️  Skipping opportunity: shared-state reservation denied (already claimed elsewhere or shared budget exhausted)");
+                false
+            }
+            Err(e) => {
+                warn!("⏭️  Skipping opportunity: shared-state reservation failed: {}", e);
+                false
+            }
+        }
+    }
+
+    /// Re-fetch `token_id`'s current best ask and check it hasn't risen
+    /// above `entry_price` by more than `last_look_tolerance`, used to
+    /// detect an unfavorable move on the second leg during
+    /// `inter_leg_delay_max_ms`. There's no cancel-order API to unwind the
+    /// first leg if this fails, so callers only use this to warn, not to
+    /// abort - placing the second leg late and slightly worse still beats
+    /// leaving the first leg unhedged.
+    async fn leg_price_still_within_tolerance(&self, token_id: &str, entry_price: Decimal) -> bool {
+        let tolerance = Decimal::from_f64_retain(self.config.last_look_tolerance).unwrap_or(Decimal::ZERO);
+        match self.api.get_best_price(token_id).await {
+            Ok(Some(price)) if !price.is_crossed() => price.ask_price() <= entry_price + tolerance,
+            _ => false,
+        }
+    }
+
+    async fn simulate_trade(&self, opportunity: &ArbitrageOpportunity) -> Result<()> {
+        info!(
+            "🔍 SIMULATION: Arbitrage opportunity detected!"
         );
         info!(
             "   SOL Up Token Price: ${:.4}",
@@ -279,50 +1577,43 @@ impl Trader {
             opportunity.btc_down_token_id
         );
 
-        // Calculate position size (total dollar amount to invest)
-        let position_size = self.calculate_position_size(opportunity);
+        // Calculate units to buy and the resulting investment (applies
+        // whole-unit rounding when configured).
+        let (units, position_size) = self.calculate_units(opportunity).await;
+        if units <= 0.0 {
+            info!("   ⏭️  Skipping opportunity: rounded position below min_order_size");
+            return Ok(());
+        }
         info!("   Position Size: ${:.2} (total investment amount)", position_size);
-        
-        // Calculate how many units we're buying
+
         let cost_per_unit = f64::try_from(opportunity.total_cost).unwrap_or(1.0);
-        let units = position_size / cost_per_unit;
-        info!("   Units: {:.2} (each unit = ${:.4}, so ${:.2} / ${:.4} = {:.2} units)", 
+        info!("   Units: {:.2} (each unit = ${:.4}, so ${:.2} / ${:.4} = {:.2} units)",
               units, cost_per_unit, position_size, cost_per_unit, units);
-        info!("   SOL Up amount: ${:.2} ({} units × ${:.4})", 
+        info!("   SOL Up amount: ${:.2} ({} units × ${:.4})",
               units * f64::try_from(opportunity.sol_up_price).unwrap_or(0.0),
               units, opportunity.sol_up_price);
-        info!("   BTC Down amount: ${:.2} ({} units × ${:.4})", 
+        info!("   BTC Down amount: ${:.2} ({} units × ${:.4})",
               units * f64::try_from(opportunity.btc_down_price).unwrap_or(0.0),
               units, opportunity.btc_down_price);
 
+        // Build the exact OrderRequests execute_real_trade would submit for
+        // this position (same shared helper, so this can't drift from
+        // production's order construction) and log them verbatim, without
+        // sending them. Lets a slice-splitting or precision bug in the order
+        // path be caught in simulation before it ever risks real funds.
+        let slices = self.plan_order_slices(position_size);
+        for (i, slice) in slices.iter().enumerate() {
+            let slice_units = slice / cost_per_unit;
+            let (sol_order, btc_order) = self.build_leg_orders(opportunity, slice_units);
+            info!("   📝 [DRY RUN - not sent] slice {}/{} SOL order: {:?}", i + 1, slices.len(), sol_order);
+            info!("   📝 [DRY RUN - not sent] slice {}/{} BTC order: {:?}", i + 1, slices.len(), btc_order);
+        }
+
         // In simulation mode, we track the trade and will calculate actual profit when markets close
         // Use condition IDs as key - accumulate multiple trades in the same period
-        let trade_key = format!("{}_{}", opportunity.sol_condition_id, opportunity.btc_condition_id);
-        
-        let mut pending = self.pending_trades.lock().await;
-        
-        // If we already have a trade for this period, accumulate it (add units and investment)
-        if let Some(existing_trade) = pending.get_mut(&trade_key) {
-            // Accumulate: add new units and investment to existing trade
-            existing_trade.units += units;
-            existing_trade.investment_amount += position_size;
-            info!("   📊 Accumulated trade: Total units: {:.2}, Total investment: ${:.2}", 
-                  existing_trade.units, existing_trade.investment_amount);
-        } else {
-            // First trade for this period - create new entry
-            let pending_trade = PendingTrade {
-                sol_token_id: opportunity.sol_up_token_id.clone(),
-                btc_token_id: opportunity.btc_down_token_id.clone(),
-                sol_condition_id: opportunity.sol_condition_id.clone(),
-                btc_condition_id: opportunity.btc_condition_id.clone(),
-                investment_amount: position_size,
-                units,
-                timestamp: std::time::Instant::now(),
-            };
-            pending.insert(trade_key, pending_trade);
-        }
-        drop(pending);
-        
+        let trade_key = trade_key_for(opportunity);
+        self.record_pending(trade_key, opportunity, units, position_size).await;
+
         let mut trades = self.trades_executed.lock().await;
         *trades += 1;
         let trades_count = *trades;
@@ -338,126 +1629,2152 @@ impl Trader {
         Ok(())
     }
 
-    async fn execute_real_trade(&self, opportunity: &ArbitrageOpportunity) -> Result<()> {
-        info!("🚀 PRODUCTION: Executing real arbitrage trade...");
-        
-        let position_size = self.calculate_position_size(opportunity);
-        let size_str = format!("{:.6}", position_size);
+    /// Halts the process on an authentication failure rather than
+    /// continuing to place orders that can never succeed with bad
+    /// credentials. Mirrors `PolymarketApi`'s failure-budget breach, which
+    /// exits for the same reason: every subsequent call will fail the same
+    /// way, so a supervisor restart (after the credentials are fixed) is the
+    /// only way forward - retrying or merely warning per-order just burns
+    /// cycles.
+    fn halt_on_unauthorized(&self, error: &ApiError) {
+        if let ApiError::Unauthorized { status, body } = error {
+            log::error!(
+                "🔒 Authentication failed (HTTP {}): {} - check that the configured api_key is valid and not expired; halting trading since every subsequent order will fail the same way",
+                status,
+                body
+            );
+            std::process::exit(1);
+        }
+    }
 
-        // Place order for SOL Up token
-        let sol_order = OrderRequest {
-            token_id: opportunity.sol_up_token_id.clone(),
-            side: "BUY".to_string(),
-            size: size_str.clone(),
-            price: opportunity.sol_up_price.to_string(),
-            order_type: "LIMIT".to_string(),
+    /// Compares a leg's realized average fill price against the price its
+    /// opportunity was detected at, warning when the slippage exceeds
+    /// `config.max_fill_slippage_pct` and, if `config.halt_trading_on_slippage_breach`
+    /// is also set, tripping `trading_halted` so `execute_arbitrage` stops
+    /// entering new trades. A no-op when slippage enforcement isn't
+    /// configured (`max_fill_slippage_pct` is `None`) - realized slippage is
+    /// still recorded on the trade separately, via `record_pending_with_fills`.
+    fn check_fill_slippage(&self, leg: &str, detection_price: Decimal, fill_price: Decimal) {
+        let Some(max_slippage) = self.config.max_fill_slippage_pct else {
+            return;
         };
+        if detection_price.is_zero() {
+            return;
+        }
 
-        // Place order for BTC Down token
-        let btc_order = OrderRequest {
-            token_id: opportunity.btc_down_token_id.clone(),
-            side: "BUY".to_string(),
-            size: size_str.clone(),
-            price: opportunity.btc_down_price.to_string(),
-            order_type: "LIMIT".to_string(),
-        };
+        let slippage_pct = f64::try_from((fill_price - detection_price) / detection_price).unwrap_or(0.0);
+        if slippage_pct <= max_slippage {
+            return;
+        }
 
-        // Execute both orders
-        let (sol_result, btc_result) = tokio::join!(
-            self.api.place_order(&sol_order),
-            self.api.place_order(&btc_order)
+        warn!(
+            "⚠️  {} leg filled at {} vs detected at {} ({:.2}% slippage, exceeds max_fill_slippage_pct {:.2}%)",
+            leg, fill_price, detection_price, slippage_pct * 100.0, max_slippage * 100.0
         );
 
-        match sol_result {
-            Ok(response) => {
-                info!("SOL Up order placed: {:?}", response);
+        if self.config.halt_trading_on_slippage_breach && !self.trading_halted.swap(true, Ordering::Relaxed) {
+            log::error!("🛑 Halting further trading: {} leg's fill slippage breached max_fill_slippage_pct", leg);
+        }
+    }
+
+    async fn execute_real_trade(&self, opportunity: &ArbitrageOpportunity) -> Result<()> {
+        info!("🚀 PRODUCTION: Executing real arbitrage trade...");
+
+        let (units, position_size) = self.calculate_units(opportunity).await;
+        if units <= 0.0 {
+            info!("⏭️  Skipping opportunity: rounded position below min_order_size");
+            return Ok(());
+        }
+
+        let slices = self.plan_order_slices(position_size);
+        if slices.len() > 1 {
+            info!("   ✂️  Splitting ${:.2} position into {} child orders of up to ${:.2} each",
+                  position_size, slices.len(), self.config.max_order_notional.unwrap_or(position_size));
+        }
+
+        if let Some(hedge) = self.calculate_hedge(opportunity, units) {
+            self.place_hedge_orders(&hedge).await;
+        }
+
+        let cost_per_unit = f64::try_from(opportunity.total_cost).unwrap_or(1.0);
+        // Units-weighted running sum of each leg's realized fill price
+        // across slices, folded into a single average passed to
+        // `record_pending_with_fills` below - mirrors how `units` and
+        // `position_size` already aggregate the whole batch of slices into
+        // one pending-trade update.
+        let mut sol_fill_weighted_sum = Decimal::ZERO;
+        let mut sol_fill_units = 0.0_f64;
+        let mut btc_fill_weighted_sum = Decimal::ZERO;
+        let mut btc_fill_units = 0.0_f64;
+        for (i, slice) in slices.iter().enumerate() {
+            let slice_units = slice / cost_per_unit;
+            let (sol_order, btc_order) = self.build_leg_orders(opportunity, slice_units);
+
+            if self.config.validate_orders_before_placement
+                && !self.both_legs_validate(&sol_order, &btc_order).await
+            {
+                warn!("   ⏭️  Skipping slice: one or both legs failed order validation");
+                continue;
             }
-            Err(e) => {
-                warn!("Failed to place SOL Up order: {}", e);
+
+            let (sol_result, btc_result) = if self.config.inter_leg_delay_max_ms > 0 {
+                let sol_result = self.api.place_order(&sol_order).await;
+
+                let delay_ms = (rand::random::<f64>() * self.config.inter_leg_delay_max_ms as f64) as u64;
+                if delay_ms > 0 {
+                    tokio::time::sleep(Duration::from_millis(delay_ms)).await;
+                }
+
+                if sol_result.is_ok() && !self.leg_price_still_within_tolerance(&opportunity.btc_down_token_id, opportunity.btc_down_price).await {
+                    warn!(
+                        "   ⚠️  BTC leg price moved during the {}ms inter-leg delay; placing anyway to avoid leaving the SOL leg unhedged",
+                        delay_ms
+                    );
+                }
+
+                let btc_result = self.api.place_order(&btc_order).await;
+                (sol_result, btc_result)
+            } else {
+                tokio::join!(self.api.place_order(&sol_order), self.api.place_order(&btc_order))
+            };
+
+            match sol_result {
+                Ok(response) => {
+                    info!("SOL Up order placed: {:?}", response);
+                    if let Some(fill_price) = response.avg_fill_price {
+                        self.check_fill_slippage("SOL Up", opportunity.sol_up_price, fill_price);
+                        sol_fill_weighted_sum += fill_price * Decimal::try_from(slice_units).unwrap_or_default();
+                        sol_fill_units += slice_units;
+                    }
+                }
+                Err(e) => {
+                    warn!("Failed to place SOL Up order: {}", e);
+                    self.halt_on_unauthorized(&e);
+                }
             }
-        }
 
-        match btc_result {
-            Ok(response) => {
-                info!("BTC Down order placed: {:?}", response);
+            match btc_result {
+                Ok(response) => {
+                    info!("BTC Down order placed: {:?}", response);
+                    if let Some(fill_price) = response.avg_fill_price {
+                        self.check_fill_slippage("BTC Down", opportunity.btc_down_price, fill_price);
+                        btc_fill_weighted_sum += fill_price * Decimal::try_from(slice_units).unwrap_or_default();
+                        btc_fill_units += slice_units;
+                    }
+                }
+                Err(e) => {
+                    warn!("Failed to place BTC Down order: {}", e);
+                    self.halt_on_unauthorized(&e);
+                }
             }
-            Err(e) => {
-                warn!("Failed to place BTC Down order: {}", e);
+
+            if i + 1 < slices.len() && self.config.order_split_delay_ms > 0 {
+                tokio::time::sleep(Duration::from_millis(self.config.order_split_delay_ms)).await;
             }
         }
 
-        // Track the trade so we can sell tokens when markets close
-        let cost_per_unit = f64::try_from(opportunity.total_cost).unwrap_or(1.0);
-        let units = position_size / cost_per_unit;
-        
+        let sol_fill_price = (sol_fill_units > 0.0)
+            .then(|| sol_fill_weighted_sum / Decimal::try_from(sol_fill_units).unwrap_or(Decimal::ONE));
+        let btc_fill_price = (btc_fill_units > 0.0)
+            .then(|| btc_fill_weighted_sum / Decimal::try_from(btc_fill_units).unwrap_or(Decimal::ONE));
+
+        // A slice that failed validation (or where one leg's order errored)
+        // never actually entered a matched position - record only the units
+        // both legs genuinely filled, not the pre-slicing `units`/
+        // `position_size` target, or settlement/deployed-capital accounting
+        // would be inflated by capital that was never actually risked.
+        let entered_units = sol_fill_units.min(btc_fill_units);
+        if entered_units <= 0.0 {
+            warn!("⏭️  No slice was both validated and filled for either leg; nothing to record for this opportunity");
+            return Ok(());
+        }
+        let entered_position_size = entered_units * cost_per_unit;
+
+        if let Some(hedge) = self.calculate_hedge(opportunity, entered_units) {
+            self.place_hedge_orders(&hedge).await;
+        }
+
         // Use condition IDs as key - accumulate multiple trades in the same period
-        let trade_key = format!("{}_{}", opportunity.sol_condition_id, opportunity.btc_condition_id);
-        
+        let trade_key = trade_key_for(opportunity);
+        self.record_pending_with_fills(trade_key, opportunity, entered_units, entered_position_size, sol_fill_price, btc_fill_price).await;
+
+        let mut trades = self.trades_executed.lock().await;
+        *trades += 1;
+        let trades_count = *trades;
+        drop(trades);
+
+        info!(
+            "✅ Real Trade Executed - Investment: ${:.2} | Expected Profit: ${:.4} | Trades: {}",
+            entered_position_size,
+            f64::try_from(opportunity.expected_profit).unwrap_or(0.0) * entered_units,
+            trades_count
+        );
+
+        Ok(())
+    }
+
+    /// Record `units`/`investment_amount` for a trade under `trade_key`,
+    /// accumulating into an existing pending trade for the same period
+    /// instead of overwriting it, or creating a fresh entry if this is the
+    /// first trade seen for that key. Shared by `simulate_trade` and
+    /// `execute_real_trade` so the two paths can't drift apart.
+    ///
+    /// `investment_amount` is the raw position size before entry fees;
+    /// `taker_fee_bps` is folded in here so it's charged identically for
+    /// both paths and flows through the existing settlement math as part of
+    /// `investment_amount`, without touching `settlement_profit`'s signature.
+    async fn record_pending(
+        &self,
+        trade_key: String,
+        opportunity: &ArbitrageOpportunity,
+        units: f64,
+        investment_amount: f64,
+    ) {
+        self.record_pending_with_fills(trade_key, opportunity, units, investment_amount, None, None).await;
+    }
+
+    /// Same as `record_pending`, but additionally threads the realized
+    /// average fill price for each leg (when known) onto the trade, for
+    /// `check_fill_slippage` and for later analysis via `TradeLogEntry`.
+    /// Simulation has no real fills and so always goes through
+    /// `record_pending`, which passes `None` for both; `execute_real_trade`
+    /// calls this directly with whatever the order responses reported.
+    ///
+    /// When accumulating into an existing pending trade, the new fill price
+    /// is folded into a running units-weighted average rather than
+    /// overwritten, matching how `units`/`investment_amount` already
+    /// accumulate.
+    async fn record_pending_with_fills(
+        &self,
+        trade_key: String,
+        opportunity: &ArbitrageOpportunity,
+        units: f64,
+        investment_amount: f64,
+        sol_fill_price: Option<Decimal>,
+        btc_fill_price: Option<Decimal>,
+    ) {
+        let entry_fee = entry_fee_for_position(investment_amount, self.config.taker_fee_bps);
+        let investment_amount = investment_amount + entry_fee;
+        if entry_fee > 0.0 {
+            info!("   💸 Entry fee: ${:.4} ({:.1} bps on ${:.2})", entry_fee, self.config.taker_fee_bps, investment_amount - entry_fee);
+        }
+
+        let hedge = self.calculate_hedge(opportunity, units);
+        let hedge_investment_amount = hedge.as_ref().map(|h| h.investment_amount).unwrap_or(0.0);
+
+        *self.total_deployed.lock().await += investment_amount + hedge_investment_amount;
+        let trade_expected_profit = f64::try_from(opportunity.expected_profit).unwrap_or(0.0) * units;
+
         let mut pending = self.pending_trades.lock().await;
-        
-        // If we already have a trade for this period, accumulate it (add units and investment)
+
         if let Some(existing_trade) = pending.get_mut(&trade_key) {
-            // Accumulate: add new units and investment to existing trade
+            if existing_trade.settling {
+                warn!("   ⚠️  Trade {} is already settling, not accumulating new units into it",
+                      existing_trade.trade_id);
+                drop(pending);
+                return;
+            }
+            let existing_units = existing_trade.units;
+            existing_trade.sol_fill_price = weighted_average_fill_price(existing_trade.sol_fill_price, existing_units, sol_fill_price, units);
+            existing_trade.btc_fill_price = weighted_average_fill_price(existing_trade.btc_fill_price, existing_units, btc_fill_price, units);
             existing_trade.units += units;
-            existing_trade.investment_amount += position_size;
-            info!("   📊 Accumulated trade: Total units: {:.2}, Total investment: ${:.2}", 
+            existing_trade.investment_amount += investment_amount;
+            existing_trade.expected_profit += trade_expected_profit;
+            existing_trade.hedge = accumulate_hedge(existing_trade.hedge.take(), hedge);
+            info!("   📊 Accumulated trade: Total units: {:.2}, Total investment: ${:.2}",
                   existing_trade.units, existing_trade.investment_amount);
         } else {
-            // First trade for this period - create new entry
             let pending_trade = PendingTrade {
+                trade_id: self.new_trade_id(),
                 sol_token_id: opportunity.sol_up_token_id.clone(),
                 btc_token_id: opportunity.btc_down_token_id.clone(),
                 sol_condition_id: opportunity.sol_condition_id.clone(),
                 btc_condition_id: opportunity.btc_condition_id.clone(),
-                investment_amount: position_size,
+                investment_amount,
                 units,
-                timestamp: std::time::Instant::now(),
+                timestamp: unix_now_secs(),
+                entry_sol_price: opportunity.sol_up_price,
+                entry_btc_price: opportunity.btc_down_price,
+                sol_fill_price,
+                btc_fill_price,
+                strategy: opportunity.strategy.clone(),
+                expected_profit: trade_expected_profit,
+                settling: false,
+                hedge,
             };
+            info!("   🆔 Trade ID: {} (entry SOL: ${}, entry BTC: ${})",
+                  pending_trade.trade_id, pending_trade.entry_sol_price, pending_trade.entry_btc_price);
             pending.insert(trade_key, pending_trade);
         }
         drop(pending);
-        
-        let mut trades = self.trades_executed.lock().await;
-        *trades += 1;
-        let trades_count = *trades;
-        drop(trades);
 
-        info!(
-            "✅ Real Trade Executed - Investment: ${:.2} | Expected Profit: ${:.4} | Trades: {}",
-            position_size,
-            f64::try_from(opportunity.expected_profit).unwrap_or(0.0) * units,
-            trades_count
+        let mut strategy_stats = self.strategy_stats.lock().await;
+        strategy_stats.entry(opportunity.strategy.clone()).or_default().trades_executed += 1;
+    }
+
+    /// Calculate the units to buy and the resulting investment amount for an
+    /// opportunity, applying whole-unit rounding when configured.
+    ///
+    /// Returns `(units, investment_amount)`. If `round_units_to_whole` is set
+    /// and flooring drops the trade below `min_order_size`, returns `(0.0, 0.0)`
+    /// to signal the opportunity should be skipped.
+    async fn calculate_units(&self, opportunity: &ArbitrageOpportunity) -> (f64, f64) {
+        let Some(cost_per_unit) = viable_cost_per_unit(opportunity.total_cost) else {
+            warn!("⏭️  Skipping opportunity: total_cost {} is too small or invalid to size a position", opportunity.total_cost);
+            return (0.0, 0.0);
+        };
+
+        let position_size = self.calculate_position_size(opportunity).await;
+        let units = position_size / cost_per_unit;
+
+        if !self.config.round_units_to_whole {
+            return (units, position_size);
+        }
+
+        let whole_units = units.floor();
+        if whole_units < self.config.min_order_size {
+            return (0.0, 0.0);
+        }
+
+        // Recompute investment from the floored units - this can only shrink
+        // the position, so it can never exceed max_position_size.
+        (whole_units, whole_units * cost_per_unit)
+    }
+
+    /// Plan the child order slices for a target position, splitting it into
+    /// chunks of at most `max_order_notional` when splitting is enabled.
+    fn plan_order_slices(&self, total_position: f64) -> Vec<f64> {
+        match self.config.max_order_notional {
+            Some(max_notional) if self.config.enable_order_splitting && max_notional > 0.0 => {
+                split_notional(total_position, max_notional)
+            }
+            _ => vec![total_position],
+        }
+    }
+
+    /// Dry-run validate both legs of a slice against the CLOB before either
+    /// is placed, so a rejection (price off tick, below min size, market not
+    /// accepting orders, insufficient balance) is caught without leaving one
+    /// leg filled and the other stranded. A validation call that itself
+    /// errors (network, parse) is treated as a failed validation - safer to
+    /// skip the slice than to place orders we couldn't confirm are sound.
+    async fn both_legs_validate(&self, sol_order: &OrderRequest, btc_order: &OrderRequest) -> bool {
+        let (sol_validation, btc_validation) = tokio::join!(
+            self.api.validate_order(sol_order),
+            self.api.validate_order(btc_order)
         );
 
-        Ok(())
+        match (sol_validation, btc_validation) {
+            (Ok(sol), Ok(btc)) => {
+                if !sol.valid {
+                    warn!("   ❌ SOL leg failed validation: {}", sol.reason.as_deref().unwrap_or("no reason given"));
+                }
+                if !btc.valid {
+                    warn!("   ❌ BTC leg failed validation: {}", btc.reason.as_deref().unwrap_or("no reason given"));
+                }
+                sol.valid && btc.valid
+            }
+            (sol_result, btc_result) => {
+                if let Err(e) = sol_result {
+                    warn!("   ❌ SOL leg validation request failed: {}", e);
+                }
+                if let Err(e) = btc_result {
+                    warn!("   ❌ BTC leg validation request failed: {}", e);
+                }
+                false
+            }
+        }
+    }
+
+    /// Build the two leg orders for an opportunity, buying the *same number
+    /// of units* on both legs so each leg resolves to a matching $1 payout,
+    /// even though unequal prices mean unequal dollar amounts per leg. This
+    /// is an extraction of logic that already passed the same size to both
+    /// legs before both callers shared this function - it doesn't change
+    /// the sizing itself.
+    fn build_leg_orders(
+        &self,
+        opportunity: &ArbitrageOpportunity,
+        units: f64,
+    ) -> (OrderRequest, OrderRequest) {
+        let size_decimal = Decimal::from_f64_retain(units).unwrap_or_default();
+        let size_str = order_format::format_size(size_decimal, self.config.size_lot_size, self.config.price_rounding_mode);
+
+        let sol_order = OrderRequest {
+            token_id: opportunity.sol_up_token_id.clone(),
+            side: "BUY".to_string(),
+            size: size_str.clone(),
+            price: order_format::format_price(
+                opportunity.sol_up_price,
+                self.config.price_tick_size,
+                OrderSide::Buy,
+                self.config.price_rounding_mode,
+            ),
+            order_type: "LIMIT".to_string(),
+            time_in_force: self.config.order_time_in_force.clone(),
+            funder: None,
+        };
+
+        let btc_order = OrderRequest {
+            token_id: opportunity.btc_down_token_id.clone(),
+            side: "BUY".to_string(),
+            size: size_str,
+            price: order_format::format_price(
+                opportunity.btc_down_price,
+                self.config.price_tick_size,
+                OrderSide::Buy,
+                self.config.price_rounding_mode,
+            ),
+            order_type: "LIMIT".to_string(),
+            time_in_force: self.config.order_time_in_force.clone(),
+            funder: None,
+        };
+
+        (sol_order, btc_order)
+    }
+
+    /// Best-effort BUY of both legs of `hedge`. Unlike the main trade's legs,
+    /// a hedge leg that fails to fill doesn't strand anything - it was funded
+    /// out of the trade's expected profit rather than principal, so it's
+    /// logged and skipped rather than retried or allowed to block/unwind the
+    /// main trade, which has already been placed by the time this runs.
+    async fn place_hedge_orders(&self, hedge: &HedgeLeg) {
+        let size_decimal = Decimal::from_f64_retain(hedge.units).unwrap_or_default();
+        let size_str = order_format::format_size(size_decimal, self.config.size_lot_size, self.config.price_rounding_mode);
+
+        let sol_order = OrderRequest {
+            token_id: hedge.sol_token_id.clone(),
+            side: "BUY".to_string(),
+            size: size_str.clone(),
+            price: order_format::format_price(hedge.sol_price, self.config.price_tick_size, OrderSide::Buy, self.config.price_rounding_mode),
+            order_type: "LIMIT".to_string(),
+            time_in_force: self.config.order_time_in_force.clone(),
+            funder: None,
+        };
+        let btc_order = OrderRequest {
+            token_id: hedge.btc_token_id.clone(),
+            side: "BUY".to_string(),
+            size: size_str,
+            price: order_format::format_price(hedge.btc_price, self.config.price_tick_size, OrderSide::Buy, self.config.price_rounding_mode),
+            order_type: "LIMIT".to_string(),
+            time_in_force: self.config.order_time_in_force.clone(),
+            funder: None,
+        };
+
+        let (sol_result, btc_result) = tokio::join!(self.api.place_order(&sol_order), self.api.place_order(&btc_order));
+        if let Err(e) = sol_result {
+            warn!("   ⚠️  Tail hedge SOL leg failed to place: {}", e);
+        }
+        if let Err(e) = btc_result {
+            warn!("   ⚠️  Tail hedge BTC leg failed to place: {}", e);
+        }
     }
 
-    fn calculate_position_size(&self, opportunity: &ArbitrageOpportunity) -> f64 {
+    async fn calculate_position_size(&self, opportunity: &ArbitrageOpportunity) -> f64 {
         // Position size is the total dollar amount to invest in this arbitrage opportunity
         // We use max_position_size from config as the maximum investment per trade
         let max_size = self.config.max_position_size;
-        let cost_per_unit = f64::try_from(opportunity.total_cost).unwrap_or(1.0);
-        
+        let Some(cost_per_unit) = viable_cost_per_unit(opportunity.total_cost) else {
+            return 0.0;
+        };
+
         // Calculate how many "units" (pairs of tokens) we can buy with max position size
         // Each unit costs total_cost (e.g., $0.75), so with $100 we can buy 100/0.75 = 133.33 units
         let units = max_size / cost_per_unit;
-        
+
         // The actual position size is: units * cost_per_unit
         // But we cap it at max_size to not exceed our limit
-        let position_size = (units * cost_per_unit).min(max_size);
-        
+        let mut position_size = (units * cost_per_unit).min(max_size);
+        let mut binding_constraint = "max_position_size";
+
         // For example:
         // - If total_cost = $0.75 and max_size = $100
         // - units = 100 / 0.75 = 133.33
         // - position_size = 133.33 * 0.75 = $100 (capped at max_size)
         // - This means we buy $100 worth of tokens total ($50 SOL Up + $50 BTC Down)
+
+        if let Some(depth_units) = self.depth_fraction_unit_cap(opportunity).await {
+            let depth_position_size = depth_units * cost_per_unit;
+            if depth_position_size < position_size {
+                position_size = depth_position_size.max(0.0);
+                binding_constraint = "max_depth_fraction";
+            }
+        }
+
+        debug!("   📏 Position size ${:.2}, binding constraint: {}", position_size, binding_constraint);
         position_size
     }
 
-    pub async fn get_stats(&self) -> (f64, u64) {
-        let total = *self.total_profit.lock().await;
-        let trades = *self.trades_executed.lock().await;
-        (total, trades)
-    }
+    /// Units cap implied by `config.max_depth_fraction` of each leg's
+    /// resting orderbook depth available at or below its opportunity price -
+    /// the smaller of the two legs' caps, since both legs buy the same
+    /// number of units. Returns `None` when the check is disabled, or when
+    /// either leg's orderbook can't be fetched (a stale/unreachable book
+    /// shouldn't itself block the trade, so the cap is simply skipped for
+    /// that opportunity rather than treated as zero depth).
+    async fn depth_fraction_unit_cap(&self, opportunity: &ArbitrageOpportunity) -> Option<f64> {
+        let fraction = self.config.max_depth_fraction?;
+
+        let (sol_book, btc_book) = tokio::join!(
+            self.api.get_orderbook(&opportunity.sol_up_token_id),
+            self.api.get_orderbook(&opportunity.btc_down_token_id)
+        );
+
+        let sol_book = match sol_book {
+            Ok(book) => book,
+            Err(e) => {
+                warn!("   ⚠️  Failed to fetch SOL orderbook for depth cap: {}", e);
+                return None;
+            }
+        };
+        let btc_book = match btc_book {
+            Ok(book) => book,
+            Err(e) => {
+                warn!("   ⚠️  Failed to fetch BTC orderbook for depth cap: {}", e);
+                return None;
+            }
+        };
+
+        let sol_units = available_ask_depth(&sol_book, opportunity.sol_up_price) * fraction;
+        let btc_units = available_ask_depth(&btc_book, opportunity.btc_down_price) * fraction;
+        Some(sol_units.min(btc_units))
+    }
+
+    /// Sizes an optional tail hedge in `opportunity`'s opposing outcome
+    /// combination for a trade of `main_units`, spending
+    /// `config.tail_hedge_fraction` of the trade's dollar expected profit
+    /// rather than any of its principal - see
+    /// `TradingConfig::tail_hedge_fraction`. Returns `None` when hedging is
+    /// disabled, the trade has no expected edge to fund it from, the
+    /// opportunity carries no `hedge_candidate` (the opposing pair's prices
+    /// weren't both available at detection), or the opposing pair's combined
+    /// cost isn't viable to size a position against.
+    fn calculate_hedge(&self, opportunity: &ArbitrageOpportunity, main_units: f64) -> Option<HedgeLeg> {
+        let fraction = self.config.tail_hedge_fraction?;
+        if fraction <= 0.0 {
+            return None;
+        }
+        let candidate = opportunity.hedge_candidate.as_ref()?;
+
+        let trade_expected_profit = f64::try_from(opportunity.expected_profit).unwrap_or(0.0) * main_units;
+        if trade_expected_profit <= 0.0 {
+            return None;
+        }
+
+        let hedge_investment_amount = trade_expected_profit * fraction;
+        let cost_per_unit = viable_cost_per_unit(candidate.sol_price + candidate.btc_price)?;
+        let units = hedge_investment_amount / cost_per_unit;
+
+        Some(HedgeLeg {
+            sol_token_id: candidate.sol_token_id.clone(),
+            btc_token_id: candidate.btc_token_id.clone(),
+            sol_price: candidate.sol_price,
+            btc_price: candidate.btc_price,
+            units,
+            investment_amount: hedge_investment_amount,
+        })
+    }
+
+    pub async fn get_stats(&self) -> (f64, u64) {
+        let total = *self.total_profit.lock().await;
+        let trades = *self.trades_executed.lock().await;
+        (total, trades)
+    }
+
+    /// Cumulative capital ever deployed across all trades, for comparing
+    /// against `config.max_lifetime_deployed` or surfacing in a heartbeat.
+    pub async fn total_deployed(&self) -> f64 {
+        *self.total_deployed.lock().await
+    }
+
+    /// Number of distinct trade keys currently awaiting settlement.
+    pub async fn pending_trade_count(&self) -> usize {
+        self.pending_trades.lock().await.len()
+    }
+
+    /// Per-strategy breakdown of profit, trade count, and win rate, keyed by
+    /// strategy name (see `arbitrage::STRATEGY_*`). Lets an operator see
+    /// which strategy is actually making money before deciding to disable
+    /// one via `ArbitrageDetector::set_strategy_enabled`.
+    pub async fn get_detailed_stats(&self) -> HashMap<String, StrategyStats> {
+        self.strategy_stats.lock().await.clone()
+    }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::PolymarketApi;
+    use crate::arbitrage::{STRATEGY_SOL_DOWN_BTC_UP, STRATEGY_SOL_UP_BTC_DOWN};
+    use rust_decimal_macros::dec;
+
+    fn make_opportunity(sol_price: Decimal, btc_price: Decimal) -> ArbitrageOpportunity {
+        let total_cost = sol_price + btc_price;
+        let expected_profit = dec!(1.0) - total_cost;
+        ArbitrageOpportunity {
+            sol_up_price: sol_price,
+            btc_down_price: btc_price,
+            total_cost,
+            expected_profit,
+            sol_up_token_id: "sol-token".to_string(),
+            btc_down_token_id: "btc-token".to_string(),
+            sol_condition_id: "sol-cond".to_string(),
+            btc_condition_id: "btc-cond".to_string(),
+            is_midpoint_derived: false,
+            strategy: crate::arbitrage::STRATEGY_SOL_UP_BTC_DOWN.to_string(),
+            // checked_div rather than `/` so a zero total_cost fixture (used
+            // to test calculate_units/calculate_position_size's viability
+            // guard) doesn't panic building the opportunity itself.
+            breakeven_price_move_pct: expected_profit.checked_div(total_cost).unwrap_or(dec!(0)),
+            hedge_candidate: None,
+        }
+    }
+
+    fn make_opportunity_with_hedge(sol_price: Decimal, btc_price: Decimal, hedge: HedgeCandidate) -> ArbitrageOpportunity {
+        ArbitrageOpportunity {
+            hedge_candidate: Some(hedge),
+            ..make_opportunity(sol_price, btc_price)
+        }
+    }
+
+    fn trader_with_config(config: TradingConfig) -> Trader {
+        let api = Arc::new(PolymarketApi::new(
+            "https://gamma.example".to_string(),
+            "https://clob.example".to_string(),
+            None,
+        ));
+        Trader::new(api, config, true, None, None, None)
+    }
+
+    fn base_config() -> TradingConfig {
+        TradingConfig {
+            min_profit_threshold: 0.01,
+            max_position_size: 100.0,
+            max_depth_fraction: None,
+            min_book_depth: 0.0,
+            sol_condition_id: None,
+            btc_condition_id: None,
+            check_interval_ms: 1000,
+            round_units_to_whole: false,
+            min_order_size: 5.0,
+            redemption_cost_estimate: 0.0,
+            require_profit_above_redemption_cost: false,
+            max_order_notional: None,
+            enable_order_splitting: false,
+            order_split_delay_ms: 0,
+            inter_leg_delay_max_ms: 0,
+            token_cache_path: None,
+            up_outcome_keywords: vec!["UP".to_string(), "1".to_string()],
+            down_outcome_keywords: vec!["DOWN".to_string(), "0".to_string()],
+            late_profit_threshold: None,
+            log_profit_threshold: None,
+            adversarial_loss_probability: None,
+            period_duration_secs: 900,
+            last_look_tolerance: 0.0,
+            price_ema_alpha: 0.3,
+            require_smoothed_confirmation: false,
+            heartbeat_interval_ms: 60_000,
+            trust_midpoint_for_execution: false,
+            price_history_len: 200,
+            min_total_cost: 0.0,
+            max_total_cost: 1.0,
+            order_time_in_force: "GTC".to_string(),
+            period_boundary_tolerance_secs: 30,
+            validate_orders_before_placement: false,
+            min_sane_price: 0.001,
+            max_sane_price: 0.999,
+            price_inversion_policy: crate::monitor::PriceInversionPolicy::default(),
+            price_source_preference: crate::monitor::PriceSourcePreference::default(),
+            max_consecutive_price_failures: None,
+            cross_check_source: None,
+            cross_check_tolerance_pct: 0.10,
+            skip_trading_on_cross_check_mismatch: false,
+            trading_windows: Vec::new(),
+            max_lifetime_deployed: None,
+            allow_non_50_50_markets: false,
+            leg_combinations: vec![
+                crate::config::LegCombination {
+                    sol_outcome: "Up".to_string(),
+                    btc_outcome: "Down".to_string(),
+                },
+                crate::config::LegCombination {
+                    sol_outcome: "Down".to_string(),
+                    btc_outcome: "Up".to_string(),
+                },
+            ],
+            trade_sample_rate: 1.0,
+            trade_sample_seed: None,
+            post_rollover_grace_ms: 0,
+            sol_enabled: true,
+            btc_enabled: true,
+            settlement_check_interval_ms: 30_000,
+            settlement_idle_check_interval_ms: 300_000,
+            discovery_check_interval_ms: 60_000,
+            taker_fee_bps: 0.0,
+            market_cache_max_age_secs: 3600,
+            enable_sol_up_btc_down: true,
+            enable_sol_down_btc_up: true,
+            last_trade_price_band_pct: None,
+            shutdown_settlement_wait_secs: None,
+            settlement_concurrency: 4,
+            max_fill_slippage_pct: None,
+            halt_trading_on_slippage_breach: false,
+            watchdog_stall_threshold_secs: None,
+            tail_hedge_fraction: None,
+            shared_state_path: None,
+            max_shared_deployed: None,
+            shared_state_lock_timeout_ms: 5000,
+            price_tick_size: dec!(0.01),
+            size_lot_size: dec!(0.000001),
+            price_rounding_mode: crate::order_format::RoundingMode::default(),
+        }
+    }
+
+    #[tokio::test]
+    async fn fractional_units_by_default() {
+        let trader = trader_with_config(base_config());
+        let opportunity = make_opportunity(dec!(0.40), dec!(0.35));
+        let (units, investment) = trader.calculate_units(&opportunity).await;
+        assert!((units - 133.333_333).abs() < 0.001);
+        assert!((investment - 100.0).abs() < 0.001);
+    }
+
+    #[tokio::test]
+    async fn whole_unit_rounding_never_exceeds_the_position_cap() {
+        let mut config = base_config();
+        config.round_units_to_whole = true;
+        let trader = trader_with_config(config);
+
+        let opportunity = make_opportunity(dec!(0.40), dec!(0.35));
+        let (units, investment) = trader.calculate_units(&opportunity).await;
+
+        assert_eq!(units, 133.0);
+        assert!(investment <= 100.0);
+        assert!((investment - 133.0 * 0.75).abs() < 0.001);
+    }
+
+    #[tokio::test]
+    async fn leg_orders_buy_equal_units_despite_unequal_prices() {
+        let trader = trader_with_config(base_config());
+        // Very unequal leg prices: 0.90 + 0.05 = 0.95 total cost.
+        let opportunity = make_opportunity(dec!(0.90), dec!(0.05));
+        let (units, _) = trader.calculate_units(&opportunity).await;
+
+        let (sol_order, btc_order) = trader.build_leg_orders(&opportunity, units);
+
+        assert_eq!(sol_order.size, btc_order.size);
+        assert_ne!(sol_order.price, btc_order.price);
+    }
+
+    #[tokio::test]
+    async fn leg_orders_default_to_gtc_time_in_force() {
+        let trader = trader_with_config(base_config());
+        let opportunity = make_opportunity(dec!(0.40), dec!(0.35));
+        let (units, _) = trader.calculate_units(&opportunity).await;
+
+        let (sol_order, btc_order) = trader.build_leg_orders(&opportunity, units);
+
+        assert_eq!(sol_order.time_in_force, "GTC");
+        assert_eq!(btc_order.time_in_force, "GTC");
+    }
+
+    #[tokio::test]
+    async fn leg_orders_use_the_configured_time_in_force() {
+        let mut config = base_config();
+        config.order_time_in_force = "FOK".to_string();
+        let trader = trader_with_config(config);
+        let opportunity = make_opportunity(dec!(0.40), dec!(0.35));
+        let (units, _) = trader.calculate_units(&opportunity).await;
+
+        let (sol_order, btc_order) = trader.build_leg_orders(&opportunity, units);
+
+        assert_eq!(sol_order.time_in_force, "FOK");
+        assert_eq!(btc_order.time_in_force, "FOK");
+    }
+
+    #[tokio::test]
+    async fn leg_orders_round_buy_prices_up_to_the_worse_tick() {
+        let mut config = base_config();
+        config.price_tick_size = dec!(0.01);
+        let trader = trader_with_config(config);
+        let opportunity = make_opportunity(dec!(0.451), dec!(0.352));
+        let (units, _) = trader.calculate_units(&opportunity).await;
+
+        let (sol_order, btc_order) = trader.build_leg_orders(&opportunity, units);
+
+        assert_eq!(sol_order.price, "0.46");
+        assert_eq!(btc_order.price, "0.36");
+    }
+
+    #[tokio::test]
+    async fn leg_orders_round_sizes_down_to_the_lot() {
+        let mut config = base_config();
+        config.size_lot_size = dec!(0.01);
+        let trader = trader_with_config(config);
+        let opportunity = make_opportunity(dec!(0.40), dec!(0.35));
+        let (units, _) = trader.calculate_units(&opportunity).await;
+
+        let (sol_order, btc_order) = trader.build_leg_orders(&opportunity, units + 0.0049);
+
+        let size: f64 = sol_order.size.parse().unwrap();
+        assert!(size <= units + 0.0049);
+        assert_eq!(sol_order.size, btc_order.size);
+    }
+
+    #[tokio::test]
+    async fn whole_unit_rounding_skips_dust_below_min_order_size() {
+        let mut config = base_config();
+        config.round_units_to_whole = true;
+        config.max_position_size = 4.0; // total_cost of 0.9 -> floor(4.44) = 4 units < min 5
+        config.min_order_size = 5.0;
+        let trader = trader_with_config(config);
+
+        let opportunity = make_opportunity(dec!(0.50), dec!(0.40));
+        let (units, investment) = trader.calculate_units(&opportunity).await;
+
+        assert_eq!(units, 0.0);
+        assert_eq!(investment, 0.0);
+    }
+
+    #[tokio::test]
+    async fn calculate_units_rejects_a_zero_total_cost_instead_of_dividing_by_it() {
+        let trader = trader_with_config(base_config());
+
+        // A zero-priced opportunity (bad quote, corrupt decimal) must never
+        // size a position - dividing max_size by zero would otherwise
+        // explode toward infinity/NaN.
+        let opportunity = make_opportunity(dec!(0.0), dec!(0.0));
+        let (units, investment) = trader.calculate_units(&opportunity).await;
+
+        assert_eq!(units, 0.0);
+        assert_eq!(investment, 0.0);
+    }
+
+    #[tokio::test]
+    async fn calculate_units_rejects_a_total_cost_below_the_viability_epsilon() {
+        let trader = trader_with_config(base_config());
+
+        // Not literally zero, but small enough that it's not a real price -
+        // still needs to be rejected rather than sized against.
+        let opportunity = make_opportunity(dec!(0.0000001), dec!(0.0000001));
+        let (units, investment) = trader.calculate_units(&opportunity).await;
+
+        assert_eq!(units, 0.0);
+        assert_eq!(investment, 0.0);
+    }
+
+    #[tokio::test]
+    async fn calculate_position_size_is_zero_for_a_zero_or_tiny_total_cost() {
+        let trader = trader_with_config(base_config());
+
+        let zero_cost = make_opportunity(dec!(0.0), dec!(0.0));
+        assert_eq!(trader.calculate_position_size(&zero_cost).await, 0.0);
+
+        let tiny_cost = make_opportunity(dec!(0.0000001), dec!(0.0000001));
+        assert_eq!(trader.calculate_position_size(&tiny_cost).await, 0.0);
+    }
+
+    fn make_pending_trade(units: f64, investment_amount: f64) -> PendingTrade {
+        PendingTrade {
+            trade_id: "trade-1".to_string(),
+            sol_token_id: "sol-token".to_string(),
+            btc_token_id: "btc-token".to_string(),
+            sol_condition_id: "sol-cond".to_string(),
+            btc_condition_id: "btc-cond".to_string(),
+            investment_amount,
+            units,
+            timestamp: unix_now_secs(),
+            entry_sol_price: dec!(0.5),
+            entry_btc_price: dec!(0.4),
+            sol_fill_price: None,
+            btc_fill_price: None,
+            strategy: crate::arbitrage::STRATEGY_SOL_UP_BTC_DOWN.to_string(),
+            expected_profit: 0.0,
+            settling: false,
+            hedge: None,
+        }
+    }
+
+    fn dummy_market_details(closed: bool, tokens: Vec<MarketToken>) -> MarketDetails {
+        MarketDetails {
+            accepting_order_timestamp: None,
+            accepting_orders: !closed,
+            active: !closed,
+            archived: false,
+            closed,
+            condition_id: "cond".to_string(),
+            description: String::new(),
+            enable_order_book: true,
+            end_date_iso: String::new(),
+            fpmm: String::new(),
+            game_start_time: None,
+            icon: String::new(),
+            image: String::new(),
+            is_50_50_outcome: true,
+            maker_base_fee: Decimal::ZERO,
+            market_slug: "market".to_string(),
+            minimum_order_size: Decimal::ONE,
+            minimum_tick_size: dec!(0.01),
+            neg_risk: false,
+            neg_risk_market_id: String::new(),
+            neg_risk_request_id: String::new(),
+            notifications_enabled: true,
+            question: String::new(),
+            question_id: String::new(),
+            rewards: Rewards {
+                max_spread: Decimal::ZERO,
+                min_size: Decimal::ZERO,
+                rates: None,
+            },
+            seconds_delay: 0,
+            tags: Vec::new(),
+            taker_base_fee: Decimal::ZERO,
+            tokens,
+        }
+    }
+
+    #[test]
+    fn leg_result_for_token_reports_won_and_lost_from_a_normal_resolution() {
+        let market = dummy_market_details(
+            true,
+            vec![
+                MarketToken { outcome: "Up".to_string(), price: dec!(1.0), token_id: "up".to_string(), winner: true },
+                MarketToken { outcome: "Down".to_string(), price: dec!(0.0), token_id: "down".to_string(), winner: false },
+            ],
+        );
+
+        assert_eq!(leg_result_for_token(&market, "up"), LegResult::Won);
+        assert_eq!(leg_result_for_token(&market, "down"), LegResult::Lost);
+    }
+
+    #[test]
+    fn leg_result_for_token_reports_invalid_when_no_token_won() {
+        let market = dummy_market_details(
+            true,
+            vec![
+                MarketToken { outcome: "Up".to_string(), price: dec!(0.5), token_id: "up".to_string(), winner: false },
+                MarketToken { outcome: "Down".to_string(), price: dec!(0.5), token_id: "down".to_string(), winner: false },
+            ],
+        );
+
+        assert_eq!(leg_result_for_token(&market, "up"), LegResult::Invalid);
+        assert_eq!(leg_result_for_token(&market, "down"), LegResult::Invalid);
+    }
+
+    #[tokio::test]
+    async fn check_market_result_cached_retries_rather_than_booking_a_loss_when_tokens_are_not_yet_populated() {
+        let trader = trader_with_config(base_config());
+        let market = dummy_market_details(true, vec![]);
+        trader.market_cache.lock().await.insert(
+            "condition-1".to_string(),
+            CachedMarketData { market, cached_at: unix_now_secs() },
+        );
+
+        let (closed, result) = trader.check_market_result_cached("condition-1", "up").await.unwrap();
+
+        // A closed market with no tokens yet populated is a transient CLOB
+        // response state, not a genuine loss - `closed` must stay false so
+        // the caller retries instead of booking `result`.
+        assert!(!closed);
+        assert_eq!(result, LegResult::Lost);
+    }
+
+    #[tokio::test]
+    async fn check_market_result_cached_retries_rather_than_booking_a_loss_when_our_token_is_missing() {
+        let trader = trader_with_config(base_config());
+        let market = dummy_market_details(
+            true,
+            vec![
+                MarketToken { outcome: "Up".to_string(), price: dec!(1.0), token_id: "some-other-token".to_string(), winner: true },
+                MarketToken { outcome: "Down".to_string(), price: dec!(0.0), token_id: "yet-another-token".to_string(), winner: false },
+            ],
+        );
+        trader.market_cache.lock().await.insert(
+            "condition-1".to_string(),
+            CachedMarketData { market, cached_at: unix_now_secs() },
+        );
+
+        let (closed, result) = trader.check_market_result_cached("condition-1", "up").await.unwrap();
+
+        assert!(!closed);
+        assert_eq!(result, LegResult::Lost);
+    }
+
+    #[test]
+    fn redemption_cost_is_deducted_once_per_settlement() {
+        let mut config = base_config();
+        config.redemption_cost_estimate = 2.0;
+        let trader = trader_with_config(config);
+
+        let trade = make_pending_trade(100.0, 90.0);
+        // Both legs win and are confirmed sold: payout = 2.0 * 100 units = 200, profit = 200 - 90 - 2 = 108
+        let (profit, fully_realized) = trader.calculate_actual_profit(&trade, LegResult::Won, LegResult::Won, true, true);
+        assert!((profit - 108.0).abs() < 0.001);
+        assert!(fully_realized);
+    }
+
+    #[test]
+    fn settlement_self_test_passes_against_the_current_settlement_math() {
+        assert!(run_settlement_self_test().is_ok());
+    }
+
+    #[test]
+    fn unconfirmed_winning_leg_is_not_credited_and_marked_unrealized() {
+        let trader = trader_with_config(base_config());
+
+        let trade = make_pending_trade(100.0, 90.0);
+        // Both legs win, but only SOL's sell was confirmed: payout = 1.0 * 100 = 100, profit = 100 - 90 = 10
+        let (profit, fully_realized) = trader.calculate_actual_profit(&trade, LegResult::Won, LegResult::Won, true, false);
+        assert!((profit - 10.0).abs() < 0.001);
+        assert!(!fully_realized);
+    }
+
+    #[tokio::test]
+    async fn concurrent_claim_attempts_only_let_one_caller_settle_a_trade() {
+        let trader = Arc::new(trader_with_config(base_config()));
+        let trade = make_pending_trade(100.0, 90.0);
+        trader.pending_trades.lock().await.insert("period-1".to_string(), trade);
+
+        let (a, b) = tokio::join!(
+            trader.claim_settleable_trades(Duration::from_secs(0)),
+            trader.claim_settleable_trades(Duration::from_secs(0)),
+        );
+
+        // Exactly one of the two concurrent callers should have claimed the
+        // trade; the other must see it already `settling` and get nothing.
+        assert_eq!(a.len() + b.len(), 1);
+
+        let pending = trader.pending_trades.lock().await;
+        assert!(pending.get("period-1").unwrap().settling);
+    }
+
+    #[tokio::test]
+    async fn a_claimed_trade_is_skipped_by_a_later_claim_until_released() {
+        let trader = trader_with_config(base_config());
+        let trade = make_pending_trade(100.0, 90.0);
+        trader.pending_trades.lock().await.insert("period-1".to_string(), trade);
+
+        let first = trader.claim_settleable_trades(Duration::from_secs(0)).await;
+        assert_eq!(first.len(), 1);
+
+        let second = trader.claim_settleable_trades(Duration::from_secs(0)).await;
+        assert!(second.is_empty());
+
+        // Releasing the claim (as check_pending_trades does when a market
+        // hasn't closed yet) makes the trade eligible again.
+        trader.pending_trades.lock().await.get_mut("period-1").unwrap().settling = false;
+        let third = trader.claim_settleable_trades(Duration::from_secs(0)).await;
+        assert_eq!(third.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn claim_settleable_trades_ignores_min_age_gate_when_zero() {
+        let trader = trader_with_config(base_config());
+        let trade = make_pending_trade(100.0, 90.0);
+        trader.pending_trades.lock().await.insert("period-1".to_string(), trade);
+
+        // A brand-new trade wouldn't clear the normal period-boundary min-age
+        // gate, but `Duration::ZERO` (what startup recovery uses) claims it
+        // immediately regardless of age.
+        let normal_gate = trader.claim_settleable_trades(Duration::from_secs(840)).await;
+        assert!(normal_gate.is_empty());
+
+        let recovery_gate = trader.claim_settleable_trades(Duration::ZERO).await;
+        assert_eq!(recovery_gate.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn force_settle_paper_trade_settles_through_the_shared_finalize_path() {
+        let trader = trader_with_config(base_config());
+        let trade = make_pending_trade(100.0, 90.0);
+        trader.pending_trades.lock().await.insert("period-1".to_string(), trade);
+
+        // Both legs win and simulation mode counts them sold: payout = (1.0 +
+        // 1.0) * 100 = 200, profit = 200 - 90 = 110, same math
+        // `finalize_settlement` runs for a normally-polled settlement.
+        trader
+            .force_settle_paper_trade("period-1", LegResult::Won, LegResult::Won)
+            .await
+            .unwrap();
+
+        assert!((*trader.total_profit.lock().await - 110.0).abs() < 0.001);
+        assert_eq!(*trader.wins.lock().await, 1);
+        assert!(!trader.pending_trades.lock().await.contains_key("period-1"));
+    }
+
+    #[tokio::test]
+    async fn force_settle_paper_trade_rejects_an_unknown_trade_key() {
+        let trader = trader_with_config(base_config());
+
+        let result = trader.force_settle_paper_trade("no-such-trade", LegResult::Won, LegResult::Lost).await;
+
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn force_settle_paper_trade_is_rejected_outside_simulation_mode() {
+        let api = Arc::new(PolymarketApi::new(
+            "https://gamma.example".to_string(),
+            "https://clob.example".to_string(),
+            None,
+        ));
+        let trader = Trader::new(api, base_config(), false, None, None, None);
+        trader.pending_trades.lock().await.insert("period-1".to_string(), make_pending_trade(100.0, 90.0));
+
+        let result = trader.force_settle_paper_trade("period-1", LegResult::Won, LegResult::Won).await;
+
+        assert!(result.is_err());
+        // Rejected before touching the pending trade map, so it's still there.
+        assert!(trader.pending_trades.lock().await.contains_key("period-1"));
+    }
+
+    #[tokio::test]
+    async fn force_settle_paper_trade_nets_a_losing_hedge_into_the_main_trades_profit() {
+        let trader = trader_with_config(base_config());
+        let mut trade = make_pending_trade(100.0, 90.0);
+        // Main trade wins both legs (profit = 200 - 90 = 110); hedge is the
+        // opposing outcome, so it loses both legs and its $5 stake is a pure
+        // loss, netting to 110 - 5 = 105.
+        trade.hedge = Some(HedgeLeg {
+            sol_token_id: "hedge-sol".to_string(),
+            btc_token_id: "hedge-btc".to_string(),
+            sol_price: dec!(0.6),
+            btc_price: dec!(0.5),
+            units: 5.0,
+            investment_amount: 5.0,
+        });
+        trader.pending_trades.lock().await.insert("period-1".to_string(), trade);
+
+        trader.force_settle_paper_trade("period-1", LegResult::Won, LegResult::Won).await.unwrap();
+
+        assert!((*trader.total_profit.lock().await - 105.0).abs() < 0.001);
+    }
+
+    #[tokio::test]
+    async fn force_settle_paper_trade_nets_a_winning_hedge_into_the_main_trades_loss() {
+        let trader = trader_with_config(base_config());
+        let mut trade = make_pending_trade(100.0, 90.0);
+        // Main trade loses both legs (profit = -90); hedge is the opposing
+        // outcome, so it wins both legs: payout = (1.0 + 1.0) * 5 - 5 = 5,
+        // netting to -90 + 5 = -85.
+        trade.hedge = Some(HedgeLeg {
+            sol_token_id: "hedge-sol".to_string(),
+            btc_token_id: "hedge-btc".to_string(),
+            sol_price: dec!(0.6),
+            btc_price: dec!(0.5),
+            units: 5.0,
+            investment_amount: 5.0,
+        });
+        trader.pending_trades.lock().await.insert("period-1".to_string(), trade);
+
+        trader.force_settle_paper_trade("period-1", LegResult::Lost, LegResult::Lost).await.unwrap();
+
+        assert!((*trader.total_profit.lock().await - (-85.0)).abs() < 0.001);
+    }
+
+    #[tokio::test]
+    async fn next_settlement_check_delay_idles_when_nothing_is_pending() {
+        let trader = trader_with_config(base_config());
+
+        let delay = trader.next_settlement_check_delay().await;
+
+        assert_eq!(delay, Duration::from_millis(base_config().settlement_idle_check_interval_ms));
+    }
+
+    #[tokio::test]
+    async fn next_settlement_check_delay_sleeps_until_the_soonest_trades_window_opens() {
+        let trader = trader_with_config(base_config());
+        // period_duration_secs=900, so min_age is 840s; a brand-new trade's
+        // window opens ~840s from now.
+        trader.pending_trades.lock().await.insert("period-1".to_string(), make_pending_trade(100.0, 90.0));
+
+        let delay = trader.next_settlement_check_delay().await;
+
+        assert!(delay.as_secs() > 800 && delay.as_secs() <= 840, "expected ~840s, got {:?}", delay);
+    }
+
+    #[tokio::test]
+    async fn next_settlement_check_delay_falls_back_to_the_busy_cadence_once_the_window_is_open() {
+        let trader = trader_with_config(base_config());
+        let mut trade = make_pending_trade(100.0, 90.0);
+        trade.timestamp = unix_now_secs().saturating_sub(900);
+        trader.pending_trades.lock().await.insert("period-1".to_string(), trade);
+
+        let delay = trader.next_settlement_check_delay().await;
+
+        assert_eq!(delay, Duration::from_millis(base_config().settlement_check_interval_ms));
+    }
+
+    #[tokio::test]
+    async fn claim_settleable_trades_uses_wall_clock_so_a_suspended_process_still_ages_trades() {
+        let trader = trader_with_config(base_config());
+        let mut trade = make_pending_trade(100.0, 90.0);
+        // Simulate a laptop suspending for an hour by back-dating the
+        // trade's wall-clock timestamp rather than actually sleeping the
+        // test. An `Instant`-based age would have stayed frozen across a
+        // real suspend; a `unix_now_secs`-based age reflects the gap the
+        // moment the process wakes up.
+        trade.timestamp = unix_now_secs().saturating_sub(3600);
+        trader.pending_trades.lock().await.insert("period-1".to_string(), trade);
+
+        let claimed = trader.claim_settleable_trades(Duration::from_secs(840)).await;
+
+        assert_eq!(claimed.len(), 1);
+    }
+
+    #[test]
+    fn fill_confirmation_recognizes_terminal_statuses() {
+        assert!(Trader::is_fill_confirmed("filled"));
+        assert!(Trader::is_fill_confirmed("MATCHED"));
+        assert!(Trader::is_fill_confirmed("Confirmed"));
+        assert!(!Trader::is_fill_confirmed("PENDING"));
+        assert!(!Trader::is_fill_confirmed("open"));
+    }
+
+    #[test]
+    fn available_ask_depth_sums_only_entries_at_or_below_the_limit_price() {
+        let book = OrderBook {
+            bids: Vec::new(),
+            asks: vec![
+                OrderBookEntry { price: dec!(0.40), size: dec!(50) },
+                OrderBookEntry { price: dec!(0.45), size: dec!(30) },
+                OrderBookEntry { price: dec!(0.50), size: dec!(100) },
+            ],
+        };
+        assert_eq!(available_ask_depth(&book, dec!(0.45)), 80.0);
+        assert_eq!(available_ask_depth(&book, dec!(0.99)), 180.0);
+        assert_eq!(available_ask_depth(&book, dec!(0.10)), 0.0);
+    }
+
+    #[tokio::test]
+    async fn depth_fraction_unit_cap_is_none_when_unconfigured() {
+        let trader = trader_with_config(base_config());
+        let opportunity = make_opportunity(dec!(0.40), dec!(0.35));
+        assert!(trader.depth_fraction_unit_cap(&opportunity).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn meets_min_book_depth_is_true_when_unconfigured() {
+        let trader = trader_with_config(base_config());
+        let opportunity = make_opportunity(dec!(0.40), dec!(0.35));
+        assert!(trader.meets_min_book_depth(&opportunity).await);
+    }
+
+    #[test]
+    fn split_notional_stays_under_the_cap_and_preserves_total() {
+        let slices = split_notional(250.0, 100.0);
+        assert_eq!(slices.len(), 3);
+        assert!(slices.iter().all(|s| *s <= 100.0));
+        assert!((slices.iter().sum::<f64>() - 250.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn split_notional_is_a_no_op_under_the_cap() {
+        assert_eq!(split_notional(50.0, 100.0), vec![50.0]);
+    }
+
+    #[test]
+    fn plan_order_slices_respects_enable_flag() {
+        let mut config = base_config();
+        config.max_order_notional = Some(20.0);
+        config.enable_order_splitting = false;
+        let trader = trader_with_config(config);
+        assert_eq!(trader.plan_order_slices(100.0), vec![100.0]);
+
+        let mut config = base_config();
+        config.max_order_notional = Some(20.0);
+        config.enable_order_splitting = true;
+        let trader = trader_with_config(config);
+        assert_eq!(trader.plan_order_slices(100.0).len(), 5);
+    }
+
+    #[test]
+    fn adversarial_override_is_a_no_op_when_unset() {
+        let trader = trader_with_config(base_config());
+        assert_eq!(trader.apply_adversarial_override(LegResult::Won), LegResult::Won);
+        assert_eq!(trader.apply_adversarial_override(LegResult::Lost), LegResult::Lost);
+    }
+
+    #[test]
+    fn adversarial_override_always_forces_a_loss_at_probability_one() {
+        let mut config = base_config();
+        config.adversarial_loss_probability = Some(1.0);
+        let trader = trader_with_config(config);
+
+        assert_eq!(trader.apply_adversarial_override(LegResult::Won), LegResult::Lost);
+        assert_eq!(trader.apply_adversarial_override(LegResult::Lost), LegResult::Lost);
+    }
+
+    #[test]
+    fn adversarial_override_never_forces_a_loss_at_probability_zero() {
+        let mut config = base_config();
+        config.adversarial_loss_probability = Some(0.0);
+        let trader = trader_with_config(config);
+
+        assert_eq!(trader.apply_adversarial_override(LegResult::Won), LegResult::Won);
+    }
+
+    #[test]
+    fn adversarial_override_never_touches_an_invalid_market_result() {
+        let mut config = base_config();
+        config.adversarial_loss_probability = Some(1.0);
+        let trader = trader_with_config(config);
+
+        assert_eq!(trader.apply_adversarial_override(LegResult::Invalid), LegResult::Invalid);
+    }
+
+    fn cached_market_data_aged(seconds_old: u64) -> CachedMarketData {
+        CachedMarketData {
+            market: dummy_market_details(true, vec![]),
+            cached_at: unix_now_secs().saturating_sub(seconds_old),
+        }
+    }
+
+    #[test]
+    fn evict_stale_market_cache_entries_removes_entries_past_max_age() {
+        let mut cache = HashMap::new();
+        cache.insert("fresh".to_string(), cached_market_data_aged(10));
+        cache.insert("stale".to_string(), cached_market_data_aged(7200));
+
+        evict_stale_market_cache_entries(&mut cache, unix_now_secs(), 3600);
+
+        assert!(cache.contains_key("fresh"));
+        assert!(!cache.contains_key("stale"));
+    }
+
+    #[test]
+    fn evict_stale_market_cache_entries_keeps_everything_under_a_generous_max_age() {
+        let mut cache = HashMap::new();
+        cache.insert("a".to_string(), cached_market_data_aged(10));
+        cache.insert("b".to_string(), cached_market_data_aged(20));
+
+        evict_stale_market_cache_entries(&mut cache, unix_now_secs(), 3600);
+
+        assert_eq!(cache.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn record_pending_creates_a_fresh_entry_for_a_new_key() {
+        let trader = trader_with_config(base_config());
+        let opportunity = make_opportunity(dec!(0.4), dec!(0.5));
+
+        trader.record_pending("period-1".to_string(), &opportunity, 10.0, 9.0).await;
+
+        let pending = trader.pending_trades.lock().await;
+        let trade = pending.get("period-1").unwrap();
+        assert_eq!(trade.units, 10.0);
+        assert_eq!(trade.investment_amount, 9.0);
+    }
+
+    #[tokio::test]
+    async fn record_pending_scales_expected_profit_by_units_and_accumulates_it() {
+        let trader = trader_with_config(base_config());
+        // total_cost = 0.9, so expected_profit per unit = 0.1.
+        let opportunity = make_opportunity(dec!(0.4), dec!(0.5));
+
+        trader.record_pending("period-1".to_string(), &opportunity, 10.0, 9.0).await;
+        trader.record_pending("period-1".to_string(), &opportunity, 5.0, 4.5).await;
+
+        let pending = trader.pending_trades.lock().await;
+        let trade = pending.get("period-1").unwrap();
+        assert!((trade.expected_profit - 1.5).abs() < 0.0001);
+    }
+
+    #[tokio::test]
+    async fn record_pending_leaves_hedge_unset_when_tail_hedge_fraction_is_disabled() {
+        let trader = trader_with_config(base_config());
+        let opportunity = make_opportunity_with_hedge(
+            dec!(0.4),
+            dec!(0.5),
+            HedgeCandidate { sol_token_id: "hedge-sol".to_string(), sol_price: dec!(0.6), btc_token_id: "hedge-btc".to_string(), btc_price: dec!(0.5) },
+        );
+
+        trader.record_pending("period-1".to_string(), &opportunity, 10.0, 9.0).await;
+
+        let pending = trader.pending_trades.lock().await;
+        assert!(pending.get("period-1").unwrap().hedge.is_none());
+    }
+
+    #[tokio::test]
+    async fn record_pending_sizes_a_hedge_from_the_trade_expected_profit_when_enabled() {
+        let mut config = base_config();
+        config.tail_hedge_fraction = Some(0.5);
+        let trader = trader_with_config(config);
+        // total_cost = 0.9, expected_profit per unit = 0.1, so 10 units'
+        // expected profit is $1.00 and half of that ($0.50) funds the hedge.
+        let opportunity = make_opportunity_with_hedge(
+            dec!(0.4),
+            dec!(0.5),
+            HedgeCandidate { sol_token_id: "hedge-sol".to_string(), sol_price: dec!(0.6), btc_token_id: "hedge-btc".to_string(), btc_price: dec!(0.5) },
+        );
+
+        trader.record_pending("period-1".to_string(), &opportunity, 10.0, 9.0).await;
+
+        let pending = trader.pending_trades.lock().await;
+        let hedge = pending.get("period-1").unwrap().hedge.as_ref().unwrap();
+        assert!((hedge.investment_amount - 0.5).abs() < 0.0001);
+        assert!((hedge.units - 0.5 / 1.1).abs() < 0.0001);
+        assert_eq!(hedge.sol_token_id, "hedge-sol");
+        assert_eq!(hedge.btc_token_id, "hedge-btc");
+    }
+
+    #[tokio::test]
+    async fn record_pending_accumulates_hedge_units_across_repeated_calls_for_the_same_key() {
+        let mut config = base_config();
+        config.tail_hedge_fraction = Some(0.5);
+        let trader = trader_with_config(config);
+        let opportunity = make_opportunity_with_hedge(
+            dec!(0.4),
+            dec!(0.5),
+            HedgeCandidate { sol_token_id: "hedge-sol".to_string(), sol_price: dec!(0.6), btc_token_id: "hedge-btc".to_string(), btc_price: dec!(0.5) },
+        );
+
+        trader.record_pending("period-1".to_string(), &opportunity, 10.0, 9.0).await;
+        trader.record_pending("period-1".to_string(), &opportunity, 10.0, 9.0).await;
+
+        let pending = trader.pending_trades.lock().await;
+        let hedge = pending.get("period-1").unwrap().hedge.as_ref().unwrap();
+        assert!((hedge.investment_amount - 1.0).abs() < 0.0001);
+    }
+
+    #[tokio::test]
+    async fn record_pending_does_not_hedge_an_opportunity_with_no_hedge_candidate() {
+        let mut config = base_config();
+        config.tail_hedge_fraction = Some(0.5);
+        let trader = trader_with_config(config);
+        let opportunity = make_opportunity(dec!(0.4), dec!(0.5));
+
+        trader.record_pending("period-1".to_string(), &opportunity, 10.0, 9.0).await;
+
+        let pending = trader.pending_trades.lock().await;
+        assert!(pending.get("period-1").unwrap().hedge.is_none());
+    }
+
+    #[test]
+    fn entry_fee_for_position_is_zero_when_no_fee_is_configured() {
+        assert_eq!(entry_fee_for_position(100.0, 0.0), 0.0);
+    }
+
+    #[test]
+    fn entry_fee_for_position_charges_the_configured_basis_points() {
+        // 10 bps on $100 = $0.10
+        assert_eq!(entry_fee_for_position(100.0, 10.0), 0.1);
+    }
+
+    #[tokio::test]
+    async fn record_pending_folds_the_configured_taker_fee_into_investment_amount() {
+        let mut config = base_config();
+        config.taker_fee_bps = 100.0; // 1%
+        let trader = trader_with_config(config);
+        let opportunity = make_opportunity(dec!(0.4), dec!(0.5));
+
+        trader.record_pending("period-1".to_string(), &opportunity, 10.0, 90.0).await;
+
+        let pending = trader.pending_trades.lock().await;
+        let trade = pending.get("period-1").unwrap();
+        // $90 position + 1% entry fee = $90.90, so simulated profit at
+        // settlement is net of the fee rather than assuming a free entry.
+        assert_eq!(trade.investment_amount, 90.9);
+    }
+
+    #[tokio::test]
+    async fn record_pending_accumulates_repeated_calls_for_the_same_key() {
+        let trader = trader_with_config(base_config());
+        let opportunity = make_opportunity(dec!(0.4), dec!(0.5));
+
+        trader.record_pending("period-1".to_string(), &opportunity, 10.0, 9.0).await;
+        trader.record_pending("period-1".to_string(), &opportunity, 5.0, 4.5).await;
+
+        let pending = trader.pending_trades.lock().await;
+        assert_eq!(pending.len(), 1);
+        let trade = pending.get("period-1").unwrap();
+        assert_eq!(trade.units, 15.0);
+        assert_eq!(trade.investment_amount, 13.5);
+    }
+
+    #[tokio::test]
+    async fn record_pending_keeps_distinct_keys_separate() {
+        let trader = trader_with_config(base_config());
+        let opportunity = make_opportunity(dec!(0.4), dec!(0.5));
+
+        trader.record_pending("period-1".to_string(), &opportunity, 10.0, 9.0).await;
+        trader.record_pending("period-2".to_string(), &opportunity, 3.0, 2.7).await;
+
+        let pending = trader.pending_trades.lock().await;
+        assert_eq!(pending.len(), 2);
+        assert_eq!(pending.get("period-1").unwrap().units, 10.0);
+        assert_eq!(pending.get("period-2").unwrap().units, 3.0);
+    }
+
+    #[tokio::test]
+    async fn pending_trade_count_reflects_distinct_keys() {
+        let trader = trader_with_config(base_config());
+        let opportunity = make_opportunity(dec!(0.4), dec!(0.5));
+        assert_eq!(trader.pending_trade_count().await, 0);
+
+        trader.record_pending("period-1".to_string(), &opportunity, 10.0, 9.0).await;
+        trader.record_pending("period-2".to_string(), &opportunity, 3.0, 2.7).await;
+
+        assert_eq!(trader.pending_trade_count().await, 2);
+    }
+
+    #[tokio::test]
+    async fn wait_for_pending_settlement_returns_immediately_when_nothing_is_pending() {
+        let trader = trader_with_config(base_config());
+
+        let remaining = trader
+            .wait_for_pending_settlement(Duration::from_millis(50), Duration::from_millis(5))
+            .await;
+
+        assert_eq!(remaining, 0);
+    }
+
+    #[tokio::test]
+    async fn wait_for_pending_settlement_times_out_on_a_trade_not_yet_close_to_its_boundary() {
+        let trader = trader_with_config(base_config());
+        let opportunity = make_opportunity(dec!(0.4), dec!(0.5));
+        trader.record_pending("period-1".to_string(), &opportunity, 10.0, 9.0).await;
+
+        // A freshly-recorded trade is nowhere near `check_pending_trades`'
+        // min-age gate, so no amount of polling within this short timeout
+        // settles it.
+        let remaining = trader
+            .wait_for_pending_settlement(Duration::from_millis(30), Duration::from_millis(5))
+            .await;
+
+        assert_eq!(remaining, 1);
+    }
+
+    #[tokio::test]
+    async fn recover_resolved_trades_on_startup_processes_a_batch_larger_than_settlement_concurrency() {
+        let mut config = base_config();
+        config.settlement_concurrency = 2;
+        let trader = trader_with_config(config);
+        let opportunity = make_opportunity(dec!(0.4), dec!(0.5));
+        for i in 0..5 {
+            trader.record_pending(format!("period-{}", i), &opportunity, 10.0, 9.0).await;
+        }
+
+        // Every trade's market lookup fails against the unreachable test API,
+        // which `check_market_result_cached` treats as "not closed yet" -
+        // each claim is released rather than settled. This still exercises
+        // the bounded `buffer_unordered` fan-out across a batch bigger than
+        // `settlement_concurrency` without losing or double-claiming a trade.
+        trader.recover_resolved_trades_on_startup().await.unwrap();
+
+        assert_eq!(trader.pending_trade_count().await, 5);
+        let pending = trader.pending_trades.lock().await;
+        for i in 0..5 {
+            assert!(!pending.get(&format!("period-{}", i)).unwrap().settling);
+        }
+    }
+
+    #[tokio::test]
+    async fn execute_arbitrage_skips_a_midpoint_derived_opportunity_when_trust_is_disabled() {
+        let trader = trader_with_config(base_config());
+        let mut opportunity = make_opportunity(dec!(0.4), dec!(0.5));
+        opportunity.is_midpoint_derived = true;
+
+        let result = trader.execute_arbitrage(&opportunity, None, None).await;
+
+        assert!(result.is_ok());
+        assert_eq!(trader.pending_trade_count().await, 0);
+        let (total_profit, trades_executed) = trader.get_stats().await;
+        assert_eq!(trades_executed, 0);
+        assert_eq!(total_profit, 0.0);
+    }
+
+    #[tokio::test]
+    async fn execute_arbitrage_skips_during_the_post_rollover_grace_period() {
+        let mut config = base_config();
+        config.post_rollover_grace_ms = 5_000;
+        let trader = trader_with_config(config);
+        let opportunity = make_opportunity(dec!(0.4), dec!(0.5));
+
+        let result = trader.execute_arbitrage(&opportunity, Some(1_000), None).await;
+
+        assert!(result.is_ok());
+        assert_eq!(trader.pending_trade_count().await, 0);
+    }
+
+    #[tokio::test]
+    async fn execute_arbitrage_skips_a_market_not_yet_accepting_orders() {
+        let trader = trader_with_config(base_config());
+        let opportunity = make_opportunity(dec!(0.4), dec!(0.5));
+
+        let result = trader.execute_arbitrage(&opportunity, None, Some(3)).await;
+
+        assert!(result.is_ok());
+        assert_eq!(trader.pending_trade_count().await, 0);
+    }
+
+    #[test]
+    fn is_in_post_rollover_grace_blocks_only_before_the_grace_elapses() {
+        assert!(is_in_post_rollover_grace(Some(1_000), 5_000));
+        assert!(!is_in_post_rollover_grace(Some(10_000), 5_000));
+    }
+
+    #[test]
+    fn is_in_post_rollover_grace_never_blocks_with_no_rollover_observed() {
+        assert!(!is_in_post_rollover_grace(None, 5_000));
+    }
+
+    #[test]
+    fn is_in_post_rollover_grace_never_blocks_when_disabled() {
+        assert!(!is_in_post_rollover_grace(Some(0), 0));
+    }
+
+    #[tokio::test]
+    async fn record_pending_tallies_the_trade_count_under_its_strategy() {
+        let trader = trader_with_config(base_config());
+        let sol_up = make_opportunity(dec!(0.4), dec!(0.5));
+        let mut sol_down = make_opportunity(dec!(0.3), dec!(0.6));
+        sol_down.strategy = crate::arbitrage::STRATEGY_SOL_DOWN_BTC_UP.to_string();
+
+        trader.record_pending("period-1".to_string(), &sol_up, 10.0, 9.0).await;
+        trader.record_pending("period-2".to_string(), &sol_down, 3.0, 2.7).await;
+        trader.record_pending("period-3".to_string(), &sol_up, 1.0, 0.9).await;
+
+        let stats = trader.get_detailed_stats().await;
+        assert_eq!(stats[STRATEGY_SOL_UP_BTC_DOWN].trades_executed, 2);
+        assert_eq!(stats[STRATEGY_SOL_DOWN_BTC_UP].trades_executed, 1);
+    }
+
+    #[test]
+    fn win_rate_is_zero_with_no_settled_trades() {
+        let stats = StrategyStats::default();
+        assert_eq!(stats.win_rate(), 0.0);
+    }
+
+    #[test]
+    fn win_rate_reflects_the_fraction_of_wins() {
+        let stats = StrategyStats {
+            profit: 10.0,
+            trades_executed: 4,
+            wins: 3,
+            losses: 1,
+            expected_profit: 0.0,
+            profit_divergence: 0.0,
+        };
+        assert!((stats.win_rate() - 0.75).abs() < 0.0001);
+    }
+
+    #[test]
+    fn avg_profit_divergence_is_zero_with_no_settled_trades() {
+        let stats = StrategyStats::default();
+        assert_eq!(stats.avg_profit_divergence(), 0.0);
+    }
+
+    #[test]
+    fn avg_profit_divergence_averages_the_gap_across_settled_trades() {
+        let stats = StrategyStats {
+            profit: 10.0,
+            trades_executed: 2,
+            wins: 1,
+            losses: 1,
+            expected_profit: 20.0,
+            profit_divergence: -10.0,
+        };
+        assert!((stats.avg_profit_divergence() - -5.0).abs() < 0.0001);
+    }
+
+    #[test]
+    fn is_within_trading_windows_allows_anything_when_unconfigured() {
+        assert!(is_within_trading_windows(0, &[]));
+        assert!(is_within_trading_windows(86_399, &[]));
+    }
+
+    #[test]
+    fn is_within_trading_windows_matches_a_same_day_window() {
+        let windows = vec![TradingWindow { start_utc: "13:00".to_string(), end_utc: "21:00".to_string() }];
+        assert!(is_within_trading_windows(14 * 3600, &windows)); // 14:00 UTC
+        assert!(!is_within_trading_windows(10 * 3600, &windows)); // 10:00 UTC
+        assert!(!is_within_trading_windows(21 * 3600, &windows)); // 21:00 UTC, end is exclusive
+    }
+
+    #[test]
+    fn is_within_trading_windows_handles_a_window_crossing_midnight() {
+        let windows = vec![TradingWindow { start_utc: "22:00".to_string(), end_utc: "02:00".to_string() }];
+        assert!(is_within_trading_windows(23 * 3600, &windows)); // 23:00 UTC
+        assert!(is_within_trading_windows(3600, &windows)); // 01:00 UTC
+        assert!(!is_within_trading_windows(12 * 3600, &windows)); // 12:00 UTC
+    }
+
+    #[test]
+    fn is_within_trading_windows_skips_an_unparseable_window() {
+        let windows = vec![TradingWindow { start_utc: "not-a-time".to_string(), end_utc: "02:00".to_string() }];
+        assert!(!is_within_trading_windows(3600, &windows));
+    }
+
+    #[tokio::test]
+    async fn execute_arbitrage_skips_when_outside_the_configured_trading_window() {
+        let mut config = base_config();
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        let current_minute = ((now % 86_400) / 60) as u32;
+        // A one-minute window one hour from now, guaranteed not to include
+        // the current instant.
+        let closed_minute = (current_minute + 60) % 1440;
+        let start = format!("{:02}:{:02}", closed_minute / 60, closed_minute % 60);
+        let end_minute = (closed_minute + 1) % 1440;
+        let end = format!("{:02}:{:02}", end_minute / 60, end_minute % 60);
+        config.trading_windows = vec![TradingWindow { start_utc: start, end_utc: end }];
+
+        let trader = trader_with_config(config);
+        let opportunity = make_opportunity(dec!(0.4), dec!(0.4));
+
+        let result = trader.execute_arbitrage(&opportunity, None, None).await;
+
+        assert!(result.is_ok());
+        assert_eq!(trader.pending_trade_count().await, 0);
+    }
+
+    #[tokio::test]
+    async fn total_deployed_accumulates_across_record_pending_calls() {
+        let trader = trader_with_config(base_config());
+        let opportunity = make_opportunity(dec!(0.4), dec!(0.4));
+
+        trader.record_pending("period-1".to_string(), &opportunity, 10.0, 9.0).await;
+        trader.record_pending("period-2".to_string(), &opportunity, 5.0, 4.5).await;
+
+        assert_eq!(trader.total_deployed().await, 13.5);
+    }
+
+    #[tokio::test]
+    async fn new_seeds_lifetime_totals_from_an_existing_stats_file() {
+        let path = std::env::temp_dir().join(format!(
+            "trader_stats_seed_test_{}.json",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+
+        let stats_file = crate::stats::StatsFile::new(path.clone());
+        stats_file
+            .write(&crate::stats::LifetimeStats {
+                profit: 42.5,
+                trades: 7,
+                wins: 5,
+                losses: 2,
+                deployed: 300.0,
+                updated_at_secs: 100,
+            })
+            .await
+            .unwrap();
+
+        let api = Arc::new(PolymarketApi::new(
+            "https://gamma.example".to_string(),
+            "https://clob.example".to_string(),
+            None,
+        ));
+        let trader = Trader::new(api, base_config(), true, None, Some(path.clone()), None);
+
+        let (total_profit, trades_executed) = trader.get_stats().await;
+        assert_eq!(total_profit, 42.5);
+        assert_eq!(trades_executed, 7);
+        assert_eq!(trader.total_deployed().await, 300.0);
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[tokio::test]
+    async fn new_starts_from_zero_when_no_stats_path_is_configured() {
+        let trader = trader_with_config(base_config());
+
+        let (total_profit, trades_executed) = trader.get_stats().await;
+        assert_eq!(total_profit, 0.0);
+        assert_eq!(trades_executed, 0);
+        assert_eq!(trader.total_deployed().await, 0.0);
+    }
+
+    #[tokio::test]
+    async fn execute_arbitrage_stops_entering_trades_once_the_lifetime_cap_is_reached() {
+        let mut config = base_config();
+        config.max_lifetime_deployed = Some(5.0);
+        let trader = trader_with_config(config);
+        let opportunity = make_opportunity(dec!(0.4), dec!(0.4));
+
+        // Manually push total_deployed past the cap without going through
+        // execute_arbitrage, to isolate the gate from position sizing.
+        trader.record_pending("period-1".to_string(), &opportunity, 10.0, 10.0).await;
+        assert_eq!(trader.total_deployed().await, 10.0);
+
+        let result = trader.execute_arbitrage(&opportunity, None, None).await;
+
+        assert!(result.is_ok());
+        // No new trade for period-2's key - the cap blocked entry before
+        // sizing/last-look logic ran.
+        assert_eq!(trader.pending_trade_count().await, 1);
+    }
+
+    #[tokio::test]
+    async fn execute_arbitrage_skips_new_trades_once_trading_is_halted() {
+        let trader = trader_with_config(base_config());
+        let opportunity = make_opportunity(dec!(0.4), dec!(0.4));
+        trader.trading_halted.store(true, Ordering::Relaxed);
+
+        let result = trader.execute_arbitrage(&opportunity, None, None).await;
+
+        assert!(result.is_ok());
+        assert_eq!(trader.pending_trade_count().await, 0);
+    }
+
+    // Hand-rolled `SharedStateBackend` test double so `execute_arbitrage`'s
+    // reservation/release wiring can be exercised without a real
+    // `FileSharedState` on disk.
+    struct FakeSharedState {
+        grant_reservations: bool,
+        reserve_calls: Mutex<u64>,
+        release_calls: Mutex<u64>,
+    }
+
+    impl FakeSharedState {
+        fn new(grant_reservations: bool) -> Self {
+            Self {
+                grant_reservations,
+                reserve_calls: Mutex::new(0),
+                release_calls: Mutex::new(0),
+            }
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl SharedStateBackend for FakeSharedState {
+        async fn try_reserve(&self, _trade_key: &str, _amount: f64, _max_shared_deployed: f64) -> std::result::Result<bool, crate::error::SharedStateError> {
+            *self.reserve_calls.lock().await += 1;
+            Ok(self.grant_reservations)
+        }
+
+        async fn release_claim(&self, _trade_key: &str) -> std::result::Result<(), crate::error::SharedStateError> {
+            *self.release_calls.lock().await += 1;
+            Ok(())
+        }
+    }
+
+    fn trader_with_shared_state(config: TradingConfig, shared_state: Arc<dyn SharedStateBackend>) -> Trader {
+        let api = Arc::new(PolymarketApi::new(
+            "https://gamma.example".to_string(),
+            "https://clob.example".to_string(),
+            None,
+        ));
+        Trader::new(api, config, true, None, None, Some(shared_state))
+    }
+
+    #[tokio::test]
+    async fn reserve_shared_state_budget_is_true_when_unconfigured() {
+        let trader = trader_with_config(base_config());
+        let opportunity = make_opportunity(dec!(0.4), dec!(0.4));
+
+        assert!(trader.reserve_shared_state_budget(&opportunity).await);
+    }
+
+    #[tokio::test]
+    async fn reserve_shared_state_budget_is_false_when_the_backend_denies_the_reservation() {
+        let backend = Arc::new(FakeSharedState::new(false));
+        let trader = trader_with_shared_state(base_config(), backend.clone());
+        let opportunity = make_opportunity(dec!(0.4), dec!(0.4));
+
+        let reserved = trader.reserve_shared_state_budget(&opportunity).await;
+
+        assert!(!reserved);
+        assert_eq!(*backend.reserve_calls.lock().await, 1);
+    }
+
+    #[tokio::test]
+    async fn reserve_shared_state_budget_is_true_when_the_backend_grants_the_reservation() {
+        let backend = Arc::new(FakeSharedState::new(true));
+        let trader = trader_with_shared_state(base_config(), backend.clone());
+        let opportunity = make_opportunity(dec!(0.4), dec!(0.4));
+
+        let reserved = trader.reserve_shared_state_budget(&opportunity).await;
+
+        assert!(reserved);
+        assert_eq!(*backend.reserve_calls.lock().await, 1);
+    }
+
+    #[tokio::test]
+    async fn force_settle_paper_trade_releases_the_shared_state_claim() {
+        let backend = Arc::new(FakeSharedState::new(true));
+        let trader = trader_with_shared_state(base_config(), backend.clone());
+        let opportunity = make_opportunity(dec!(0.4), dec!(0.4));
+
+        trader.simulate_trade(&opportunity).await.unwrap();
+        assert_eq!(trader.pending_trade_count().await, 1);
+
+        let trade_key = trade_key_for(&opportunity);
+        trader
+            .force_settle_paper_trade(&trade_key, LegResult::Won, LegResult::Lost)
+            .await
+            .unwrap();
+
+        assert_eq!(trader.pending_trade_count().await, 0);
+        assert_eq!(*backend.release_calls.lock().await, 1);
+    }
+
+    // Hand-rolled `PriceSource` test double so `execute_real_trade`'s
+    // validation/fill accounting can be exercised without a real CLOB -
+    // `validate_order` fails the first `fail_first_n_calls` calls (both legs
+    // of the earliest slices), then passes every call after that, so a test
+    // can construct a trade where only some slices actually get placed.
+    struct FakePriceSource {
+        validate_calls: Mutex<u32>,
+        fail_first_n_calls: u32,
+        fill_price: Decimal,
+    }
+
+    impl FakePriceSource {
+        fn new(fail_first_n_calls: u32, fill_price: Decimal) -> Self {
+            Self {
+                validate_calls: Mutex::new(0),
+                fail_first_n_calls,
+                fill_price,
+            }
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl PriceSource for FakePriceSource {
+        async fn get_market(&self, _condition_id: &str) -> std::result::Result<MarketDetails, ApiError> {
+            Err(ApiError::Config("FakePriceSource does not support get_market".to_string()))
+        }
+
+        async fn get_price(&self, _token_id: &str, _side: &str) -> std::result::Result<Decimal, ApiError> {
+            Err(ApiError::Config("FakePriceSource does not support get_price".to_string()))
+        }
+
+        async fn get_midpoint(&self, _token_id: &str) -> std::result::Result<Decimal, ApiError> {
+            Err(ApiError::Config("FakePriceSource does not support get_midpoint".to_string()))
+        }
+
+        async fn get_last_trade_price(&self, _token_id: &str) -> std::result::Result<Decimal, ApiError> {
+            Err(ApiError::Config("FakePriceSource does not support get_last_trade_price".to_string()))
+        }
+
+        async fn get_best_price(&self, _token_id: &str) -> std::result::Result<Option<TokenPrice>, ApiError> {
+            Ok(None)
+        }
+
+        async fn place_order(&self, _order: &OrderRequest) -> std::result::Result<OrderResponse, ApiError> {
+            Ok(OrderResponse {
+                order_id: Some("fake-order".to_string()),
+                status: "FILLED".to_string(),
+                message: None,
+                avg_fill_price: Some(self.fill_price),
+            })
+        }
+
+        async fn validate_order(&self, _order: &OrderRequest) -> std::result::Result<OrderValidation, ApiError> {
+            let mut calls = self.validate_calls.lock().await;
+            *calls += 1;
+            let valid = *calls > self.fail_first_n_calls;
+            Ok(OrderValidation {
+                valid,
+                reason: (!valid).then(|| "fake rejection".to_string()),
+            })
+        }
+
+        async fn get_positions(&self) -> std::result::Result<Vec<Position>, ApiError> {
+            Ok(Vec::new())
+        }
+
+        async fn get_trade_history(&self) -> std::result::Result<Vec<TradeHistoryEntry>, ApiError> {
+            Ok(Vec::new())
+        }
+
+        async fn get_order_status(&self, _order_id: &str) -> std::result::Result<OrderResponse, ApiError> {
+            Err(ApiError::Config("FakePriceSource does not support get_order_status".to_string()))
+        }
+    }
+
+    fn trader_with_price_source(config: TradingConfig, api: Arc<dyn PriceSource>) -> Trader {
+        Trader::new(api, config, true, None, None, None)
+    }
+
+    #[tokio::test]
+    async fn execute_real_trade_records_only_the_slices_that_passed_validation() {
+        let mut config = base_config();
+        config.validate_orders_before_placement = true;
+        config.enable_order_splitting = true;
+        config.max_order_notional = Some(50.0);
+        config.max_position_size = 100.0;
+        // total_cost 0.75 -> split_notional(100, 50) plans two $50 slices of
+        // ~66.67 units each. Fail validation for the first slice's pair of
+        // legs (2 calls), pass for the second slice's pair.
+        let opportunity = make_opportunity(dec!(0.40), dec!(0.35));
+        let (full_units, full_position_size) = {
+            let trader = trader_with_config(base_config());
+            trader.calculate_units(&opportunity).await
+        };
+
+        let api: Arc<dyn PriceSource> = Arc::new(FakePriceSource::new(2, dec!(0.375)));
+        let trader = trader_with_price_source(config, api);
+
+        let result = trader.execute_real_trade(&opportunity).await;
+
+        assert!(result.is_ok());
+        assert_eq!(trader.pending_trade_count().await, 1);
+        let pending = trader.pending_trades.lock().await;
+        let trade = pending.values().next().unwrap();
+        // Only the second, validated slice was actually entered - not the
+        // full pre-slicing target computed from `calculate_units`.
+        assert!(trade.units > 0.0 && trade.units < full_units);
+        assert!(trade.investment_amount > 0.0 && trade.investment_amount < full_position_size);
+    }
+
+    #[tokio::test]
+    async fn execute_real_trade_records_nothing_when_every_slice_fails_validation() {
+        let mut config = base_config();
+        config.validate_orders_before_placement = true;
+        let opportunity = make_opportunity(dec!(0.40), dec!(0.35));
+
+        let api: Arc<dyn PriceSource> = Arc::new(FakePriceSource::new(u32::MAX, dec!(0.375)));
+        let trader = trader_with_price_source(config, api);
+
+        let result = trader.execute_real_trade(&opportunity).await;
+
+        assert!(result.is_ok());
+        assert_eq!(trader.pending_trade_count().await, 0);
+    }
+
+    #[test]
+    fn check_fill_slippage_is_a_noop_when_unconfigured() {
+        let trader = trader_with_config(base_config());
+        trader.check_fill_slippage("SOL Up", dec!(0.5), dec!(0.9));
+        assert!(!trader.trading_halted.load(Ordering::Relaxed));
+    }
+
+    #[test]
+    fn check_fill_slippage_warns_but_does_not_halt_by_default() {
+        let mut config = base_config();
+        config.max_fill_slippage_pct = Some(0.05);
+        let trader = trader_with_config(config);
+
+        trader.check_fill_slippage("SOL Up", dec!(0.5), dec!(0.6));
+
+        assert!(!trader.trading_halted.load(Ordering::Relaxed));
+    }
+
+    #[test]
+    fn check_fill_slippage_does_not_flag_a_fill_within_tolerance() {
+        let mut config = base_config();
+        config.max_fill_slippage_pct = Some(0.05);
+        config.halt_trading_on_slippage_breach = true;
+        let trader = trader_with_config(config);
+
+        trader.check_fill_slippage("SOL Up", dec!(0.5), dec!(0.51));
+
+        assert!(!trader.trading_halted.load(Ordering::Relaxed));
+    }
+
+    #[test]
+    fn check_fill_slippage_halts_trading_when_configured_and_breached() {
+        let mut config = base_config();
+        config.max_fill_slippage_pct = Some(0.05);
+        config.halt_trading_on_slippage_breach = true;
+        let trader = trader_with_config(config);
+
+        trader.check_fill_slippage("SOL Up", dec!(0.5), dec!(0.6));
+
+        assert!(trader.trading_halted.load(Ordering::Relaxed));
+    }
+
+    #[tokio::test]
+    async fn record_pending_with_fills_records_the_realized_fill_price_on_a_new_trade() {
+        let trader = trader_with_config(base_config());
+        let opportunity = make_opportunity(dec!(0.4), dec!(0.4));
+
+        trader
+            .record_pending_with_fills("period-1".to_string(), &opportunity, 10.0, 4.0, Some(dec!(0.41)), Some(dec!(0.42)))
+            .await;
+
+        let pending = trader.pending_trades.lock().await;
+        let trade = pending.get("period-1").unwrap();
+        assert_eq!(trade.sol_fill_price, Some(dec!(0.41)));
+        assert_eq!(trade.btc_fill_price, Some(dec!(0.42)));
+    }
+
+    #[tokio::test]
+    async fn record_pending_with_fills_folds_a_second_call_into_a_units_weighted_average() {
+        let trader = trader_with_config(base_config());
+        let opportunity = make_opportunity(dec!(0.4), dec!(0.4));
+
+        trader
+            .record_pending_with_fills("period-1".to_string(), &opportunity, 10.0, 4.0, Some(dec!(0.40)), None)
+            .await;
+        trader
+            .record_pending_with_fills("period-1".to_string(), &opportunity, 10.0, 4.0, Some(dec!(0.60)), None)
+            .await;
+
+        let pending = trader.pending_trades.lock().await;
+        let trade = pending.get("period-1").unwrap();
+        assert_eq!(trade.sol_fill_price, Some(dec!(0.50)));
+    }
+
+    #[test]
+    fn weighted_average_fill_price_keeps_a_known_value_over_an_unknown_one() {
+        assert_eq!(weighted_average_fill_price(Some(dec!(0.5)), 10.0, None, 5.0), Some(dec!(0.5)));
+        assert_eq!(weighted_average_fill_price(None, 10.0, Some(dec!(0.5)), 5.0), Some(dec!(0.5)));
+        assert_eq!(weighted_average_fill_price(None, 10.0, None, 5.0), None);
+    }
+
+    #[test]
+    fn weighted_average_fill_price_weights_by_units() {
+        let avg = weighted_average_fill_price(Some(dec!(0.4)), 30.0, Some(dec!(0.8)), 10.0).unwrap();
+        assert_eq!(avg, dec!(0.5));
+    }
+
+    #[tokio::test]
+    async fn sample_trade_always_executes_at_rate_one() {
+        let trader = trader_with_config(base_config());
+        for _ in 0..10 {
+            assert!(trader.sample_trade().await);
+        }
+    }
+
+    #[tokio::test]
+    async fn sample_trade_never_executes_at_rate_zero() {
+        let mut config = base_config();
+        config.trade_sample_rate = 0.0;
+        let trader = trader_with_config(config);
+        for _ in 0..10 {
+            assert!(!trader.sample_trade().await);
+        }
+    }
+
+    #[tokio::test]
+    async fn simulation_mode_ignores_trade_sample_rate() {
+        // trade_sample_rate only gates the real-trade branch of
+        // execute_arbitrage; simulate_trade itself never consults it, so a
+        // simulated trade always runs regardless of the configured rate.
+        let mut config = base_config();
+        config.trade_sample_rate = 0.0;
+        let trader = trader_with_config(config);
+        let opportunity = make_opportunity(dec!(0.4), dec!(0.4));
+
+        let result = trader.simulate_trade(&opportunity).await;
+
+        assert!(result.is_ok());
+        assert_eq!(trader.pending_trade_count().await, 1);
+        assert_eq!(trader.trades_skipped_by_sampling(), 0);
+    }
+
+    #[tokio::test]
+    async fn simulate_trade_previews_orders_through_the_same_slicing_as_a_real_trade() {
+        // build_leg_orders/plan_order_slices are shared with
+        // execute_real_trade, so this exercises the same multi-slice order
+        // construction production would use - simulate_trade should still
+        // succeed and record one pending trade regardless of how many
+        // preview slices that produces.
+        let mut config = base_config();
+        config.max_order_notional = Some(20.0);
+        config.enable_order_splitting = true;
+        let trader = trader_with_config(config);
+        let opportunity = make_opportunity(dec!(0.4), dec!(0.4));
+
+        let result = trader.simulate_trade(&opportunity).await;
+
+        assert!(result.is_ok());
+        assert_eq!(trader.pending_trade_count().await, 1);
+    }
+}